@@ -1,4 +1,9 @@
+//! Throughput benchmark of the indexable `DataLoader`.
+#![allow(missing_docs)]
+
+use ai_dataloader::collate::PooledCollate;
 use ai_dataloader::indexable::DataLoader;
+use ai_dataloader::sampler::SequentialSampler;
 use ai_dataloader::{Dataset, GetSample, Len};
 use criterion::{criterion_group, criterion_main, Criterion, Throughput};
 use ndarray::Array3;
@@ -10,6 +15,7 @@ const IMAGE_SIZE: usize = 50;
 const DATASET_LEN: usize = 500;
 
 /// Dataset that return the same random image each time.
+#[derive(Debug)]
 pub struct RandomUnique {
     image: Array3<u8>,
 }
@@ -34,28 +40,49 @@ impl GetSample for RandomUnique {
     type Sample = (Array3<u8>, i32);
 
     fn get_sample(&self, index: usize) -> Self::Sample {
-        (self.image.clone(), (index % NUM_CLASS) as i32)
+        (
+            self.image.clone(),
+            i32::try_from(index % NUM_CLASS).unwrap(),
+        )
     }
 }
 
 fn iter_all_dataset(loader: &DataLoader<RandomUnique>) -> usize {
     let mut num_sample = 0;
-    for (_sample, label) in loader.iter() {
+    for (_sample, label) in loader {
+        num_sample += label.len();
+    }
+    num_sample
+}
+
+fn iter_all_dataset_pooled(
+    loader: &DataLoader<RandomUnique, SequentialSampler, PooledCollate>,
+) -> usize {
+    let mut num_sample = 0;
+    for (_sample, label) in loader {
         num_sample += label.len();
     }
     num_sample
 }
 
 fn bench(c: &mut Criterion) {
+    const BYTES: u64 = DATASET_LEN as u64 * IMAGE_SIZE as u64 * IMAGE_SIZE as u64 * 3;
+
     let loader = DataLoader::builder(RandomUnique::default())
         .batch_size(16)
         .build();
 
-    const BYTES: u64 = DATASET_LEN as u64 * IMAGE_SIZE as u64 * IMAGE_SIZE as u64 * 3;
+    let pooled_loader = DataLoader::builder(RandomUnique::default())
+        .batch_size(16)
+        .collate_fn(PooledCollate::new())
+        .build();
 
     let mut group = c.benchmark_group("throughput-example");
     group.throughput(Throughput::Bytes(BYTES));
     group.bench_function("iter_all_dataset", |b| b.iter(|| iter_all_dataset(&loader)));
+    group.bench_function("iter_all_dataset_pooled", |b| {
+        b.iter(|| iter_all_dataset_pooled(&pooled_loader));
+    });
     group.finish();
 }
 
@@ -1,3 +1,5 @@
+//! Iterable `DataLoader` over a tokenized text dataset.
+
 use ai_dataloader::iterable::DataLoader;
 
 #[cfg(not(feature = "tch"))]
@@ -2,7 +2,20 @@
 //!
 
 mod default_collate;
-pub use default_collate::DefaultCollate;
+#[cfg(feature = "tfrecord")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tfrecord")))]
+pub use default_collate::FeatureBatch;
+pub use default_collate::{CollateRef, DefaultCollate};
+
+mod pooled_collate;
+pub use pooled_collate::{BufferPool, PooledArray, PooledCollate};
+
+#[cfg(feature = "arrow")]
+#[cfg_attr(docsrs, doc(cfg(feature = "arrow")))]
+mod columns_collate;
+#[cfg(feature = "arrow")]
+#[cfg_attr(docsrs, doc(cfg(feature = "arrow")))]
+pub use columns_collate::ColumnsCollate;
 
 #[cfg(feature = "tch")]
 #[cfg_attr(docsrs, doc(cfg(feature = "tch")))]
@@ -11,6 +24,20 @@ mod torch_collate;
 #[cfg_attr(docsrs, doc(cfg(feature = "tch")))]
 pub use torch_collate::TorchCollate;
 
+#[cfg(feature = "candle")]
+#[cfg_attr(docsrs, doc(cfg(feature = "candle")))]
+mod candle_collate;
+#[cfg(feature = "candle")]
+#[cfg_attr(docsrs, doc(cfg(feature = "candle")))]
+pub use candle_collate::CandleCollate;
+
+#[cfg(feature = "burn")]
+#[cfg_attr(docsrs, doc(cfg(feature = "burn")))]
+mod batcher_collate;
+#[cfg(feature = "burn")]
+#[cfg_attr(docsrs, doc(cfg(feature = "burn")))]
+pub use batcher_collate::BatcherCollate;
+
 /// Any collate gather samples from one batch together.
 ///
 /// A `DefaultCollate` struct is provided which will cover most of the use cases.
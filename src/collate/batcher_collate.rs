@@ -0,0 +1,62 @@
+use super::Collate;
+use burn::{data::dataloader::batcher::Batcher, tensor::backend::Backend};
+use std::{fmt, marker::PhantomData};
+
+/// Collate function wrapping a `burn` [`Batcher`], which creates the batches of output `O` on a device of the backend
+/// `B`.
+///
+/// It lets burn models be fed by the loaders of this crate, with their samplers and their parallel fetching, for
+/// instance from a burn dataset wrapped in a [`FromBurn`](crate::indexable::FromBurn).
+pub struct BatcherCollate<Bt, B: Backend, O> {
+    /// The wrapped batcher.
+    batcher: Bt,
+    /// Device on which the batches are created.
+    device: B::Device,
+    _output: PhantomData<fn() -> O>,
+}
+
+impl<Bt, B: Backend, O> BatcherCollate<Bt, B, O> {
+    /// Wrap a batcher creating its batches on `device`.
+    pub fn new(batcher: Bt, device: B::Device) -> Self {
+        Self {
+            batcher,
+            device,
+            _output: PhantomData,
+        }
+    }
+
+    /// Return the device on which the batches are created.
+    pub fn device(&self) -> &B::Device {
+        &self.device
+    }
+
+    /// Return a reference to the wrapped batcher.
+    pub fn get_ref(&self) -> &Bt {
+        &self.batcher
+    }
+}
+
+impl<Bt: Clone, B: Backend, O> Clone for BatcherCollate<Bt, B, O> {
+    fn clone(&self) -> Self {
+        Self::new(self.batcher.clone(), self.device.clone())
+    }
+}
+
+impl<Bt, B: Backend, O> fmt::Debug for BatcherCollate<Bt, B, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BatcherCollate")
+            .field("device", &self.device)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<Bt, B, I, O> Collate<I> for BatcherCollate<Bt, B, O>
+where
+    Bt: Batcher<B, I, O>,
+    B: Backend,
+{
+    type Output = O;
+    fn collate(&self, batch: Vec<I>) -> Self::Output {
+        self.batcher.batch(batch, &self.device)
+    }
+}
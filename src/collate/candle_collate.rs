@@ -0,0 +1,100 @@
+use candle_core::{DType, Device, Tensor, WithDType};
+
+/// Candle Collate function that mimic the [`default_collate` function](https://pytorch.org/docs/stable/data.html#automatic-batching-default) from ``PyTorch``.
+///
+/// Data is collated inside a `candle` [`Tensor`], created on the device of the collate function (the CPU by default)
+/// and optionally converted to a given [`DType`]. Unlike the `TorchCollate`, it doesn't need libtorch.
+///
+///
+/// Basic transformation implemented for the candle Collate :
+///
+/// - `Vec<Scalar>` -> `candle_core::Tensor<scalar>`
+/// - `Vec<tuple>` -> `tuple(Tensor)`
+/// - `Vec<HashMap<Key, Value>>` -> `HasMap<Key, CandleCollate::collate(Vec<Value>)`
+/// - `Vec<Array>` or `Vec<ArrayView>` -> `Stacked Tensor`
+/// - `Vec[V1_i, V2_i, ...]` -> `Vec[CandleCollate::collate([V1_1, V1_2, ...]), CandleCollate::collate([V2_1, V2_2, ...]), ...]`
+///
+/// `i8`, `i16` and `i32` are widened to `i64`, `u16` to `u32` and `bool` to `u8`, the closest types supported by
+/// `candle`.
+///
+/// Like for `PyTorch` version, `String` and `u8` aren't changed by the collation (No Op).
+///
+/// - `Vec<String>` -> `Vec<String>`
+/// - `Vec<&str>` -> `Vec<&str>`
+/// - `Vec<u8>` -> `Vec<u8>`
+///
+/// ```
+/// use ai_dataloader::{collate::CandleCollate, indexable::DataLoader};
+/// use candle_core::{DType, Device};
+///
+/// let loader = DataLoader::builder(vec![(0_i64, 1.5_f64), (1, 2.5), (0, 3.5), (1, 4.5)])
+///     .batch_size(2)
+///     .collate_fn(CandleCollate::new(Device::Cpu).dtype(DType::F32))
+///     .build();
+/// for (label, value) in &loader {
+///     assert_eq!(label.dims(), [2]);
+///     assert_eq!(value.dtype(), DType::F32);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CandleCollate {
+    /// Device on which the tensors are created.
+    device: Device,
+    /// Type the tensors are converted to, if set.
+    dtype: Option<DType>,
+}
+
+impl Default for CandleCollate {
+    fn default() -> Self {
+        Self::new(Device::Cpu)
+    }
+}
+
+impl CandleCollate {
+    /// Create a collate function creating the tensors on `device`.
+    #[must_use]
+    pub fn new(device: Device) -> Self {
+        Self {
+            device,
+            dtype: None,
+        }
+    }
+
+    /// Convert all the tensors to `dtype`. By default the tensors keep the type of the samples.
+    #[must_use]
+    pub fn dtype(mut self, dtype: DType) -> Self {
+        self.dtype = Some(dtype);
+        self
+    }
+
+    /// Return the device on which the tensors are created.
+    #[must_use]
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Create a tensor of the given shape on the device, converted to the dtype.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tensor can't be created on the device or converted.
+    fn tensor<T: WithDType>(&self, data: Vec<T>, shape: &[usize]) -> Tensor {
+        let tensor = Tensor::from_vec(data, shape, &self.device)
+            .unwrap_or_else(|err| panic!("could not create the batch tensor: {err}"));
+        match self.dtype {
+            Some(dtype) if dtype != tensor.dtype() => tensor
+                .to_dtype(dtype)
+                .unwrap_or_else(|err| panic!("could not convert the batch tensor: {err}")),
+            _ => tensor,
+        }
+    }
+}
+
+mod array;
+mod map;
+mod ndarray;
+mod primitive;
+mod reference;
+mod sequence;
+mod string;
+mod tuple;
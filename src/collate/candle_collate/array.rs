@@ -0,0 +1,31 @@
+use super::super::Collate;
+use super::CandleCollate;
+
+impl<T, const N: usize> Collate<[T; N]> for CandleCollate
+where
+    Self: Collate<T>,
+    T: Clone,
+{
+    type Output = Vec<<Self as Collate<T>>::Output>;
+    fn collate(&self, batch: Vec<[T; N]>) -> Self::Output {
+        let mut collated = Vec::with_capacity(batch.len());
+        for i in 0..batch[0].len() {
+            let vec: Vec<_> = batch.iter().map(|sample| sample[i].clone()).collect();
+            collated.push(self.collate(vec));
+        }
+        collated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vec_of_array() {
+        let collated = CandleCollate::default().collate(vec![[1_i64, 2], [3, 4], [5, 6]]);
+        assert_eq!(collated.len(), 2);
+        assert_eq!(collated[0].to_vec1::<i64>().unwrap(), [1, 3, 5]);
+        assert_eq!(collated[1].to_vec1::<i64>().unwrap(), [2, 4, 6]);
+    }
+}
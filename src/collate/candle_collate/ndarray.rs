@@ -0,0 +1,66 @@
+use super::super::Collate;
+use super::CandleCollate;
+use candle_core::{Tensor, WithDType};
+use ndarray::{stack, Array, ArrayBase, ArrayView, Axis, Dimension, RemoveAxis};
+
+impl<A, D> Collate<Array<A, D>> for CandleCollate
+where
+    A: Clone + WithDType,
+    D: Dimension,
+    D::Larger: RemoveAxis,
+{
+    type Output = Tensor;
+    fn collate(&self, batch: Vec<Array<A, D>>) -> Self::Output {
+        // Convert it to a `Vec` of view.
+        let vec_of_view: Vec<ArrayView<'_, A, D>> = batch.iter().map(ArrayBase::view).collect();
+        self.collate(vec_of_view)
+    }
+}
+
+/// Views are stacked straight into the tensor, so [`DataLoader::iter_ref`](crate::indexable::DataLoader::iter_ref)
+/// can collate the arrays borrowed from the dataset.
+impl<'a, A, D> Collate<ArrayView<'a, A, D>> for CandleCollate
+where
+    A: Clone + WithDType,
+    D: Dimension,
+    D::Larger: RemoveAxis,
+{
+    type Output = Tensor;
+    fn collate(&self, batch: Vec<ArrayView<'a, A, D>>) -> Self::Output {
+        let array = stack(Axis(0), batch.as_slice())
+            .expect("Make sure your items from the dataset have the same shape.");
+        let shape = array.shape().to_vec();
+        // The stacked array is in standard layout, so its raw elements are in logical order.
+        self.tensor(array.into_raw_vec(), &shape)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    #[test]
+    fn keep_dimension() {
+        let batch = CandleCollate::default().collate(vec![array![1_i64, 2], array![3, 4]]);
+        assert_eq!(batch.dims(), [2, 2]);
+        assert_eq!(batch.to_vec2::<i64>().unwrap(), [[1, 2], [3, 4]]);
+
+        let batch = CandleCollate::default().collate(vec![
+            array![[1_f32, 2., 3.], [4., 5., 6.]],
+            array![[7., 8., 9.], [10., 11., 12.]],
+        ]);
+        assert_eq!(batch.dims(), [2, 2, 3]);
+        assert_eq!(
+            batch.to_vec3::<f32>().unwrap()[1],
+            [[7., 8., 9.], [10., 11., 12.]]
+        );
+    }
+
+    #[test]
+    fn views() {
+        let arrays = [array![1_f32, 2.], array![3., 4.]];
+        let batch = CandleCollate::default().collate(arrays.iter().map(ArrayBase::view).collect());
+        assert_eq!(batch.to_vec2::<f32>().unwrap(), [[1., 2.], [3., 4.]]);
+    }
+}
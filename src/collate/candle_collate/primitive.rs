@@ -0,0 +1,76 @@
+use super::super::Collate;
+use super::CandleCollate;
+use candle_core::Tensor;
+
+macro_rules! primitive_impl {
+    ($($t:ty)*) => {
+        $(
+            impl Collate<$t> for CandleCollate {
+                type Output = Tensor;
+                fn collate(&self, batch: Vec<$t>) -> Self::Output {
+                    let len = batch.len();
+                    self.tensor(batch, &[len])
+                }
+            }
+        )*
+    };
+}
+primitive_impl!(u32 i64 f32 f64);
+
+/// Types without a `candle` equivalent, widened to the given type.
+macro_rules! widened_impl {
+    ($($t:ty => $wide:ty)*) => {
+        $(
+            impl Collate<$t> for CandleCollate {
+                type Output = Tensor;
+                fn collate(&self, batch: Vec<$t>) -> Self::Output {
+                    let len = batch.len();
+                    self.tensor(batch.into_iter().map(<$wide>::from).collect(), &[len])
+                }
+            }
+        )*
+    };
+}
+widened_impl!(
+    i8 => i64
+    i16 => i64
+    i32 => i64
+    u16 => u32
+    bool => u8
+);
+
+// char i128 isize usize u64 u128 are not compatible with `candle_core::Tensor`.
+
+/// `NoOp` for binary, as pytorch `default_collate` function.
+impl Collate<u8> for CandleCollate {
+    type Output = Vec<u8>;
+    fn collate(&self, batch: Vec<u8>) -> Self::Output {
+        batch
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candle_core::DType;
+
+    #[test]
+    fn scalar_type() {
+        let tensor = CandleCollate::default().collate(vec![0_i64, 1, 2, 3, 4, 5]);
+        assert_eq!(tensor.dtype(), DType::I64);
+        assert_eq!(tensor.to_vec1::<i64>().unwrap(), [0, 1, 2, 3, 4, 5]);
+        let tensor = CandleCollate::default().collate(vec![0_f64, 1., 2., 3., 4., 5.]);
+        assert_eq!(tensor.to_vec1::<f64>().unwrap(), [0., 1., 2., 3., 4., 5.]);
+        let tensor = CandleCollate::default().collate(vec![true, false]);
+        assert_eq!(tensor.to_vec1::<u8>().unwrap(), [1, 0]);
+    }
+
+    #[test]
+    fn dtype() {
+        let tensor = CandleCollate::default()
+            .dtype(DType::F32)
+            .collate(vec![1_i32, 2, 3]);
+        assert_eq!(tensor.dtype(), DType::F32);
+        assert_eq!(tensor.to_vec1::<f32>().unwrap(), [1., 2., 3.]);
+    }
+}
@@ -0,0 +1,16 @@
+use super::CandleCollate;
+use crate::collate::Collate;
+
+/// A tensor can't hold references, so the referenced samples are cloned before being collated.
+/// It is useful for having a non-consuming `Iterator` over the `Dataloader`. To collate borrowed arrays without
+/// cloning them, the dataset can hand out `ArrayView` through `GetSampleRef`.
+impl<T> Collate<&T> for CandleCollate
+where
+    T: Clone,
+    Self: Collate<T>,
+{
+    type Output = <Self as Collate<T>>::Output;
+    fn collate(&self, batch: Vec<&T>) -> Self::Output {
+        self.collate(batch.into_iter().cloned().collect())
+    }
+}
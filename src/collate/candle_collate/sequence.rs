@@ -0,0 +1,90 @@
+/// Implementation for Sequence.
+///
+/// Currently `BinaryHeap`, `BTreeSet`, `HashSet` and `LinkedList` are not supported because the current implementation
+/// require indexing for doing the transpose.
+///
+use super::super::Collate;
+use super::CandleCollate;
+use std::collections::VecDeque;
+
+impl<T> Collate<Vec<T>> for CandleCollate
+where
+    Self: Collate<T>,
+    T: Clone,
+{
+    type Output = Vec<<Self as Collate<T>>::Output>;
+    fn collate(&self, batch: Vec<Vec<T>>) -> Self::Output {
+        let elem_size = batch
+            .first()
+            .expect("Batch should contain at least one element")
+            .len();
+
+        assert!(
+            batch.iter().all(|vec| vec.len() == elem_size),
+            "Each Vec in the batch should have equal size"
+        );
+
+        let mut collated = Vec::with_capacity(batch.len());
+
+        for i in 0..batch[0].len() {
+            let vec: Vec<_> = batch.iter().map(|sample| sample[i].clone()).collect();
+            collated.push(self.collate(vec));
+        }
+        collated
+    }
+}
+
+impl<T> Collate<VecDeque<T>> for CandleCollate
+where
+    Self: Collate<T>,
+    T: Clone,
+{
+    type Output = Vec<<Self as Collate<T>>::Output>;
+    fn collate(&self, batch: Vec<VecDeque<T>>) -> Self::Output {
+        let elem_size = batch
+            .first()
+            .expect("Batch should contain at least one element")
+            .len();
+
+        assert!(
+            batch.iter().all(|vec| vec.len() == elem_size),
+            "Each Vec in the batch should have equal size"
+        );
+
+        let mut collated = Vec::with_capacity(batch.len());
+
+        for i in 0..batch[0].len() {
+            let vec: Vec<_> = batch.iter().map(|sample| sample[i].clone()).collect();
+            collated.push(self.collate(vec));
+        }
+        collated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vec_of_vec() {
+        let collated = CandleCollate::default().collate(vec![vec![1_i64, 2, 3], vec![4, 5, 6]]);
+        let collated: Vec<_> = collated
+            .iter()
+            .map(|tensor| tensor.to_vec1::<i64>().unwrap())
+            .collect();
+        assert_eq!(collated, [[1, 4], [2, 5], [3, 6]]);
+
+        let collated = CandleCollate::default().collate(vec![
+            VecDeque::from([true, false]),
+            VecDeque::from([true, true]),
+        ]);
+        assert_eq!(collated[0].to_vec1::<u8>().unwrap(), [1, 1]);
+        assert_eq!(collated[1].to_vec1::<u8>().unwrap(), [0, 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Each Vec in the batch should have equal size")]
+    fn ragged() {
+        let _ = CandleCollate::default().collate(vec![vec![1, 2], vec![3]]);
+    }
+}
@@ -0,0 +1,56 @@
+use super::super::Collate;
+use super::CandleCollate;
+use std::ffi::{CStr, CString, OsString};
+
+impl Collate<String> for CandleCollate {
+    type Output = Vec<String>;
+    fn collate(&self, batch: Vec<String>) -> Self::Output {
+        batch
+    }
+}
+
+impl<'a> Collate<&'a str> for CandleCollate {
+    type Output = Vec<&'a str>;
+    fn collate(&self, batch: Vec<&'a str>) -> Self::Output {
+        batch
+    }
+}
+
+impl Collate<CString> for CandleCollate {
+    type Output = Vec<CString>;
+    fn collate(&self, batch: Vec<CString>) -> Self::Output {
+        batch
+    }
+}
+
+impl<'a> Collate<&'a CStr> for CandleCollate {
+    type Output = Vec<&'a CStr>;
+    fn collate(&self, batch: Vec<&'a CStr>) -> Self::Output {
+        batch
+    }
+}
+
+impl Collate<OsString> for CandleCollate {
+    type Output = Vec<OsString>;
+    fn collate(&self, batch: Vec<OsString>) -> Self::Output {
+        batch
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_op() {
+        assert_eq!(
+            CandleCollate::default().collate(vec![String::from("a"), String::from("b")]),
+            vec![String::from("a"), String::from("b")]
+        );
+
+        assert_eq!(
+            CandleCollate::default().collate(vec!["a", "b"]),
+            vec!["a", "b"]
+        );
+    }
+}
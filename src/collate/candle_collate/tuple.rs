@@ -0,0 +1,76 @@
+use super::super::Collate;
+use super::CandleCollate;
+use itertools::Itertools;
+
+// Maybe an implementation passing the length and the index of elements to the macro could be more efficient than with the
+// `Iterttols::multiunzip`.
+
+/// `tuple` implementation, up to 16 elements.
+macro_rules! tuple_impl {
+    ($($name:ident)+) => {
+        impl<$($name),+> Collate<($($name,)+)> for CandleCollate
+        where
+            $($name: Clone,)+
+            $(CandleCollate: Collate<$name>,)+
+
+        {
+            type Output = ($(<CandleCollate as Collate<$name>>::Output,)+);
+
+            #[allow(non_snake_case)]
+            fn collate(&self, batch: Vec<($($name,)+)>) -> Self::Output {
+                let copy = batch.to_vec();
+                let ($($name,)+) = copy.into_iter().multiunzip();
+                (
+                    $(self.collate($name),)+
+                )
+
+            }
+        }
+    };
+}
+
+tuple_impl! { A }
+tuple_impl! { A B }
+tuple_impl! { A B C }
+tuple_impl! { A B C D }
+tuple_impl! { A B C D E }
+tuple_impl! { A B C D E F }
+tuple_impl! { A B C D E F G }
+tuple_impl! { A B C D E F G H }
+tuple_impl! { A B C D E F G H I }
+tuple_impl! { A B C D E F G H I J }
+tuple_impl! { A B C D E F G H I J K }
+tuple_impl! { A B C D E F G H I J K L }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candle_core::{DType, Device};
+    use ndarray::array;
+
+    #[test]
+    fn vec_of_tuple() {
+        let (labels, values) =
+            CandleCollate::default().collate(vec![(1_i64, 2.0_f64), (3, 4.0), (5, 6.0)]);
+        assert_eq!(labels.to_vec1::<i64>().unwrap(), [1, 3, 5]);
+        assert_eq!(values.to_vec1::<f64>().unwrap(), [2.0, 4.0, 6.0]);
+
+        let (labels,) = CandleCollate::default().collate(vec![(-1_i64,)]);
+        assert_eq!(labels.to_vec1::<i64>().unwrap(), [-1]);
+    }
+
+    #[test]
+    fn nested() {
+        // The device and the dtype are used for the nested tensors.
+        let collate = CandleCollate::new(Device::Cpu).dtype(DType::F32);
+        let (images, (labels, names)) = collate.collate(vec![
+            (array![1_u32, 2], (0_i32, "a")),
+            (array![3, 4], (1, "b")),
+        ]);
+        assert_eq!(images.dtype(), DType::F32);
+        assert_eq!(images.to_vec2::<f32>().unwrap(), [[1., 2.], [3., 4.]]);
+        assert_eq!(labels.to_vec1::<f32>().unwrap(), [0., 1.]);
+        assert_eq!(names, ["a", "b"]);
+        assert!(collate.device().is_cpu());
+    }
+}
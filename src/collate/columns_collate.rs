@@ -0,0 +1,168 @@
+use super::Collate;
+use arrow::{
+    array::{Array, AsArray},
+    compute::{cast_with_options, CastOptions},
+    datatypes::{ArrowPrimitiveType, DataType},
+    record_batch::RecordBatch,
+};
+use ndarray::{ArrayD, IxDyn};
+use std::{collections::HashMap, fmt, marker::PhantomData, sync::Arc};
+
+/// Cast failing on values that can't be represented in the target type, instead of replacing them by nulls.
+const STRICT_CAST: CastOptions<'static> = CastOptions {
+    safe: false,
+    format_options: arrow::util::display::FormatOptions::new(),
+};
+
+/// Collate Arrow record batches into one `ndarray` per column, indexed by the column name.
+///
+/// Every column is cast to the Arrow primitive type `T`, and the values are copied straight from the rows
+/// of the batch into the output array, without an intermediate [`RecordBatch`].
+///
+/// - primitive columns give an array of shape `[batch_size]`,
+/// - fixed size list columns (embeddings for instance) give an array of shape `[batch_size, list_size]`.
+///
+/// The collation panics if a column can't be cast to `T` or contains null values.
+///
+/// ```
+/// use ai_dataloader::collate::{Collate, ColumnsCollate};
+/// use arrow::{array::Int32Array, datatypes::Float32Type, record_batch::RecordBatch};
+/// use ndarray::array;
+/// use std::sync::Arc;
+///
+/// let row = |age| RecordBatch::try_from_iter([("age", Arc::new(Int32Array::from(vec![age])) as _)]).unwrap();
+/// let columns = ColumnsCollate::<Float32Type>::new().collate(vec![row(32), row(54)]);
+/// assert_eq!(columns["age"], array![32., 54.].into_dyn());
+/// ```
+pub struct ColumnsCollate<T> {
+    _type: PhantomData<fn() -> T>,
+}
+
+impl<T> ColumnsCollate<T> {
+    /// Create a new [`ColumnsCollate`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self { _type: PhantomData }
+    }
+}
+
+impl<T> Default for ColumnsCollate<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for ColumnsCollate<T> {
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for ColumnsCollate<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ColumnsCollate").finish()
+    }
+}
+
+impl<T: ArrowPrimitiveType> Collate<RecordBatch> for ColumnsCollate<T> {
+    type Output = HashMap<String, ArrayD<T::Native>>;
+    fn collate(&self, batch: Vec<RecordBatch>) -> Self::Output {
+        let schema = batch
+            .first()
+            .expect("Batch should contain at least one element")
+            .schema();
+        let num_rows = batch.iter().map(RecordBatch::num_rows).sum();
+
+        let mut collated = HashMap::with_capacity(schema.fields().len());
+        for (i, field) in schema.fields().iter().enumerate() {
+            let (list_size, target) = match field.data_type() {
+                DataType::FixedSizeList(_, size) => (
+                    Some(usize::try_from(*size).expect("list size is positive")),
+                    DataType::FixedSizeList(
+                        arrow::datatypes::Field::new_list_field(T::DATA_TYPE, false).into(),
+                        *size,
+                    ),
+                ),
+                _ => (None, T::DATA_TYPE),
+            };
+
+            let mut values = Vec::with_capacity(num_rows * list_size.unwrap_or(1));
+            for record_batch in &batch {
+                let column = cast_with_options(record_batch.column(i), &target, &STRICT_CAST)
+                    .unwrap_or_else(|err| panic!("column `{}` can't be cast: {err}", field.name()));
+                assert_eq!(
+                    column.null_count(),
+                    0,
+                    "column `{}` contains null values",
+                    field.name()
+                );
+                let column = match list_size {
+                    Some(_) => Arc::clone(column.as_fixed_size_list().values()),
+                    None => column,
+                };
+                values.extend_from_slice(column.as_primitive::<T>().values());
+            }
+
+            let shape = match list_size {
+                Some(size) => IxDyn(&[num_rows, size]),
+                None => IxDyn(&[num_rows]),
+            };
+            let array = ArrayD::from_shape_vec(shape, values).expect("shape match the values");
+            collated.insert(field.name().clone(), array);
+        }
+        collated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::{
+        array::{FixedSizeListArray, Float32Array, Int64Array},
+        datatypes::{Float64Type, Int32Type},
+    };
+    use ndarray::array;
+    use std::sync::Arc;
+
+    fn record_batch(ids: Vec<i64>, embeddings: Vec<[f32; 2]>) -> RecordBatch {
+        let embeddings = FixedSizeListArray::new(
+            arrow::datatypes::Field::new_list_field(DataType::Float32, false).into(),
+            2,
+            Arc::new(Float32Array::from_iter_values(
+                embeddings.into_iter().flatten(),
+            )),
+            None,
+        );
+        RecordBatch::try_from_iter([
+            ("id", Arc::new(Int64Array::from(ids)) as _),
+            ("embedding", Arc::new(embeddings) as _),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn columns() {
+        let batch = vec![
+            record_batch(vec![0, 1], vec![[0., 0.5], [1., 1.5]]),
+            // Sliced batches, as yielded by `ParquetDataset`.
+            record_batch(vec![2, 3], vec![[2., 2.5], [3., 3.5]]).slice(1, 1),
+        ];
+        let collated = ColumnsCollate::<Float64Type>::new().collate(batch);
+        assert_eq!(collated["id"], array![0., 1., 3.].into_dyn());
+        assert_eq!(
+            collated["embedding"],
+            array![[0., 0.5], [1., 1.5], [3., 3.5]].into_dyn()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "can't be cast")]
+    fn invalid_cast() {
+        let batch = RecordBatch::try_from_iter([(
+            "name",
+            Arc::new(arrow::array::StringArray::from(vec!["a"])) as _,
+        )])
+        .unwrap();
+        let _ = ColumnsCollate::<Int32Type>::new().collate(vec![batch]);
+    }
+}
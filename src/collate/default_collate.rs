@@ -12,6 +12,7 @@
 /// - `Vec<HashMap<Key, Value>>` -> `HasMap<Key, DefaultCollate::default().collate(Vec<Value>)`
 /// - `Vec<Array>` -> `Vec<Stack Array>`
 /// - `Vec[V1_i, V2_i, ...]` -> `Vec[DefaultCollate::default().collate([V1_1, V1_2, ...]), DefaultCollate::default().collate([V2_1, V2_2, ...]), ...]`
+/// - `Vec<&T>` -> collated like `Vec<T>` for a `T` implementing [`CollateRef`], without cloning the arrays
 ///
 ///
 /// Like for `PyTorch` version, `String` and `u8` aren't changed by the collation (No Op).
@@ -19,17 +20,29 @@
 /// - `Vec<String>` -> `Vec<String>`
 /// - `Vec<&str>` -> `Vec<&str>`
 /// - `Vec<u8>` -> `Vec<u8>`
+/// - `Vec<RecordBatch>` -> `RecordBatch` (with the `arrow` feature)
+/// - `Vec<tfrecord::Feature>` -> `FeatureBatch` (with the `tfrecord` feature)
+/// - `Vec<serde_json::Value>` -> `Vec<serde_json::Value>` (with the `json` feature)
 ///
 ///
 #[derive(Default, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct DefaultCollate;
 
 mod array;
+#[cfg(feature = "arrow")]
+mod arrow;
+#[cfg(feature = "json")]
+mod json;
 mod map;
 mod ndarray;
 mod nonzero;
 mod primitive;
 mod reference;
+pub use reference::CollateRef;
 mod sequence;
 mod string;
+#[cfg(feature = "tfrecord")]
+mod tfrecord;
+#[cfg(feature = "tfrecord")]
+pub use tfrecord::FeatureBatch;
 mod tuple;
@@ -1,5 +1,5 @@
 use super::super::Collate;
-use super::DefaultCollate;
+use super::{CollateRef, DefaultCollate};
 
 impl<T, const N: usize> Collate<[T; N]> for DefaultCollate
 where
@@ -17,6 +17,13 @@ where
     }
 }
 
+impl<T: CollateRef, const N: usize> CollateRef for [T; N] {
+    type Output = Vec<T::Output>;
+    fn collate_ref(batch: Vec<&Self>) -> Self::Output {
+        <[T]>::collate_ref(batch.into_iter().map(<[T; N]>::as_slice).collect())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,56 @@
+use super::super::Collate;
+use super::{CollateRef, DefaultCollate};
+use arrow::{compute::concat_batches, record_batch::RecordBatch};
+
+/// Arrow record batches are concatenated into a single one, keeping their columnar layout.
+impl Collate<RecordBatch> for DefaultCollate {
+    type Output = RecordBatch;
+    fn collate(&self, batch: Vec<RecordBatch>) -> Self::Output {
+        let schema = batch
+            .first()
+            .expect("Batch should contain at least one element")
+            .schema();
+        concat_batches(&schema, &batch)
+            .expect("Make sure you're record batches have the same schema.")
+    }
+}
+
+/// Record batches borrowed from the dataset are concatenated without cloning them first.
+impl CollateRef for RecordBatch {
+    type Output = RecordBatch;
+    fn collate_ref(batch: Vec<&Self>) -> Self::Output {
+        let schema = batch
+            .first()
+            .expect("Batch should contain at least one element")
+            .schema();
+        concat_batches(&schema, batch)
+            .expect("Make sure you're record batches have the same schema.")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Int32Array, StringArray};
+    use std::sync::Arc;
+
+    #[test]
+    fn concat() {
+        let row = |id, name| {
+            RecordBatch::try_from_iter([
+                ("id", Arc::new(Int32Array::from(vec![id])) as _),
+                ("name", Arc::new(StringArray::from(vec![name])) as _),
+            ])
+            .unwrap()
+        };
+        let expected = RecordBatch::try_from_iter([
+            ("id", Arc::new(Int32Array::from(vec![0, 1])) as _),
+            ("name", Arc::new(StringArray::from(vec!["a", "b"])) as _),
+        ])
+        .unwrap();
+        assert_eq!(
+            DefaultCollate.collate(vec![row(0, "a"), row(1, "b")]),
+            expected
+        );
+    }
+}
@@ -0,0 +1,25 @@
+use super::super::Collate;
+use super::DefaultCollate;
+use serde_json::Value;
+
+/// `NoOp` for dynamic JSON values, as their structure can vary from one sample to another.
+impl Collate<Value> for DefaultCollate {
+    type Output = Vec<Value>;
+    fn collate(&self, batch: Vec<Value>) -> Self::Output {
+        batch
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn no_op() {
+        assert_eq!(
+            DefaultCollate.collate(vec![json!({"a": 1}), json!([2])]),
+            vec![json!({"a": 1}), json!([2])]
+        );
+    }
+}
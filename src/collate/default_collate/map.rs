@@ -1,5 +1,5 @@
 use super::super::Collate;
-use super::DefaultCollate;
+use super::{CollateRef, DefaultCollate};
 use std::{
     cmp::Eq,
     collections::{BTreeMap, HashMap},
@@ -40,6 +40,38 @@ where
     }
 }
 
+impl<K, V, H> CollateRef for HashMap<K, V, H>
+where
+    K: Eq + Hash + Clone,
+    V: CollateRef,
+    H: BuildHasher,
+{
+    type Output = HashMap<K, V::Output>;
+    fn collate_ref(batch: Vec<&Self>) -> Self::Output {
+        let mut collated = HashMap::with_capacity(batch[0].keys().len());
+        for key in batch[0].keys() {
+            let vec: Vec<_> = batch.iter().map(|hash_map| &hash_map[key]).collect();
+            collated.insert(key.clone(), V::collate_ref(vec));
+        }
+        collated
+    }
+}
+impl<K, V> CollateRef for BTreeMap<K, V>
+where
+    K: Ord + Clone,
+    V: CollateRef,
+{
+    type Output = BTreeMap<K, V::Output>;
+    fn collate_ref(batch: Vec<&Self>) -> Self::Output {
+        let mut collated = BTreeMap::new();
+        for key in batch[0].keys() {
+            let vec: Vec<_> = batch.iter().map(|hash_map| &hash_map[key]).collect();
+            collated.insert(key.clone(), V::collate_ref(vec));
+        }
+        collated
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,5 +1,5 @@
 use super::super::Collate;
-use super::DefaultCollate;
+use super::{CollateRef, DefaultCollate};
 use ndarray::{stack, Array, ArrayBase, ArrayView, Axis, Dimension, RemoveAxis};
 
 impl<A, D> Collate<Array<A, D>> for DefaultCollate
@@ -16,3 +16,33 @@ where
             .expect("Make sure you're items from the dataset have the same shape.")
     }
 }
+
+/// Views are stacked straight into the batch, without copying them into owned arrays first.
+impl<'a, A, D> Collate<ArrayView<'a, A, D>> for DefaultCollate
+where
+    A: Clone,
+    D: Dimension,
+    D::Larger: RemoveAxis,
+{
+    type Output = Array<A, <D as Dimension>::Larger>;
+    fn collate(&self, batch: Vec<ArrayView<'a, A, D>>) -> Self::Output {
+        stack(Axis(0), batch.as_slice())
+            .expect("Make sure you're items from the dataset have the same shape.")
+    }
+}
+
+/// Arrays borrowed from the dataset are stacked from their views, without cloning them.
+impl<A, D> CollateRef for Array<A, D>
+where
+    A: Clone,
+    D: Dimension,
+    D::Larger: RemoveAxis,
+{
+    type Output = Array<A, <D as Dimension>::Larger>;
+    fn collate_ref(batch: Vec<&Self>) -> Self::Output {
+        let vec_of_view: Vec<ArrayView<'_, A, D>> =
+            batch.into_iter().map(ArrayBase::view).collect();
+        stack(Axis(0), vec_of_view.as_slice())
+            .expect("Make sure you're items from the dataset have the same shape.")
+    }
+}
@@ -1,16 +1,97 @@
 use super::DefaultCollate;
 use crate::collate::Collate;
+#[cfg(feature = "tfrecord")]
+use crate::indexable::Feature;
+#[cfg(feature = "json")]
+use serde_json::Value;
+use std::{
+    ffi::{CString, OsString},
+    num::{
+        NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+        NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize,
+    },
+};
 
-/// We think it makes no sense to but a bench of reference into a Tensor. That's why if the dataset yield reference a
-/// we clone them them.
-/// It is useful for having a non-consuming `Iterator` over the `Dataloader`.
-impl<T> Collate<&T> for DefaultCollate
+/// Samples that the [`DefaultCollate`] collates from references, for instance the samples of a `Vec` dataset
+/// iterated with [`DataLoader::iter_ref`](crate::indexable::DataLoader::iter_ref).
+///
+/// Arrays are stacked from their views and containers are transposed into references to their elements, so the
+/// samples aren't cloned. Scalars and strings are cloned, as they end up in an owned batch anyway.
+pub trait CollateRef {
+    /// The type of the collated batch.
+    type Output;
+    /// Take a batch of references to samples and collate them.
+    fn collate_ref(batch: Vec<&Self>) -> Self::Output;
+}
+
+impl<'a, T> Collate<&'a T> for DefaultCollate
 where
-    T: Clone,
-    Self: Collate<T>,
+    T: CollateRef + ?Sized,
 {
-    type Output = <Self as Collate<T>>::Output;
-    fn collate(&self, batch: Vec<&T>) -> Self::Output {
-        DefaultCollate.collate(batch.into_iter().cloned().collect())
+    type Output = T::Output;
+    fn collate(&self, batch: Vec<&'a T>) -> Self::Output {
+        T::collate_ref(batch)
+    }
+}
+
+macro_rules! clone_impl {
+    ($($t:ty)*) => {
+        $(
+            impl CollateRef for $t {
+                type Output = <DefaultCollate as Collate<$t>>::Output;
+                fn collate_ref(batch: Vec<&Self>) -> Self::Output {
+                    DefaultCollate.collate(batch.into_iter().cloned().collect::<Vec<$t>>())
+                }
+            }
+        )*
+    };
+}
+clone_impl!(usize u8 u16 u32 u64 u128
+    isize i8 i16 i32 i64 i128
+    f32 f64
+    bool char
+    String CString OsString);
+clone_impl!(
+    NonZeroUsize NonZeroU8 NonZeroU16 NonZeroU32 NonZeroU64 NonZeroU128
+    NonZeroIsize NonZeroI8 NonZeroI16 NonZeroI32 NonZeroI64 NonZeroI128
+);
+#[cfg(feature = "json")]
+clone_impl!(Value);
+#[cfg(feature = "tfrecord")]
+clone_impl!(Feature);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::{array, Array};
+    use std::collections::HashMap;
+
+    #[test]
+    fn same_as_owned() {
+        assert_eq!(DefaultCollate.collate(vec![&1, &2]), array![1, 2]);
+
+        let samples = vec![
+            (vec![1., 2.], String::from("a")),
+            (vec![3., 4.], String::from("b")),
+        ];
+        assert_eq!(
+            DefaultCollate.collate(samples.iter().collect::<Vec<_>>()),
+            DefaultCollate.collate(samples.clone())
+        );
+
+        let samples = vec![
+            HashMap::from([("x", [array![0, 1], array![2, 3]])]),
+            HashMap::from([("x", [array![4, 5], array![6, 7]])]),
+        ];
+        assert_eq!(
+            DefaultCollate.collate(samples.iter().collect::<Vec<_>>()),
+            DefaultCollate.collate(samples.clone())
+        );
+
+        let slices: [&[Array<i32, _>]; 2] = [&[array![0]], &[array![1]]];
+        assert_eq!(
+            DefaultCollate.collate(slices.to_vec()),
+            vec![array![[0], [1]]]
+        );
     }
 }
@@ -4,7 +4,7 @@
 /// require indexing for doing the transpose.
 ///
 use super::super::Collate;
-use super::DefaultCollate;
+use super::{CollateRef, DefaultCollate};
 use std::collections::VecDeque;
 
 impl<T> Collate<Vec<T>> for DefaultCollate
@@ -61,6 +61,52 @@ where
     }
 }
 
+/// Sequences borrowed from the dataset are transposed into references to their elements.
+impl<T: CollateRef> CollateRef for [T] {
+    type Output = Vec<T::Output>;
+    fn collate_ref(batch: Vec<&Self>) -> Self::Output {
+        let elem_size = batch
+            .first()
+            .expect("Batch should contain at least one element")
+            .len();
+
+        assert!(
+            batch.iter().all(|vec| vec.len() == elem_size),
+            "Each Vec in the batch should have equal size"
+        );
+
+        (0..elem_size)
+            .map(|i| T::collate_ref(batch.iter().map(|sample| &sample[i]).collect()))
+            .collect()
+    }
+}
+
+impl<T: CollateRef> CollateRef for Vec<T> {
+    type Output = Vec<T::Output>;
+    fn collate_ref(batch: Vec<&Self>) -> Self::Output {
+        <[T]>::collate_ref(batch.into_iter().map(Vec::as_slice).collect())
+    }
+}
+
+impl<T: CollateRef> CollateRef for VecDeque<T> {
+    type Output = Vec<T::Output>;
+    fn collate_ref(batch: Vec<&Self>) -> Self::Output {
+        let elem_size = batch
+            .first()
+            .expect("Batch should contain at least one element")
+            .len();
+
+        assert!(
+            batch.iter().all(|vec| vec.len() == elem_size),
+            "Each Vec in the batch should have equal size"
+        );
+
+        (0..elem_size)
+            .map(|i| T::collate_ref(batch.iter().map(|sample| &sample[i]).collect()))
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,116 @@
+use super::super::Collate;
+use super::DefaultCollate;
+use crate::indexable::Feature;
+use ndarray::Array2;
+
+/// A batch of `tf.train.Feature`, collated by the [`DefaultCollate`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum FeatureBatch {
+    /// Bytes lists are kept as is (No Op), as they usually have different lengths.
+    Bytes(Vec<Vec<Vec<u8>>>),
+    /// Float lists stacked in an array of shape `[batch_size, list_len]`.
+    Float(Array2<f32>),
+    /// Int64 lists stacked in an array of shape `[batch_size, list_len]`.
+    Int64(Array2<i64>),
+}
+
+/// Float and int64 lists must have the same kind and length in the whole batch, as in a
+/// `tf.io.FixedLenFeature`.
+impl Collate<Feature> for DefaultCollate {
+    type Output = FeatureBatch;
+    fn collate(&self, batch: Vec<Feature>) -> Self::Output {
+        let batch_size = batch.len();
+        match batch
+            .first()
+            .expect("Batch should contain at least one element")
+        {
+            Feature::Bytes(_) => FeatureBatch::Bytes(
+                batch
+                    .into_iter()
+                    .map(|feature| match feature {
+                        Feature::Bytes(values) => values,
+                        _ => panic!("Each feature in the batch should have the same kind"),
+                    })
+                    .collect(),
+            ),
+            Feature::Float(values) => {
+                let len = values.len();
+                let values = batch
+                    .into_iter()
+                    .flat_map(|feature| match feature {
+                        Feature::Float(values) if values.len() == len => values,
+                        _ => {
+                            panic!("Each feature in the batch should have the same kind and length")
+                        }
+                    })
+                    .collect();
+                FeatureBatch::Float(
+                    Array2::from_shape_vec((batch_size, len), values)
+                        .expect("shape match the values"),
+                )
+            }
+            Feature::Int64(values) => {
+                let len = values.len();
+                let values = batch
+                    .into_iter()
+                    .flat_map(|feature| match feature {
+                        Feature::Int64(values) if values.len() == len => values,
+                        _ => {
+                            panic!("Each feature in the batch should have the same kind and length")
+                        }
+                    })
+                    .collect();
+                FeatureBatch::Int64(
+                    Array2::from_shape_vec((batch_size, len), values)
+                        .expect("shape match the values"),
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+    use std::collections::HashMap;
+
+    #[test]
+    fn features() {
+        assert_eq!(
+            DefaultCollate.collate(vec![Feature::Int64(vec![1, 2]), Feature::Int64(vec![3, 4])]),
+            FeatureBatch::Int64(array![[1, 2], [3, 4]])
+        );
+        assert_eq!(
+            DefaultCollate.collate(vec![Feature::Float(vec![1.]), Feature::Float(vec![2.])]),
+            FeatureBatch::Float(array![[1.], [2.]])
+        );
+        assert_eq!(
+            DefaultCollate.collate(vec![
+                Feature::Bytes(vec![b"a".to_vec()]),
+                Feature::Bytes(vec![b"bc".to_vec(), b"d".to_vec()])
+            ]),
+            FeatureBatch::Bytes(vec![
+                vec![b"a".to_vec()],
+                vec![b"bc".to_vec(), b"d".to_vec()]
+            ])
+        );
+    }
+
+    #[test]
+    fn example() {
+        let example1 = HashMap::from([(String::from("label"), Feature::Int64(vec![0]))]);
+        let example2 = HashMap::from([(String::from("label"), Feature::Int64(vec![1]))]);
+        assert_eq!(
+            DefaultCollate.collate(vec![example1, example2]),
+            HashMap::from([(String::from("label"), FeatureBatch::Int64(array![[0], [1]]))])
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "same kind and length")]
+    fn different_length() {
+        let _ =
+            DefaultCollate.collate(vec![Feature::Float(vec![1.]), Feature::Float(vec![2., 3.])]);
+    }
+}
@@ -1,5 +1,5 @@
 use super::super::Collate;
-use super::DefaultCollate;
+use super::{CollateRef, DefaultCollate};
 use itertools::Itertools;
 
 // Maybe an implementation passing the length and the index of elements to the macro could be more efficient than with the
@@ -10,7 +10,6 @@ macro_rules! tuple_impl {
     ($($name:ident)+) => {
         impl<$($name),+> Collate<($($name,)+)> for DefaultCollate
         where
-            $($name: Clone,)+
             $(DefaultCollate: Collate<$name>,)+
 
         {
@@ -18,14 +17,30 @@ macro_rules! tuple_impl {
 
             #[allow(non_snake_case)]
             fn collate(&self, batch: Vec<($($name,)+)>) -> Self::Output {
-                let copy = batch.to_vec();
-                let ($($name,)+) = copy.into_iter().multiunzip();
+                let ($($name,)+) = batch.into_iter().multiunzip();
                 (
                     $(DefaultCollate::default().collate($name),)+
                 )
 
             }
         }
+
+        impl<$($name),+> CollateRef for ($($name,)+)
+        where
+            $($name: CollateRef,)+
+
+        {
+            type Output = ($($name::Output,)+);
+
+            #[allow(non_snake_case)]
+            fn collate_ref(batch: Vec<&Self>) -> Self::Output {
+                let ($($name,)+) = batch.into_iter().map(|($($name,)+)| ($($name,)+)).multiunzip();
+                (
+                    $($name::collate_ref($name),)+
+                )
+
+            }
+        }
     };
 }
 
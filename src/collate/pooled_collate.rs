@@ -0,0 +1,382 @@
+use super::Collate;
+use itertools::Itertools;
+use ndarray::{Array, Array1, ArrayBase, ArrayView, Dimension};
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fmt,
+    ops::{Deref, DerefMut},
+    sync::{Arc, Mutex, PoisonError},
+};
+
+/// Default maximum number of idle buffers kept for each element type.
+const DEFAULT_MAX_IDLE: usize = 16;
+
+/// Pool of buffers, recycled from one batch to another.
+///
+/// Buffers are stored by element type. Cloning a [`BufferPool`] gives another handle to the same buffers.
+#[derive(Clone)]
+pub struct BufferPool {
+    /// Idle buffers, a `Vec<Vec<A>>` for each element type `A`.
+    buffers: Arc<Mutex<HashMap<TypeId, Box<dyn Any + Send>>>>,
+    /// Maximum number of idle buffers kept for each element type.
+    max_idle: usize,
+}
+
+impl BufferPool {
+    /// Create a new empty [`BufferPool`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_max_idle(DEFAULT_MAX_IDLE)
+    }
+
+    /// Create a new empty [`BufferPool`] keeping at most `max_idle` idle buffers for each element type.
+    /// Buffers given back to a full pool are freed.
+    #[must_use]
+    pub fn with_max_idle(max_idle: usize) -> Self {
+        Self {
+            buffers: Arc::default(),
+            max_idle,
+        }
+    }
+
+    /// Return the number of idle buffers of elements `A` in the pool.
+    pub fn idle<A: 'static>(&self) -> usize {
+        self.buffers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&TypeId::of::<A>())
+            .and_then(|buffers| buffers.downcast_ref::<Vec<Vec<A>>>())
+            .map_or(0, Vec::len)
+    }
+
+    /// Take an empty buffer from the pool, or allocate one, with room for at least `capacity` elements.
+    fn take<A: Send + 'static>(&self, capacity: usize) -> Vec<A> {
+        let buffer = self
+            .buffers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_mut(&TypeId::of::<A>())
+            .and_then(|buffers| buffers.downcast_mut::<Vec<Vec<A>>>())
+            .and_then(Vec::pop);
+        match buffer {
+            Some(mut buffer) => {
+                buffer.clear();
+                buffer.reserve(capacity);
+                buffer
+            }
+            None => Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Give a buffer back to the pool.
+    fn put<A: Send + 'static>(&self, buffer: Vec<A>) {
+        let mut buffers = self.buffers.lock().unwrap_or_else(PoisonError::into_inner);
+        let buffers = buffers
+            .entry(TypeId::of::<A>())
+            .or_insert_with(|| Box::new(Vec::<Vec<A>>::new()))
+            .downcast_mut::<Vec<Vec<A>>>()
+            .expect("buffers are stored by element type");
+        if buffers.len() < self.max_idle {
+            buffers.push(buffer);
+        }
+    }
+}
+
+impl Default for BufferPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for BufferPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufferPool")
+            .field("max_idle", &self.max_idle)
+            .finish_non_exhaustive()
+    }
+}
+
+/// An array whose buffer is given back to its [`BufferPool`] when dropped.
+///
+/// It dereferences to the underlying `Array`.
+pub struct PooledArray<A: Send + 'static, D: Dimension> {
+    /// The array, always `Some` until dropped or taken by [`PooledArray::into_inner`].
+    array: Option<Array<A, D>>,
+    /// The pool the buffer comes from.
+    pool: BufferPool,
+}
+
+impl<A: Send + 'static, D: Dimension> PooledArray<A, D> {
+    /// Take the array out of the guard. Its buffer won't be given back to the pool.
+    #[allow(clippy::missing_panics_doc)]
+    pub fn into_inner(mut self) -> Array<A, D> {
+        self.array.take().expect("array is taken only once")
+    }
+}
+
+impl<A: Send + 'static, D: Dimension> Deref for PooledArray<A, D> {
+    type Target = Array<A, D>;
+    fn deref(&self) -> &Self::Target {
+        self.array.as_ref().expect("array is taken only once")
+    }
+}
+
+impl<A: Send + 'static, D: Dimension> DerefMut for PooledArray<A, D> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.array.as_mut().expect("array is taken only once")
+    }
+}
+
+impl<A: Send + 'static, D: Dimension> Drop for PooledArray<A, D> {
+    fn drop(&mut self) {
+        if let Some(array) = self.array.take() {
+            self.pool.put(array.into_raw_vec());
+        }
+    }
+}
+
+impl<A: Send + 'static + fmt::Debug, D: Dimension> fmt::Debug for PooledArray<A, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<A, D, S> PartialEq<ArrayBase<S, D>> for PooledArray<A, D>
+where
+    A: Send + 'static + PartialEq,
+    D: Dimension,
+    S: ndarray::Data<Elem = A>,
+{
+    fn eq(&self, other: &ArrayBase<S, D>) -> bool {
+        **self == *other
+    }
+}
+
+/// Collate that writes the batches into buffers recycled from a [`BufferPool`], instead of allocating new ones.
+///
+/// It supports the same samples as the [`DefaultCollate`](super::DefaultCollate) for numbers, `ndarray` (owned or
+/// views) and tuples of them, but the arrays of the batch are [`PooledArray`], giving back their buffer to the pool
+/// when dropped. Once the pool is warm, the batches don't allocate anymore.
+///
+/// ```
+/// use ai_dataloader::collate::PooledCollate;
+/// use ai_dataloader::indexable::DataLoader;
+/// use ndarray::Array2;
+///
+/// let dataset = vec![(Array2::<f32>::zeros((4, 4)), 0); 8];
+/// let loader = DataLoader::builder(dataset).batch_size(2).collate_fn(PooledCollate::new()).build();
+///
+/// for (images, labels) in &loader {
+///     assert_eq!(images.shape(), [2, 4, 4]);
+///     // `images` and `labels` buffers are reused by the next batch once dropped here.
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PooledCollate {
+    /// Pool the buffers of the batches are taken from.
+    pool: BufferPool,
+}
+
+impl PooledCollate {
+    /// Create a new [`PooledCollate`] with its own [`BufferPool`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new [`PooledCollate`] taking its buffers from the given pool.
+    #[must_use]
+    pub fn with_pool(pool: BufferPool) -> Self {
+        Self { pool }
+    }
+
+    /// Return the pool the buffers of the batches are taken from.
+    #[must_use]
+    pub fn pool(&self) -> &BufferPool {
+        &self.pool
+    }
+
+    /// Copy the samples into a pooled array of shape `[batch_size, sample_shape...]`.
+    fn stack<A, D>(&self, batch: &[ArrayView<'_, A, D>]) -> PooledArray<A, D::Larger>
+    where
+        A: Clone + Send + 'static,
+        D: Dimension,
+    {
+        let first = batch
+            .first()
+            .expect("Batch should contain at least one element");
+        let mut buffer = self.pool.take(batch.len() * first.len());
+        for sample in batch {
+            assert_eq!(
+                sample.shape(),
+                first.shape(),
+                "Make sure you're items from the dataset have the same shape."
+            );
+            match sample.as_slice() {
+                Some(slice) => buffer.extend_from_slice(slice),
+                None => buffer.extend(sample.iter().cloned()),
+            }
+        }
+
+        let mut shape = D::Larger::zeros(first.ndim() + 1);
+        shape[0] = batch.len();
+        for (axis, &len) in first.shape().iter().enumerate() {
+            shape[axis + 1] = len;
+        }
+        PooledArray {
+            array: Some(Array::from_shape_vec(shape, buffer).expect("shape match the values")),
+            pool: self.pool.clone(),
+        }
+    }
+}
+
+impl<A, D> Collate<Array<A, D>> for PooledCollate
+where
+    A: Clone + Send + 'static,
+    D: Dimension,
+{
+    type Output = PooledArray<A, D::Larger>;
+    fn collate(&self, batch: Vec<Array<A, D>>) -> Self::Output {
+        let views: Vec<_> = batch.iter().map(ArrayBase::view).collect();
+        self.stack(&views)
+    }
+}
+
+impl<'a, A, D> Collate<ArrayView<'a, A, D>> for PooledCollate
+where
+    A: Clone + Send + 'static,
+    D: Dimension,
+{
+    type Output = PooledArray<A, D::Larger>;
+    fn collate(&self, batch: Vec<ArrayView<'a, A, D>>) -> Self::Output {
+        self.stack(&batch)
+    }
+}
+
+macro_rules! primitive_impl {
+    ($($t:ty)*) => {
+        $(
+            impl Collate<$t> for PooledCollate {
+                type Output = PooledArray<$t, ndarray::Ix1>;
+                fn collate(&self, batch: Vec<$t>) -> Self::Output {
+                    let mut buffer = self.pool.take(batch.len());
+                    buffer.extend_from_slice(&batch);
+                    PooledArray {
+                        array: Some(Array1::from_vec(buffer)),
+                        pool: self.pool.clone(),
+                    }
+                }
+            }
+        )*
+    };
+}
+primitive_impl!(usize u8 u16 u32 u64 u128
+    isize i8 i16 i32 i64 i128
+    f32 f64
+    bool char);
+
+macro_rules! tuple_impl {
+    ($($name:ident)+) => {
+        impl<$($name),+> Collate<($($name,)+)> for PooledCollate
+        where
+            $(PooledCollate: Collate<$name>,)+
+        {
+            type Output = ($(<PooledCollate as Collate<$name>>::Output,)+);
+
+            #[allow(non_snake_case)]
+            fn collate(&self, batch: Vec<($($name,)+)>) -> Self::Output {
+                let ($($name,)+) = batch.into_iter().multiunzip();
+                (
+                    $(self.collate($name),)+
+                )
+            }
+        }
+    };
+}
+
+tuple_impl! { A }
+tuple_impl! { A B }
+tuple_impl! { A B C }
+tuple_impl! { A B C D }
+tuple_impl! { A B C D E }
+tuple_impl! { A B C D E F }
+tuple_impl! { A B C D E F G }
+tuple_impl! { A B C D E F G H }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexable::DataLoader;
+    use ndarray::{array, s, Array2};
+
+    #[test]
+    fn recycle_buffers() {
+        let collate = PooledCollate::new();
+        let batch = collate.collate(vec![array![1., 2.], array![3., 4.]]);
+        assert_eq!(batch, array![[1., 2.], [3., 4.]]);
+        let ptr = batch.as_ptr();
+        assert_eq!(collate.pool().idle::<f64>(), 0);
+
+        drop(batch);
+        assert_eq!(collate.pool().idle::<f64>(), 1);
+        let batch = collate.collate(vec![array![5., 6.], array![7., 8.]]);
+        assert_eq!(batch, array![[5., 6.], [7., 8.]]);
+        assert_eq!(batch.as_ptr(), ptr);
+        assert_eq!(collate.pool().idle::<f64>(), 0);
+
+        // Detached arrays are not given back.
+        let array = batch.into_inner();
+        assert_eq!(array, array![[5., 6.], [7., 8.]]);
+        assert_eq!(collate.pool().idle::<f64>(), 0);
+    }
+
+    #[test]
+    fn max_idle() {
+        let collate = PooledCollate::with_pool(BufferPool::with_max_idle(1));
+        let first = collate.collate(vec![1, 2]);
+        let second = collate.collate(vec![3, 4]);
+        drop(first);
+        drop(second);
+        assert_eq!(collate.pool().idle::<i32>(), 1);
+        assert_eq!(collate.pool().idle::<f32>(), 0);
+    }
+
+    #[test]
+    fn views_and_tuples() {
+        let images = Array2::from_shape_fn((4, 3), |(i, j)| i * 3 + j);
+        let collate = PooledCollate::new();
+
+        // Non contiguous views.
+        let batch = collate.collate(vec![images.slice(s![.., 0]), images.slice(s![.., 2])]);
+        assert_eq!(batch, array![[0, 3, 6, 9], [2, 5, 8, 11]]);
+
+        let (images, labels) = collate.collate(vec![(array![1, 2], 0.5), (array![3, 4], 1.5)]);
+        assert_eq!(images, array![[1, 2], [3, 4]]);
+        assert_eq!(labels, array![0.5, 1.5]);
+    }
+
+    #[test]
+    #[should_panic(expected = "same shape")]
+    fn shape_mismatch() {
+        let _ = PooledCollate::new().collate(vec![array![1, 2], array![3]]);
+    }
+
+    #[test]
+    fn loader() {
+        let dataset: Vec<_> = (0..10).map(|i: i32| (array![i, i], i)).collect();
+        let collate = PooledCollate::new();
+        let loader = DataLoader::builder(dataset)
+            .batch_size(3)
+            .collate_fn(collate.clone())
+            .build();
+
+        let mut sum = 0;
+        for (features, labels) in &loader {
+            sum += labels.sum();
+            assert_eq!(features.column(0), *labels);
+        }
+        assert_eq!(sum, 45);
+        assert_eq!(collate.pool().idle::<i32>(), 2);
+    }
+}
@@ -4,6 +4,34 @@ mod dataloader;
 mod dataset;
 mod fetch;
 pub mod sampler;
+mod worker;
 
-pub use dataloader::DataLoader;
-pub use dataset::{Dataset, GetSample, Len, NdarrayDataset};
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub use dataloader::AsyncDataLoaderIter;
+pub use dataloader::{Batch, DataLoader, FetchError, SampleError, TimeoutError};
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub use dataset::AsyncGetSample;
+#[cfg(feature = "cache")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cache")))]
+pub use dataset::DiskCachedDataset;
+#[cfg(feature = "parquet")]
+#[cfg_attr(docsrs, doc(cfg(feature = "parquet")))]
+pub use dataset::ParquetDataset;
+#[cfg(feature = "process")]
+#[cfg_attr(docsrs, doc(cfg(feature = "process")))]
+pub use dataset::{run_worker, ProcessDataset};
+pub use dataset::{
+    CachedDataset, Dataset, GetSample, GetSampleRef, Len, NdarrayDataset, ShardedDataset,
+};
+#[cfg(feature = "tfrecord")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tfrecord")))]
+pub use dataset::{Example, Feature, TfRecordDataset, TfRecordIter};
+#[cfg(feature = "burn")]
+#[cfg_attr(docsrs, doc(cfg(feature = "burn")))]
+pub use dataset::{FromBurn, ToBurn};
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+pub use dataset::{JsonLines, JsonLinesDataset};
+pub use worker::{worker_info, PerWorker, WorkerInfo};
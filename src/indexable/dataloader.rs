@@ -1,13 +1,34 @@
 //! Data loader. Combines a dataset and a sampler, and provides an iterable over the given dataset.
 
-use super::fetch::{Fetcher, MapDatasetFetcher};
+use super::fetch::{MapDatasetFetcher, MapDatasetRefFetcher};
+#[cfg(feature = "rayon")]
+use super::{
+    fetch::{InFlight, OutOfOrder, Timeout},
+    worker::WorkerPool,
+};
 use crate::{
     collate::{Collate, DefaultCollate},
-    sampler::{BatchIterator, BatchSampler, Sampler, SequentialSampler},
-    Dataset, Len,
+    indexable::GetSampleRef,
+    sampler::{epoch_seed, splitmix64, BatchIterator, BatchSampler, Sampler, SequentialSampler},
+    Dataset, Len, Loader, LoaderState,
+};
+#[cfg(feature = "rayon")]
+use rayon::ThreadPool;
+use std::{
+    error::Error,
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
 };
 
+#[cfg(feature = "async")]
+mod async_iter;
 mod builder;
+#[cfg(feature = "async")]
+pub use async_iter::AsyncDataLoaderIter;
 use builder::Builder;
 
 /// Data loader. Combines a dataset and a sampler, and provides an iterable over the given dataset.
@@ -24,14 +45,32 @@ use builder::Builder;
 /// }
 /// ```
 ///
-#[derive(Debug, Clone, PartialEq, PartialOrd, Hash, Eq, Ord)]
+#[derive(Debug, Clone)]
 pub struct DataLoader<D, S = SequentialSampler, C = DefaultCollate> {
-    /// Dataset from which to load the data.
-    dataset: D,
+    /// Dataset from which to load the data, shared with the samples fetched with a timeout.
+    dataset: Arc<D>,
     /// Return a batch of indices at a time.
     batch_sampler: BatchSampler<S>,
     /// Collate function.
     collate_fn: C,
+    /// Seed from which the sampler seed and the generator of each sample are derived.
+    seed: Option<u64>,
+    /// Current epoch, mixed in the sampler seed and the generator of each sample.
+    epoch: u64,
+    /// Number of iterations started in the current epoch, each one being seeded differently.
+    iterations: Iterations,
+    /// Thread pool in which the samples are fetched.
+    #[cfg(feature = "rayon")]
+    thread_pool: Arc<WorkerPool>,
+    /// Maximum duration of the fetch of a batch.
+    #[cfg(feature = "rayon")]
+    timeout: Option<Timeout<D>>,
+    /// Set if the batches are yielded as soon as they are ready, instead of in the order of the sampler.
+    #[cfg(feature = "rayon")]
+    out_of_order: Option<OutOfOrder<D>>,
+    /// Maximum number of samples of an async dataset fetched concurrently.
+    #[cfg(feature = "async")]
+    max_concurrency: Option<usize>,
 }
 
 impl<D> DataLoader<D, SequentialSampler, DefaultCollate>
@@ -45,6 +84,18 @@ where
     }
 }
 
+impl<D> DataLoader<D, SequentialSampler, DefaultCollate>
+where
+    D: GetSampleRef + Len,
+{
+    /// Helper to return a [`DataLoader`] builder for a dataset lending its samples.
+    ///
+    /// Iterate over the resulting dataloader with [`DataLoader::iter_ref`].
+    pub fn builder_ref(dataset: D) -> Builder<D, SequentialSampler, DefaultCollate> {
+        Builder::new_ref(dataset)
+    }
+}
+
 impl<D, S, C> DataLoader<D, S, C>
 where
     D: Dataset + Sync,
@@ -53,16 +104,181 @@ where
     D::Sample: Send,
 {
     /// Return not owning iterator over the dataloader.
+    ///
+    /// # Panics
+    ///
+    /// Panics with the index, the batch and the epoch of the sample if a `get_sample` panics, unless the samples are
+    /// skipped with the `skip_failed_samples` method of the iterator.
     pub fn iter(&self) -> SingleProcessDataLoaderIter<'_, D, S, C> {
         SingleProcessDataLoaderIter::new(self)
     }
+
+    /// Return not owning iterator over the dataloader, yielding an error instead of panicking when the `get_sample`
+    /// of a sample panics.
+    ///
+    /// The [`SampleError`] records the index of the sample, the batch and the epoch, and a [`TimeoutError`] is
+    /// yielded if the batch takes longer than the builder `timeout`. The iteration can go on with the next batch.
+    ///
+    /// ```
+    /// use ai_dataloader::{indexable::DataLoader, GetSample, Len};
+    ///
+    /// struct Corrupted;
+    /// # impl ai_dataloader::Dataset for Corrupted {}
+    /// # impl Len for Corrupted {
+    /// #     fn len(&self) -> usize { 4 }
+    /// # }
+    /// impl GetSample for Corrupted {
+    ///     type Sample = usize;
+    ///     fn get_sample(&self, index: usize) -> usize {
+    ///         assert_ne!(index, 2, "corrupted sample");
+    ///         index
+    ///     }
+    /// }
+    ///
+    /// let loader = DataLoader::builder(Corrupted).batch_size(2).build();
+    /// let error = loader.try_iter().nth(1).unwrap().unwrap_err();
+    /// assert!(error.to_string().starts_with("sample 2 of batch 1 in epoch 0 panicked"));
+    /// ```
+    pub fn try_iter(&self) -> TryDataLoaderIter<'_, D, S, C> {
+        TryDataLoaderIter {
+            inner: SingleProcessDataLoaderIter::new(self),
+        }
+    }
+
+    /// Return not owning iterator over the dataloader, yielding each batch along with the indices of its samples,
+    /// the epoch and its step in the epoch.
+    ///
+    /// ```
+    /// use ai_dataloader::indexable::DataLoader;
+    ///
+    /// let loader = DataLoader::builder(vec![1., 2., f64::NAN, 4.]).batch_size(2).build();
+    /// for batch in loader.iter_with_meta() {
+    ///     if batch.data.iter().any(|x| x.is_nan()) {
+    ///         assert_eq!((batch.step, batch.indices), (1, vec![2, 3]));
+    ///     }
+    /// }
+    /// ```
+    pub fn iter_with_meta(&self) -> MetaDataLoaderIter<'_, D, S, C> {
+        MetaDataLoaderIter {
+            inner: SingleProcessDataLoaderIter::new(self),
+        }
+    }
+
+    /// Return an owning iterator over a shared dataloader.
+    ///
+    /// Unlike [`DataLoader::iter`], the iterator doesn't borrow the dataloader: it's `Send + 'static` when the
+    /// dataset and the collate function are, so it can be moved into another thread producing the batches.
+    ///
+    /// ```
+    /// use ai_dataloader::indexable::DataLoader;
+    /// use std::sync::{mpsc, Arc};
+    ///
+    /// let loader = Arc::new(DataLoader::builder(vec![1, 2, 3, 4]).batch_size(2).build());
+    /// let (sender, receiver) = mpsc::sync_channel(2);
+    /// let producer = std::thread::spawn({
+    ///     let loader = Arc::clone(&loader);
+    ///     move || {
+    ///         for batch in loader.iter_arc() {
+    ///             sender.send(batch).unwrap();
+    ///         }
+    ///     }
+    /// });
+    /// assert_eq!(receiver.iter().count(), 2);
+    /// producer.join().unwrap();
+    /// ```
+    pub fn iter_arc(self: Arc<Self>) -> IntoIter<D, S, C> {
+        let (sampler_iter, seed) = self.start_iteration();
+        IntoIter {
+            sampler_iter,
+            seed,
+            num_yielded: 0,
+            #[cfg(feature = "rayon")]
+            thread_pool: self.thread_pool.get(),
+            #[cfg(feature = "rayon")]
+            in_flight: self.out_of_order.as_ref().map(InFlight::new),
+            loader: self,
+        }
+    }
+}
+
+impl<D, S, C> DataLoader<D, S, C>
+where
+    S: Sampler,
+{
+    /// Return the seed given to the builder `seed` method, from which the sampler seed and the generator of each
+    /// sample are derived.
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Return the current epoch.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Set the current epoch.
+    ///
+    /// With a seed, the sampler and the samples generators only depend on the seed, the epoch and the number of
+    /// iterations started since the epoch was set: iterating again over the loader draws a new order and new random
+    /// transforms, and setting the epoch again replays its first iteration.
+    pub fn set_epoch(&mut self, epoch: u64) {
+        self.epoch = epoch;
+        *self.iterations.0.get_mut() = 0;
+    }
+
+    /// Start an iteration, returning its batch iterator and the seed of its samples generators.
+    fn start_iteration(&self) -> (BatchIterator<S::IntoIter>, u64) {
+        let iteration = self.iterations.0.fetch_add(1, Ordering::Relaxed);
+        match self.seed {
+            Some(seed) => {
+                let seed = iteration_seed(seed, iteration);
+                (
+                    self.batch_sampler
+                        .iter_with_seed(epoch_seed(seed, self.epoch)),
+                    seed,
+                )
+            }
+            // Without a seed, every iteration get a new order and new random transforms.
+            None => (self.batch_sampler.iter(), rand::random()),
+        }
+    }
+
+    /// Return the thread pool in which the samples are fetched, creating it if no sample was fetched yet.
+    #[cfg(feature = "rayon")]
+    pub fn thread_pool(&self) -> Arc<ThreadPool> {
+        self.thread_pool.get()
+    }
+}
+
+impl<D, S, C> DataLoader<D, S, C>
+where
+    D: GetSampleRef + Sync,
+    S: Sampler,
+{
+    /// Return not owning iterator over the dataloader, collating the samples borrowed from the dataset.
+    ///
+    /// Unlike [`DataLoader::iter`], no owned sample is created: the collate function receives the samples as
+    /// returned by [`GetSampleRef::get_sample_ref`], for instance `ArrayView` that are stacked straight into the batch.
+    pub fn iter_ref<'dataset>(&'dataset self) -> RefDataLoaderIter<'dataset, D, S, C>
+    where
+        C: Collate<D::SampleRef<'dataset>>,
+        D::SampleRef<'dataset>: Send,
+    {
+        RefDataLoaderIter {
+            sampler_iter: self.start_iteration().0,
+            data_fetcher: MapDatasetRefFetcher {
+                dataset: &self.dataset,
+                collate_fn: &self.collate_fn,
+                #[cfg(feature = "rayon")]
+                thread_pool: self.thread_pool.get(),
+            },
+        }
+    }
 }
 
 impl<D, S, C> Len for DataLoader<D, S, C>
 where
-    D: Dataset,
     S: Sampler,
-    C: Collate<D::Sample>,
 {
     /// Return the number of batch that contain the dataloader.
     fn len(&self) -> usize {
@@ -80,10 +296,15 @@ where
 {
     /// The batch iterator of this iterator.
     sampler_iter: BatchIterator<S::IntoIter>,
-    /// Number of sample yielded.
-    num_yielded: u64,
+    /// Number of batches drawn from the sampler.
+    num_yielded: usize,
     /// Used to fetch the data from the dataset.
     data_fetcher: MapDatasetFetcher<'dataset, D, C>,
+    /// Samples left out of their batch because their `get_sample` panicked.
+    skipped: Vec<SampleError>,
+    /// Batches fetched concurrently, if the loader yields them out of order.
+    #[cfg(feature = "rayon")]
+    in_flight: Option<InFlight<D>>,
 }
 
 impl<'dataset, D, S, C> SingleProcessDataLoaderIter<'dataset, D, S, C>
@@ -94,29 +315,124 @@ where
     D::Sample: Send,
 {
     fn new(loader: &DataLoader<D, S, C>) -> SingleProcessDataLoaderIter<'_, D, S, C> {
+        let (sampler_iter, seed) = loader.start_iteration();
         SingleProcessDataLoaderIter {
-            sampler_iter: loader.batch_sampler.iter(),
+            sampler_iter,
             num_yielded: 0,
             data_fetcher: MapDatasetFetcher {
                 dataset: &loader.dataset,
                 collate_fn: &loader.collate_fn,
+                seed,
+                epoch: loader.epoch,
+                skip_failed: false,
+                #[cfg(feature = "rayon")]
+                thread_pool: loader.thread_pool.get(),
+                #[cfg(feature = "rayon")]
+                timeout: loader.timeout.as_ref(),
             },
+            skipped: Vec::new(),
+            #[cfg(feature = "rayon")]
+            in_flight: loader.out_of_order.as_ref().map(InFlight::new),
         }
     }
+    /// Skip the first `step` batches, without fetching them.
+    fn skip_batches(mut self, step: usize) -> Self {
+        self.sampler_iter.by_ref().take(step).for_each(drop);
+        self.num_yielded += step;
+        self
+    }
+    /// Fetch the next batch, keeping its indices and the panics of its samples.
+    fn try_next_batch(&mut self) -> Option<Batch<Result<C::Output, FetchError>>> {
+        #[cfg(feature = "rayon")]
+        if self.in_flight.is_some() {
+            return self.try_next_ready_batch();
+        }
+        loop {
+            let step = self.num_yielded;
+            let mut indices = self.next_index()?;
+            if let Some(data) = self
+                .data_fetcher
+                .fetch(&mut indices, step, &mut self.skipped)
+            {
+                return Some(Batch {
+                    data,
+                    indices,
+                    epoch: self.data_fetcher.epoch,
+                    step,
+                });
+            }
+        }
+    }
+    /// Fetch the next batch, keeping its indices.
+    fn next_batch(&mut self) -> Option<Batch<C::Output>> {
+        let batch = self.try_next_batch()?;
+        Some(Batch {
+            data: batch.data.unwrap_or_else(|error| panic!("{error}")),
+            indices: batch.indices,
+            epoch: batch.epoch,
+            step: batch.step,
+        })
+    }
+    /// Fetch the next batch ready, keeping one batch per thread in flight.
+    #[cfg(feature = "rayon")]
+    fn try_next_ready_batch(&mut self) -> Option<Batch<Result<C::Output, FetchError>>> {
+        self.data_fetcher.next_out_of_order(
+            self.in_flight.as_mut()?,
+            &mut self.sampler_iter,
+            &mut self.num_yielded,
+            &mut self.skipped,
+        )
+    }
     fn next_index(&mut self) -> Option<Vec<usize>> {
-        self.sampler_iter.next()
+        let index = self.sampler_iter.next()?;
+        self.num_yielded += 1;
+        Some(index)
+    }
+
+    /// Leave the samples whose `get_sample` panics out of their batch instead of panicking, to go on with the
+    /// training past corrupted samples.
+    ///
+    /// The batches whose samples all panicked are skipped, so the returned iterator may yield fewer batches than
+    /// [`Len::len`](crate::Len::len). The skipped samples are listed by its `skipped` method.
+    ///
+    /// ```
+    /// use ai_dataloader::{indexable::DataLoader, GetSample, Len};
+    ///
+    /// struct Corrupted;
+    /// # impl ai_dataloader::Dataset for Corrupted {}
+    /// # impl Len for Corrupted {
+    /// #     fn len(&self) -> usize { 4 }
+    /// # }
+    /// impl GetSample for Corrupted {
+    ///     type Sample = usize;
+    ///     fn get_sample(&self, index: usize) -> usize {
+    ///         assert_ne!(index, 2, "corrupted sample");
+    ///         index
+    ///     }
+    /// }
+    ///
+    /// let loader = DataLoader::builder(Corrupted).batch_size(2).build();
+    /// let mut iter = loader.iter().skip_failed_samples();
+    /// assert_eq!(iter.nth(1).unwrap(), ndarray::array![3]);
+    /// assert_eq!(iter.skipped()[0].index, 2);
+    /// ```
+    pub fn skip_failed_samples(mut self) -> SkippingDataLoaderIter<'dataset, D, S, C> {
+        self.data_fetcher.skip_failed = true;
+        SkippingDataLoaderIter { inner: self }
     }
-    fn next_data(&mut self) -> Option<C::Output> {
-        let index = self.next_index();
-        if let Some(index) = index {
-            let data = self.data_fetcher.fetch(index);
-            return Some(data);
+
+    /// Return the state of the loader after the batches drawn so far, to resume the epoch after them.
+    ///
+    /// Unlike the number of yielded batches, the step counts the batches left out by `skip_failed_samples`.
+    pub fn state(&self) -> LoaderState {
+        LoaderState {
+            epoch: self.data_fetcher.epoch,
+            step: self.num_yielded,
         }
-        None
     }
 }
 
-impl<'dataset, D, S, C> Iterator for SingleProcessDataLoaderIter<'dataset, D, S, C>
+impl<D, S, C> Iterator for SingleProcessDataLoaderIter<'_, D, S, C>
 where
     D: Dataset + Sync,
     S: Sampler,
@@ -125,20 +441,96 @@ where
 {
     type Item = C::Output;
     fn next(&mut self) -> Option<Self::Item> {
-        let data = self.next_data();
-
-        if let Some(data) = data {
-            self.num_yielded += 1;
-            return Some(data);
-        }
-        None
+        let batch = self.try_next_batch()?;
+        Some(batch.data.unwrap_or_else(|error| panic!("{error}")))
     }
     fn size_hint(&self) -> (usize, Option<usize>) {
         let (lower, upper) = self.sampler_iter.size_hint();
+        #[cfg(feature = "rayon")]
+        if let Some(in_flight) = &self.in_flight {
+            return (
+                lower + in_flight.len(),
+                upper.map(|upper| upper + in_flight.len()),
+            );
+        }
         (lower, upper)
     }
 }
 
+impl<D, S, C> ExactSizeIterator for SingleProcessDataLoaderIter<'_, D, S, C>
+where
+    D: Dataset + Sync,
+    S: Sampler,
+    S::IntoIter: ExactSizeIterator,
+    C: Collate<D::Sample>,
+    D::Sample: Send,
+{
+}
+
+/// Iterate over the dataloader, leaving the samples whose `get_sample` panics out of their batch.
+///
+/// Returned by the `skip_failed_samples` method of [`SingleProcessDataLoaderIter`]. The batches whose samples all
+/// panicked are skipped, so unlike the other iterators, it doesn't implement [`ExactSizeIterator`].
+pub struct SkippingDataLoaderIter<'dataset, D, S = SequentialSampler, C = DefaultCollate>
+where
+    D: Dataset + Sync,
+    S: Sampler,
+    C: Collate<D::Sample>,
+{
+    /// The iterator yielding the batches.
+    inner: SingleProcessDataLoaderIter<'dataset, D, S, C>,
+}
+
+impl<D, S, C> SkippingDataLoaderIter<'_, D, S, C>
+where
+    D: Dataset + Sync,
+    S: Sampler,
+    C: Collate<D::Sample>,
+    D::Sample: Send,
+{
+    /// Return the samples left out of their batch so far, because their `get_sample` panicked.
+    pub fn skipped(&self) -> &[SampleError] {
+        &self.inner.skipped
+    }
+
+    /// Return the state of the loader after the batches drawn so far, to resume the epoch after them.
+    ///
+    /// Unlike the number of yielded batches, the step counts the skipped batches.
+    pub fn state(&self) -> LoaderState {
+        self.inner.state()
+    }
+}
+
+impl<'dataset, D, S, C> fmt::Debug for SkippingDataLoaderIter<'dataset, D, S, C>
+where
+    D: Dataset + Sync,
+    S: Sampler,
+    C: Collate<D::Sample>,
+    SingleProcessDataLoaderIter<'dataset, D, S, C>: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SkippingDataLoaderIter")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<D, S, C> Iterator for SkippingDataLoaderIter<'_, D, S, C>
+where
+    D: Dataset + Sync,
+    S: Sampler,
+    C: Collate<D::Sample>,
+    D::Sample: Send,
+{
+    type Item = C::Output;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.inner.size_hint().1)
+    }
+}
+
 impl<'dataset, D, S, C> IntoIterator for &'dataset DataLoader<D, S, C>
 where
     D: Dataset + Sync,
@@ -154,7 +546,140 @@ where
     }
 }
 
-impl<'dataset, D, S, C> ExactSizeIterator for SingleProcessDataLoaderIter<'dataset, D, S, C>
+impl<D, S, C> Loader for DataLoader<D, S, C>
+where
+    D: Dataset + Sync,
+    S: Sampler,
+    C: Collate<D::Sample>,
+    D::Sample: Send,
+{
+    type Batch<'loader>
+        = C::Output
+    where
+        Self: 'loader;
+    type Iter<'loader>
+        = SingleProcessDataLoaderIter<'loader, D, S, C>
+    where
+        Self: 'loader;
+
+    /// `step` counts the batches drawn from the sampler, including the ones left out by the `skip_failed_samples`
+    /// method of the iterator because all of their samples panicked: resume from the `state` method of the iterator
+    /// rather than from the number of yielded batches.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `step` isn't zero and the loader yields the batches out of order, as the batches yielded before
+    /// `step` aren't the first ones of the sampler, or if it shuffles the samples without a seed, as the order of the
+    /// epoch can't be drawn again.
+    fn iter_from(&self, step: usize) -> Self::Iter<'_> {
+        #[cfg(feature = "rayon")]
+        assert!(
+            step == 0 || self.out_of_order.is_none(),
+            "a loader built with `in_order(false)` can't resume an epoch from a step"
+        );
+        assert!(
+            step == 0 || self.seed.is_some() || !self.batch_sampler.sampler.is_shuffling(),
+            "a loader shuffling without a seed can't resume an epoch from a step"
+        );
+        SingleProcessDataLoaderIter::new(self).skip_batches(step)
+    }
+    fn num_batches(&self) -> Option<usize> {
+        Some(self.len())
+    }
+    fn epoch(&self) -> u64 {
+        self.epoch
+    }
+    fn set_epoch(&mut self, epoch: u64) {
+        DataLoader::set_epoch(self, epoch);
+    }
+}
+
+/// Number of iterations started in the current epoch, keeping its value when the loader is cloned.
+#[derive(Debug, Default)]
+struct Iterations(AtomicU64);
+
+impl Clone for Iterations {
+    fn clone(&self) -> Self {
+        Self(AtomicU64::new(self.0.load(Ordering::Relaxed)))
+    }
+}
+
+/// Return the seed of an iteration in an epoch, the loader seed itself for the first one.
+fn iteration_seed(seed: u64, iteration: u64) -> u64 {
+    if iteration == 0 {
+        seed
+    } else {
+        splitmix64(seed ^ splitmix64(iteration))
+    }
+}
+
+/// A batch along with the metadata of the samples it was collated from, yielded by [`DataLoader::iter_with_meta`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Batch<T> {
+    /// The collated samples.
+    pub data: T,
+    /// Indices in the dataset of the samples of the batch, in the order they were collated.
+    pub indices: Vec<usize>,
+    /// Epoch of the batch.
+    pub epoch: u64,
+    /// Position of the batch in the epoch, starting at zero.
+    pub step: usize,
+}
+
+impl<T> Batch<T> {
+    /// Return the state of the loader after this batch, to resume the epoch after it.
+    ///
+    /// A batch yielded out of order can't be resumed from.
+    pub fn state(&self) -> LoaderState {
+        LoaderState {
+            epoch: self.epoch,
+            step: self.step + 1,
+        }
+    }
+}
+
+/// Iterate over the dataloader, yielding the batches with their metadata.
+pub struct MetaDataLoaderIter<'dataset, D, S = SequentialSampler, C = DefaultCollate>
+where
+    D: Dataset + Sync,
+    S: Sampler,
+    C: Collate<D::Sample>,
+{
+    /// The iterator yielding the batches.
+    inner: SingleProcessDataLoaderIter<'dataset, D, S, C>,
+}
+
+impl<'dataset, D, S, C> fmt::Debug for MetaDataLoaderIter<'dataset, D, S, C>
+where
+    D: Dataset + Sync,
+    S: Sampler,
+    C: Collate<D::Sample>,
+    SingleProcessDataLoaderIter<'dataset, D, S, C>: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MetaDataLoaderIter")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<D, S, C> Iterator for MetaDataLoaderIter<'_, D, S, C>
+where
+    D: Dataset + Sync,
+    S: Sampler,
+    C: Collate<D::Sample>,
+    D::Sample: Send,
+{
+    type Item = Batch<C::Output>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next_batch()
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<D, S, C> ExactSizeIterator for MetaDataLoaderIter<'_, D, S, C>
 where
     D: Dataset + Sync,
     S: Sampler,
@@ -164,18 +689,286 @@ where
 {
 }
 
+/// A panic of `get_sample`, caught while fetching a batch.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SampleError {
+    /// Index in the dataset of the sample.
+    pub index: usize,
+    /// Position in the epoch of the batch of the sample.
+    pub batch: usize,
+    /// Epoch of the batch.
+    pub epoch: u64,
+    /// Message of the panic.
+    pub message: String,
+}
+
+impl fmt::Display for SampleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "sample {} of batch {} in epoch {} panicked: {}",
+            self.index, self.batch, self.epoch, self.message
+        )
+    }
+}
+
+impl Error for SampleError {}
+
+/// The fetch of a batch timed out, set with the builder `timeout` method.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TimeoutError {
+    /// Indices in the dataset of the samples still pending.
+    pub indices: Vec<usize>,
+    /// Position in the epoch of the batch.
+    pub batch: usize,
+    /// Epoch of the batch.
+    pub epoch: u64,
+    /// The timeout of the batch.
+    pub timeout: Duration,
+}
+
+impl fmt::Display for TimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "batch {} in epoch {} timed out after {:?}, samples {:?} are still pending",
+            self.batch, self.epoch, self.timeout, self.indices
+        )
+    }
+}
+
+impl Error for TimeoutError {}
+
+/// Error of the fetch of a batch, yielded by [`DataLoader::try_iter`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FetchError {
+    /// The `get_sample` of a sample panicked.
+    Panicked(SampleError),
+    /// The fetch of the batch timed out.
+    TimedOut(TimeoutError),
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Panicked(error) => error.fmt(f),
+            Self::TimedOut(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Error for FetchError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Panicked(error) => Some(error),
+            Self::TimedOut(error) => Some(error),
+        }
+    }
+}
+
+/// Iterate over the dataloader, yielding an error for the batches with a panicking sample.
+pub struct TryDataLoaderIter<'dataset, D, S = SequentialSampler, C = DefaultCollate>
+where
+    D: Dataset + Sync,
+    S: Sampler,
+    C: Collate<D::Sample>,
+{
+    /// The iterator yielding the batches.
+    inner: SingleProcessDataLoaderIter<'dataset, D, S, C>,
+}
+
+impl<'dataset, D, S, C> fmt::Debug for TryDataLoaderIter<'dataset, D, S, C>
+where
+    D: Dataset + Sync,
+    S: Sampler,
+    C: Collate<D::Sample>,
+    SingleProcessDataLoaderIter<'dataset, D, S, C>: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TryDataLoaderIter")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<D, S, C> Iterator for TryDataLoaderIter<'_, D, S, C>
+where
+    D: Dataset + Sync,
+    S: Sampler,
+    C: Collate<D::Sample>,
+    D::Sample: Send,
+{
+    type Item = Result<C::Output, FetchError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.inner.try_next_batch()?.data)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<D, S, C> ExactSizeIterator for TryDataLoaderIter<'_, D, S, C>
+where
+    D: Dataset + Sync,
+    S: Sampler,
+    S::IntoIter: ExactSizeIterator,
+    C: Collate<D::Sample>,
+    D::Sample: Send,
+{
+}
+
+/// Owning iterator over the dataloader, holding it in an [`Arc`].
+#[derive(Debug)]
+pub struct IntoIter<D, S = SequentialSampler, C = DefaultCollate>
+where
+    D: Dataset,
+    S: Sampler,
+{
+    /// The batch iterator of this iterator.
+    sampler_iter: BatchIterator<S::IntoIter>,
+    /// Seed of the samples generators for this iteration.
+    seed: u64,
+    /// Number of batches drawn from the sampler.
+    num_yielded: usize,
+    /// Thread pool of the dataloader, in which the samples are fetched.
+    #[cfg(feature = "rayon")]
+    thread_pool: Arc<ThreadPool>,
+    /// Batches fetched concurrently, if the loader yields them out of order.
+    #[cfg(feature = "rayon")]
+    in_flight: Option<InFlight<D>>,
+    /// The iterated dataloader.
+    loader: Arc<DataLoader<D, S, C>>,
+}
+
+impl<D, S, C> Iterator for IntoIter<D, S, C>
+where
+    D: Dataset + Sync,
+    S: Sampler,
+    C: Collate<D::Sample>,
+    D::Sample: Send,
+{
+    type Item = C::Output;
+    fn next(&mut self) -> Option<Self::Item> {
+        let data_fetcher = MapDatasetFetcher {
+            dataset: &self.loader.dataset,
+            collate_fn: &self.loader.collate_fn,
+            seed: self.seed,
+            epoch: self.loader.epoch,
+            skip_failed: false,
+            #[cfg(feature = "rayon")]
+            thread_pool: Arc::clone(&self.thread_pool),
+            #[cfg(feature = "rayon")]
+            timeout: self.loader.timeout.as_ref(),
+        };
+        #[cfg(feature = "rayon")]
+        if let Some(in_flight) = &mut self.in_flight {
+            let batch = data_fetcher.next_out_of_order(
+                in_flight,
+                &mut self.sampler_iter,
+                &mut self.num_yielded,
+                &mut Vec::new(),
+            )?;
+            return Some(batch.data.unwrap_or_else(|error| panic!("{error}")));
+        }
+        loop {
+            let mut index = self.sampler_iter.next()?;
+            let step = self.num_yielded;
+            self.num_yielded += 1;
+            if let Some(data) = data_fetcher.fetch(&mut index, step, &mut Vec::new()) {
+                return Some(data.unwrap_or_else(|error| panic!("{error}")));
+            }
+        }
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.sampler_iter.size_hint();
+        #[cfg(feature = "rayon")]
+        if let Some(in_flight) = &self.in_flight {
+            return (
+                lower + in_flight.len(),
+                upper.map(|upper| upper + in_flight.len()),
+            );
+        }
+        (lower, upper)
+    }
+}
+
+impl<D, S, C> ExactSizeIterator for IntoIter<D, S, C>
+where
+    D: Dataset + Sync,
+    S: Sampler,
+    S::IntoIter: ExactSizeIterator,
+    C: Collate<D::Sample>,
+    D::Sample: Send,
+{
+}
+
+impl<D, S, C> IntoIterator for DataLoader<D, S, C>
+where
+    D: Dataset + Sync,
+    S: Sampler,
+    C: Collate<D::Sample>,
+    D::Sample: Send,
+{
+    type Item = C::Output;
+    type IntoIter = IntoIter<D, S, C>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Arc::new(self).iter_arc()
+    }
+}
+
+/// Iterate over the dataloader, collating the samples borrowed from the dataset.
+#[derive(Debug)]
+pub struct RefDataLoaderIter<'dataset, D, S = SequentialSampler, C = DefaultCollate>
+where
+    S: Sampler,
+{
+    /// The batch iterator of this iterator.
+    sampler_iter: BatchIterator<S::IntoIter>,
+    /// Used to borrow the data from the dataset.
+    data_fetcher: MapDatasetRefFetcher<'dataset, D, C>,
+}
+
+impl<'dataset, D, S, C> Iterator for RefDataLoaderIter<'dataset, D, S, C>
+where
+    D: GetSampleRef + Sync,
+    S: Sampler,
+    C: Collate<D::SampleRef<'dataset>>,
+    D::SampleRef<'dataset>: Send,
+{
+    type Item = C::Output;
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.sampler_iter.next()?;
+        Some(self.data_fetcher.fetch(index))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.sampler_iter.size_hint()
+    }
+}
+
+impl<'dataset, D, S, C> ExactSizeIterator for RefDataLoaderIter<'dataset, D, S, C>
+where
+    D: GetSampleRef + Sync,
+    S: Sampler,
+    S::IntoIter: ExactSizeIterator,
+    C: Collate<D::SampleRef<'dataset>>,
+    D::SampleRef<'dataset>: Send,
+{
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::collate::NoOpCollate;
-    use crate::sampler::RandomSampler;
     use crate::sampler::SequentialSampler;
-    use crate::Len;
+    use crate::sampler::{PermutationSampler, RandomSampler};
     use crate::NdarrayDataset;
+    use crate::{GetSample, Len};
     use ndarray::{arr0, array, Array, Array1, Array4, Axis, Ix1, Ix4, Slice};
     use ndarray_rand::rand_distr::{Normal, Uniform};
     use ndarray_rand::RandomExt;
     use std::collections::HashMap;
+    use std::sync::atomic::AtomicUsize;
 
     #[test]
     fn len() {
@@ -192,6 +985,396 @@ mod tests {
         assert_eq!(iter.len(), 4);
     }
 
+    /// Dataset whose samples multiple of 3 can't be read.
+    struct Corrupted(usize);
+
+    impl Dataset for Corrupted {}
+
+    impl Len for Corrupted {
+        fn len(&self) -> usize {
+            self.0
+        }
+    }
+
+    impl GetSample for Corrupted {
+        type Sample = usize;
+        fn get_sample(&self, index: usize) -> Self::Sample {
+            assert!(index % 3 != 0, "unreadable sample");
+            index
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "sample 6 of batch 3 in epoch 2 panicked: unreadable sample")]
+    fn panic_context() {
+        let mut loader = DataLoader::builder(Corrupted(10)).batch_size(2).build();
+        loader.set_epoch(2);
+        loader.iter_from(3).for_each(drop);
+    }
+
+    #[test]
+    fn catch_panics() {
+        let loader = DataLoader::builder(Corrupted(7)).batch_size(2).build();
+        let batches: Vec<_> = loader.try_iter().collect();
+        assert_eq!(batches.len(), 4);
+        assert_eq!(
+            batches[0],
+            Err(FetchError::Panicked(SampleError {
+                index: 0,
+                batch: 0,
+                epoch: 0,
+                message: "unreadable sample".to_owned()
+            }))
+        );
+        assert!(batches[1].is_err());
+        assert_eq!(batches[2], Ok(array![4, 5]));
+        assert!(matches!(&batches[3], Err(FetchError::Panicked(error)) if error.index == 6));
+    }
+
+    #[test]
+    fn skip_panicking_samples() {
+        let loader = DataLoader::builder(Corrupted(10)).batch_size(2).build();
+        let mut iter = loader.iter().skip_failed_samples();
+        let batches: Vec<_> = iter.by_ref().collect();
+        assert_eq!(
+            batches,
+            vec![array![1], array![2], array![4, 5], array![7], array![8]]
+        );
+        let skipped: Vec<_> = iter
+            .skipped()
+            .iter()
+            .map(|error| (error.index, error.batch))
+            .collect();
+        assert_eq!(skipped, vec![(0, 0), (3, 1), (6, 3), (9, 4)]);
+    }
+
+    #[test]
+    fn resume_after_skipped_batches() {
+        let mut loader = DataLoader::builder(Corrupted(10)).batch_size(1).build();
+        let mut iter = loader.iter().skip_failed_samples();
+        // The batches of the samples 0 and 3 are left out.
+        assert_eq!(iter.nth(2), Some(array![4]));
+        let state = iter.state();
+        assert_eq!(state, LoaderState { epoch: 0, step: 5 });
+        let remaining: Vec<_> = iter.collect();
+        assert_eq!(
+            loader
+                .restore(state)
+                .skip_failed_samples()
+                .collect::<Vec<_>>(),
+            remaining
+        );
+        assert_eq!(remaining[0], array![5]);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "a loader shuffling without a seed can't resume an epoch from a step"
+    )]
+    fn resume_unseeded_shuffling() {
+        let mut loader = DataLoader::builder(vec![1, 2, 3, 4])
+            .batch_size(2)
+            .shuffle()
+            .build();
+        assert_eq!(Loader::iter(&loader).count(), 2);
+        let _ = loader.restore(LoaderState { epoch: 0, step: 1 });
+    }
+
+    #[test]
+    fn size_hint_with_skipped_batches() {
+        let loader = DataLoader::builder(Corrupted(4)).batch_size(1).build();
+        let iter = loader.iter().skip_failed_samples();
+        assert_eq!(iter.size_hint(), (0, Some(4)));
+        assert_eq!(iter.count(), 2);
+        let iter = loader.try_iter();
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.count(), loader.len());
+    }
+
+    /// Dataset whose first read of the sample 3 hangs.
+    #[cfg(feature = "rayon")]
+    #[derive(Default)]
+    struct Hung(std::sync::atomic::AtomicBool);
+
+    #[cfg(feature = "rayon")]
+    impl Dataset for Hung {}
+
+    #[cfg(feature = "rayon")]
+    impl Len for Hung {
+        fn len(&self) -> usize {
+            6
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    impl GetSample for Hung {
+        type Sample = usize;
+        fn get_sample(&self, index: usize) -> Self::Sample {
+            if index == 3 && !self.0.swap(true, std::sync::atomic::Ordering::SeqCst) {
+                std::thread::sleep(Duration::from_secs(1));
+            }
+            index
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn timeout() {
+        let loader = DataLoader::builder(Hung::default())
+            .batch_size(2)
+            .num_threads(2)
+            .timeout(Duration::from_millis(100))
+            .build();
+        let batches: Vec<_> = loader.try_iter().collect();
+        assert_eq!(batches[0], Ok(array![0, 1]));
+        assert_eq!(
+            batches[1],
+            Err(FetchError::TimedOut(TimeoutError {
+                indices: vec![3],
+                batch: 1,
+                epoch: 0,
+                timeout: Duration::from_millis(100),
+            }))
+        );
+        assert_eq!(batches[2], Ok(array![4, 5]));
+
+        let loader = DataLoader::builder(Hung::default())
+            .batch_size(2)
+            .num_threads(2)
+            .timeout(Duration::from_millis(100))
+            .timeout_retries(1)
+            .build();
+        assert_eq!(
+            loader.iter().collect::<Vec<_>>(),
+            vec![array![0, 1], array![2, 3], array![4, 5]]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn out_of_order() {
+        let loader = DataLoader::builder(Hung::default())
+            .batch_size(2)
+            .num_threads(2)
+            .in_order(false)
+            .build();
+        let batches: Vec<_> = loader.iter_with_meta().collect();
+        // The batch of the hung sample is yielded last, after the batch dispatched once the first one was yielded.
+        assert_eq!(
+            batches.iter().map(|batch| batch.step).collect::<Vec<_>>(),
+            [0, 2, 1]
+        );
+        let mut indices: Vec<_> = batches
+            .iter()
+            .flat_map(|batch| batch.indices.clone())
+            .collect();
+        indices.sort_unstable();
+        assert_eq!(indices, (0..6).collect::<Vec<_>>());
+        for batch in &batches {
+            assert_eq!(batch.data, Array::from_vec(batch.indices.clone()));
+        }
+
+        // The owning iterator also yields the batches out of order.
+        let loader = DataLoader::builder(Hung::default())
+            .batch_size(2)
+            .num_threads(2)
+            .in_order(false)
+            .build();
+        let iter = Arc::new(loader).iter_arc();
+        assert_eq!(iter.len(), 3);
+        let batches: Vec<_> = iter.collect();
+        assert_eq!(batches, [array![0, 1], array![4, 5], array![2, 3]]);
+
+        let loader = DataLoader::builder(Hung::default())
+            .batch_size(2)
+            .num_threads(2)
+            .timeout(Duration::from_millis(100))
+            .in_order(false)
+            .build();
+        let mut iter = loader.try_iter();
+        assert_eq!(iter.len(), 3);
+        let batches: Vec<_> = iter.by_ref().collect();
+        assert_eq!(
+            batches[2],
+            Err(FetchError::TimedOut(TimeoutError {
+                indices: vec![3],
+                batch: 1,
+                epoch: 0,
+                timeout: Duration::from_millis(100),
+            }))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    #[should_panic(expected = "can't resume")]
+    fn resume_out_of_order() {
+        let mut loader = DataLoader::builder(vec![1, 2, 3, 4])
+            .batch_size(2)
+            .in_order(false)
+            .build();
+        assert_eq!(Loader::iter(&loader).count(), 2);
+        let _ = loader.restore(LoaderState { epoch: 0, step: 1 });
+    }
+
+    #[test]
+    fn batch_metadata() {
+        let mut loader = DataLoader::builder((0..5).collect::<Vec<usize>>())
+            .batch_size(2)
+            .shuffle()
+            .seed(0)
+            .build();
+        loader.set_epoch(4);
+        let batches: Vec<_> = loader.iter_with_meta().collect();
+        assert_eq!(batches.len(), 3);
+        for (step, batch) in batches.iter().enumerate() {
+            assert_eq!(batch.step, step);
+            assert_eq!(batch.epoch, 4);
+            assert_eq!(batch.data, Array::from_vec(batch.indices.clone()));
+        }
+        loader.set_epoch(4);
+        assert_eq!(
+            batches
+                .into_iter()
+                .map(|batch| batch.data)
+                .collect::<Vec<_>>(),
+            loader.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn resume() {
+        let mut loader = DataLoader::builder((0..10).collect::<Vec<_>>())
+            .batch_size(3)
+            .shuffle()
+            .seed(0)
+            .build();
+        Loader::set_epoch(&mut loader, 2);
+        let batches: Vec<_> = Loader::iter(&loader).collect();
+        assert_eq!(loader.num_batches(), Some(4));
+
+        let state = loader.state(1);
+        assert_eq!(state, LoaderState { epoch: 2, step: 1 });
+        Loader::set_epoch(&mut loader, 0);
+        let iter = loader.restore(state);
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.collect::<Vec<_>>(), batches[1..]);
+    }
+
+    #[test]
+    fn owned_iterator() {
+        fn assert_send_static<T: Send + 'static>(_: &T) {}
+
+        let mut loader = DataLoader::builder(vec![1, 2, 3, 4, 5])
+            .batch_size(2)
+            .shuffle()
+            .seed(0)
+            .build();
+        let batches: Vec<_> = loader.iter().collect();
+        loader.set_epoch(0);
+
+        let loader = Arc::new(loader);
+        let iter = Arc::clone(&loader).iter_arc();
+        assert_send_static(&iter);
+        assert_eq!(iter.len(), 3);
+        let handle = std::thread::spawn(move || iter.collect::<Vec<_>>());
+        assert_eq!(handle.join().unwrap(), batches);
+
+        let mut loader = Arc::try_unwrap(loader).unwrap();
+        loader.set_epoch(0);
+        let handle = std::thread::spawn(move || loader.into_iter().collect::<Vec<_>>());
+        assert_eq!(handle.join().unwrap(), batches);
+    }
+
+    #[test]
+    fn seeded_shuffling() {
+        let dataset: Vec<i32> = (0..100).collect();
+        let mut loader = DataLoader::builder(dataset.clone())
+            .shuffle()
+            .seed(3)
+            .build();
+        let epoch_0: Vec<_> = loader.iter().collect();
+        // Iterating again over the epoch draws a new order.
+        assert_ne!(epoch_0, loader.iter().collect::<Vec<_>>());
+        loader.set_epoch(1);
+        let epoch_1: Vec<_> = loader.iter().collect();
+        assert_ne!(epoch_0, epoch_1);
+        loader.set_epoch(0);
+        assert_eq!(epoch_0, loader.iter().collect::<Vec<_>>());
+
+        let mut loader = DataLoader::builder(dataset)
+            .sampler::<PermutationSampler>()
+            .batch_size(10)
+            .seed(3)
+            .build();
+        let epoch: Vec<_> = loader.iter().collect();
+        loader.set_epoch(0);
+        assert_eq!(epoch, loader.iter().collect::<Vec<_>>());
+        let mut indices: Vec<_> = epoch.iter().flatten().copied().collect();
+        assert_ne!(indices, (0..100).collect::<Vec<_>>());
+        indices.sort_unstable();
+        assert_eq!(indices, (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn borrowed_samples() {
+        let dataset = NdarrayDataset {
+            ndarrays: (
+                Array::from_shape_fn((5, 2, 2), |(i, j, k)| i * 4 + j * 2 + k),
+                array![0, 1, 0, 1, 0],
+            ),
+        };
+        let loader = DataLoader::builder(dataset.clone()).batch_size(2).build();
+        let ref_loader = DataLoader::builder_ref(dataset).batch_size(2).build();
+        assert_eq!(ref_loader.iter_ref().len(), 3);
+        assert_eq!(
+            loader.iter().collect::<Vec<_>>(),
+            ref_loader.iter_ref().collect::<Vec<_>>()
+        );
+
+        // Scalars and strings are cloned by the default collate.
+        let loader = DataLoader::builder_ref(vec![(1, String::from("a")), (2, String::from("b"))])
+            .batch_size(2)
+            .build();
+        assert_eq!(
+            loader.iter_ref().next(),
+            Some((array![1, 2], vec![String::from("a"), String::from("b")]))
+        );
+
+        let loader = DataLoader::builder_ref(vec![String::from("a"), String::from("bc")])
+            .batch_size(2)
+            .shuffle()
+            .collate_fn_ref(|batch: Vec<&String>| {
+                batch.iter().map(|text| text.len()).sum::<usize>()
+            })
+            .build();
+        assert_eq!(loader.iter_ref().collect::<Vec<_>>(), vec![3]);
+    }
+
+    #[test]
+    fn borrowed_arrays_not_cloned() {
+        static CLONES: AtomicUsize = AtomicUsize::new(0);
+
+        #[derive(Debug, PartialEq)]
+        struct Counted(usize);
+        impl Clone for Counted {
+            fn clone(&self) -> Self {
+                CLONES.fetch_add(1, Ordering::Relaxed);
+                Counted(self.0)
+            }
+        }
+
+        let dataset: Vec<_> = (0..4)
+            .map(|i| (Array::from_shape_fn(3, |j| Counted(i * 3 + j)), i))
+            .collect();
+        let loader = DataLoader::builder_ref(dataset).batch_size(2).build();
+        let batches: Vec<_> = loader.iter_ref().collect();
+        assert_eq!(batches[1].0[[1, 2]], Counted(11));
+        assert_eq!(batches[1].1, array![2, 3]);
+        // Each element is only copied once, when it's stacked into its batch.
+        assert_eq!(CLONES.load(Ordering::Relaxed), 12);
+    }
+
     #[test]
     fn one_dimension_basic() {
         let dataset = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
@@ -259,6 +1442,7 @@ mod tests {
         Sequential(TestDataLoader<SequentialSampler>),
         Random(TestDataLoader<RandomSampler>),
     }
+    #[allow(clippy::fn_params_excessive_bools)]
     fn get_loader_with_dummy_data(batch_size: usize, shuffle: bool) -> TestDataLoaderData {
         // We use a normal distribution for the random numbers
         let normal: Normal<f64> = Normal::new(0.0, 1.0).unwrap();
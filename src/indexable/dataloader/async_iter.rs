@@ -0,0 +1,163 @@
+use super::DataLoader;
+use crate::{
+    collate::{Collate, DefaultCollate},
+    indexable::{fetch::AsyncDatasetFetcher, AsyncGetSample},
+    sampler::{Sampler, SequentialSampler},
+    Len,
+};
+use futures_util::{
+    stream::{self, LocalBoxStream},
+    Stream, StreamExt,
+};
+use std::fmt;
+use tokio::runtime::Handle;
+
+use super::Builder;
+
+impl<D> DataLoader<D, SequentialSampler, DefaultCollate>
+where
+    D: AsyncGetSample + Len,
+{
+    /// Helper to return a [`DataLoader`] builder for an async dataset.
+    ///
+    /// Iterate over the resulting dataloader with [`DataLoader::stream_async`] or [`DataLoader::iter_async`].
+    pub fn builder_async(dataset: D) -> Builder<D, SequentialSampler, DefaultCollate> {
+        Builder::new_async(dataset)
+    }
+}
+
+impl<D, S, C> DataLoader<D, S, C>
+where
+    D: AsyncGetSample,
+    S: Sampler,
+    C: Collate<D::Sample>,
+{
+    /// Return a stream over the batches of an async dataset.
+    ///
+    /// The futures of the samples of a batch are polled concurrently, up to the `max_concurrency` of the builder, and
+    /// the samples are collated in the order of the sampler.
+    pub fn stream_async(&self) -> impl Stream<Item = C::Output> + '_ {
+        let fetcher = AsyncDatasetFetcher {
+            dataset: &*self.dataset,
+            collate_fn: &self.collate_fn,
+            max_concurrency: self.max_concurrency,
+        };
+        stream::unfold(
+            (self.start_iteration().0, fetcher),
+            |(mut sampler_iter, fetcher)| async move {
+                let indices = sampler_iter.next()?;
+                let batch = fetcher.fetch(indices).await;
+                Some((batch, (sampler_iter, fetcher)))
+            },
+        )
+    }
+
+    /// Return a blocking iterator over the batches of an async dataset, fetching them on the given runtime.
+    ///
+    /// The batches are fetched like with [`DataLoader::stream_async`]. The runtime should be a multi-thread runtime,
+    /// as the I/O and the timers of a current-thread runtime aren't driven by the iterator.
+    ///
+    /// # Panics
+    ///
+    /// The iterator panics if it's used from an async context.
+    pub fn iter_async(&self, runtime: Handle) -> AsyncDataLoaderIter<'_, C::Output> {
+        AsyncDataLoaderIter {
+            stream: self.stream_async().boxed_local(),
+            runtime,
+        }
+    }
+}
+
+/// Blocking iterator over the batches of an async dataset, returned by [`DataLoader::iter_async`].
+pub struct AsyncDataLoaderIter<'loader, T> {
+    /// The batches to wait for.
+    stream: LocalBoxStream<'loader, T>,
+    /// Runtime on which the samples are fetched.
+    runtime: Handle,
+}
+
+impl<T> fmt::Debug for AsyncDataLoaderIter<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncDataLoaderIter")
+            .field("runtime", &self.runtime)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Iterator for AsyncDataLoaderIter<'_, T> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.runtime.block_on(self.stream.next())
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::future::BoxFuture;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+    use tokio::runtime::Builder;
+
+    /// Samples whose fetch takes longer for the first indices, recording the number of concurrent fetches.
+    #[derive(Debug, Default)]
+    struct Remote {
+        in_flight: AtomicUsize,
+        max_in_flight: Arc<AtomicUsize>,
+    }
+
+    impl Len for Remote {
+        fn len(&self) -> usize {
+            10
+        }
+    }
+
+    impl AsyncGetSample for Remote {
+        type Sample = usize;
+        type Future<'a> = BoxFuture<'a, usize>;
+        fn get_sample_async(&self, index: usize) -> Self::Future<'_> {
+            Box::pin(async move {
+                let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+                for _ in 0..20 - 2 * index {
+                    tokio::task::yield_now().await;
+                }
+                self.in_flight.fetch_sub(1, Ordering::SeqCst);
+                index
+            })
+        }
+    }
+
+    #[test]
+    fn sampler_order() {
+        let runtime = Builder::new_multi_thread()
+            .worker_threads(2)
+            .build()
+            .unwrap();
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+        let dataset = Remote {
+            max_in_flight: Arc::clone(&max_in_flight),
+            ..Remote::default()
+        };
+        let loader = DataLoader::builder_async(dataset)
+            .batch_size(4)
+            .max_concurrency(3)
+            .collate_fn_async(|batch| batch)
+            .build();
+
+        let batches: Vec<Vec<usize>> = runtime.block_on(loader.stream_async().collect());
+        assert_eq!(batches, [vec![0, 1, 2, 3], vec![4, 5, 6, 7], vec![8, 9]]);
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 3);
+
+        let batches: Vec<_> = loader.iter_async(runtime.handle().clone()).collect();
+        assert_eq!(batches, [vec![0, 1, 2, 3], vec![4, 5, 6, 7], vec![8, 9]]);
+        // The thread pool of the sync iterators is never created.
+        #[cfg(feature = "rayon")]
+        assert!(!loader.thread_pool.is_created());
+    }
+}
@@ -1,24 +1,34 @@
 use crate::{
     collate::{Collate, DefaultCollate},
+    indexable::GetSampleRef,
     sampler::{BatchSampler, RandomSampler, Sampler, SequentialSampler},
-    Dataset,
+    Dataset, Len,
 };
 
+#[cfg(feature = "async")]
+use crate::indexable::AsyncGetSample;
 #[cfg(feature = "rayon")]
-use crate::THREAD_POOL;
+use crate::indexable::{
+    fetch::{OutOfOrder, Timeout},
+    worker::{WorkerInfo, WorkerInit, WorkerPool},
+};
+#[cfg(feature = "rayon")]
+use rayon::ThreadPool;
+use std::sync::Arc;
+#[cfg(feature = "rayon")]
+use std::time::Duration;
 
-use super::DataLoader;
+use super::{DataLoader, Iterations};
 
 /// Basic builder for creating dataloader from a type that implement `IntoIterator`.
 /// add a dataloader for all type that implement `IntoIterator`.
 /// If the iterator `Item` is not supported by default collate, you must provide your own collate function
 #[must_use]
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Hash, Ord)]
+#[derive(Debug, Clone)]
 pub struct Builder<D, S = SequentialSampler, C = DefaultCollate>
 where
-    D: Dataset,
+    D: Len,
     S: Sampler,
-    C: Collate<D::Sample>,
 {
     /// The dataset from which the loader will yield the data.
     dataset: D,
@@ -26,9 +36,29 @@ where
     batch_sampler: BatchSampler<S>,
     /// Used to collate the data together.
     collate_fn: C,
+    /// Seed of the per-sample generators, drawn randomly if not set.
+    seed: Option<u64>,
     #[cfg(feature = "rayon")]
     /// Number of threads to use.
     num_threads: usize,
+    #[cfg(feature = "rayon")]
+    /// Thread pool provided by the user, used instead of creating one.
+    thread_pool: Option<Arc<ThreadPool>>,
+    #[cfg(feature = "rayon")]
+    /// Maximum duration of the fetch of a batch.
+    timeout: Option<Timeout<D>>,
+    #[cfg(feature = "rayon")]
+    /// Number of times the pending samples are dispatched again when the timeout expires.
+    timeout_retries: usize,
+    #[cfg(feature = "rayon")]
+    /// Set if the batches are yielded as soon as they are ready.
+    out_of_order: Option<OutOfOrder<D>>,
+    #[cfg(feature = "rayon")]
+    /// Called at the start of each worker thread.
+    worker_init: Option<WorkerInit>,
+    #[cfg(feature = "async")]
+    /// Maximum number of samples of an async dataset fetched concurrently.
+    max_concurrency: Option<usize>,
 }
 
 // FIXME: kind of strange that we require DefaultCollatte even if in the end we may won't use it
@@ -40,6 +70,39 @@ where
     /// Create a new [`Builder`], with default fields.
     /// By default the [`Builder`] is sequential and have a `batch_size` of one.
     pub fn new(dataset: D) -> Self {
+        Self::with_dataset(dataset)
+    }
+}
+
+impl<D> Builder<D, SequentialSampler, DefaultCollate>
+where
+    D: GetSampleRef + Len,
+{
+    /// Create a new [`Builder`] for a dataset lending its samples, to iterate with
+    /// [`DataLoader::iter_ref`].
+    ///
+    /// By default the [`Builder`] is sequential and have a `batch_size` of one.
+    pub fn new_ref(dataset: D) -> Self {
+        Self::with_dataset(dataset)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<D> Builder<D, SequentialSampler, DefaultCollate>
+where
+    D: AsyncGetSample + Len,
+{
+    /// Create a new [`Builder`] for an async dataset, to iterate with [`DataLoader::stream_async`] or
+    /// [`DataLoader::iter_async`].
+    ///
+    /// By default the [`Builder`] is sequential and have a `batch_size` of one.
+    pub fn new_async(dataset: D) -> Self {
+        Self::with_dataset(dataset)
+    }
+}
+
+impl<D: Len> Builder<D, SequentialSampler, DefaultCollate> {
+    fn with_dataset(dataset: D) -> Self {
         #[cfg(feature = "rayon")]
         let num_threads = std::thread::available_parallelism()
             .unwrap_or(std::num::NonZeroUsize::new(1).unwrap())
@@ -54,17 +117,29 @@ where
                 drop_last: false,
             },
             collate_fn: DefaultCollate,
+            seed: None,
             #[cfg(feature = "rayon")]
             num_threads,
+            #[cfg(feature = "rayon")]
+            thread_pool: None,
+            #[cfg(feature = "rayon")]
+            timeout: None,
+            #[cfg(feature = "rayon")]
+            timeout_retries: 0,
+            #[cfg(feature = "rayon")]
+            out_of_order: None,
+            #[cfg(feature = "rayon")]
+            worker_init: None,
+            #[cfg(feature = "async")]
+            max_concurrency: None,
         }
     }
 }
 
 impl<D, S, C> Builder<D, S, C>
 where
-    D: Dataset,
+    D: Len,
     S: Sampler,
-    C: Collate<D::Sample>,
 {
     /// Use a random sampler.
     pub fn shuffle(self) -> Builder<D, RandomSampler, C> {
@@ -76,13 +151,74 @@ where
         self
     }
 
-    /// Set the number of threads to use.
+    /// Set the number of threads of the thread pool created for this dataloader.
+    ///
+    /// Ignored if a thread pool is provided with [`Builder::thread_pool`].
     #[cfg(feature = "rayon")]
     pub fn num_threads(mut self, num_threads: usize) -> Self {
         self.num_threads = num_threads;
         self
     }
 
+    /// Fetch the samples in the given thread pool instead of creating one.
+    ///
+    /// The pool can be shared between several dataloaders by giving an `Arc<ThreadPool>`.
+    #[cfg(feature = "rayon")]
+    pub fn thread_pool(mut self, thread_pool: impl Into<Arc<ThreadPool>>) -> Self {
+        self.thread_pool = Some(thread_pool.into());
+        self
+    }
+
+    /// Call `worker_init_fn` at the start of each thread of the thread pool created for this dataloader, with the
+    /// [`WorkerInfo`] of the thread, to seed a per-worker generator or set up a per-worker resource for instance.
+    ///
+    /// The [`WorkerInfo`] is also returned by [`worker_info`](crate::indexable::worker_info) on the worker threads.
+    /// Ignored if a thread pool is provided with [`Builder::thread_pool`].
+    #[cfg(feature = "rayon")]
+    pub fn worker_init_fn<F>(mut self, worker_init_fn: F) -> Self
+    where
+        F: Fn(&WorkerInfo) + Send + Sync + 'static,
+    {
+        self.worker_init = Some(WorkerInit(Arc::new(worker_init_fn)));
+        self
+    }
+
+    /// Set the maximum number of samples of an async dataset fetched concurrently. By default all the samples of a
+    /// batch are fetched concurrently.
+    ///
+    /// Only used by [`DataLoader::stream_async`] and [`DataLoader::iter_async`].
+    #[cfg(feature = "async")]
+    pub fn max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = Some(max_concurrency);
+        self
+    }
+
+    /// Dispatch the pending samples again up to `retries` times when the fetch of a batch times out, for instance if
+    /// the first attempt is stuck on a hung read. By default they are not dispatched again.
+    ///
+    /// The samples are dispatched again to the same thread pool, whose threads stuck on the previous attempts can't be
+    /// interrupted. A retry can only make progress on the threads left free, so it doesn't help once every thread of
+    /// the pool is blocked: give the loader more threads with [`Builder::num_threads`] than the number of reads
+    /// expected to hang at once.
+    ///
+    /// Only used with [`Builder::timeout`].
+    #[cfg(feature = "rayon")]
+    pub fn timeout_retries(mut self, retries: usize) -> Self {
+        self.timeout_retries = retries;
+        self
+    }
+
+    /// Set the seed from which the sampler seed and the generator of each sample are derived, making the shuffling
+    /// and the random transforms reproducible.
+    ///
+    /// Then they only depend on the seed, the epoch set with `DataLoader::set_epoch` and the number of iterations
+    /// started in the epoch. Without a seed, each iteration over the dataloader is also shuffled and transformed
+    /// differently, but not reproducibly.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
     /// Drop the lasts element if they don't feat into a batch. For instance if a dataset have 13
     /// samples and a `batch_size` of 5, the last 3 samples will be dropped.
     pub fn drop_last(mut self) -> Self {
@@ -90,18 +226,26 @@ where
         self
     }
 
-    /// Set a custom collate function.
-    pub fn collate_fn<CF>(self, collate_fn: CF) -> Builder<D, S, CF>
-    where
-        CF: Collate<D::Sample>,
-    {
+    fn with_collate_fn<CF>(self, collate_fn: CF) -> Builder<D, S, CF> {
         Builder {
             dataset: self.dataset,
-
             batch_sampler: self.batch_sampler,
             collate_fn,
+            seed: self.seed,
             #[cfg(feature = "rayon")]
             num_threads: self.num_threads,
+            #[cfg(feature = "rayon")]
+            thread_pool: self.thread_pool,
+            #[cfg(feature = "rayon")]
+            timeout: self.timeout,
+            #[cfg(feature = "rayon")]
+            timeout_retries: self.timeout_retries,
+            #[cfg(feature = "rayon")]
+            out_of_order: self.out_of_order,
+            #[cfg(feature = "rayon")]
+            worker_init: self.worker_init,
+            #[cfg(feature = "async")]
+            max_concurrency: self.max_concurrency,
         }
     }
 
@@ -111,6 +255,13 @@ where
         SA: Sampler,
     {
         let sampler: SA = SA::new(self.dataset.len());
+        self.with_sampler(sampler)
+    }
+    /// Set a custom [`Sampler`] instance, for samplers with parameters.
+    pub fn with_sampler<SA>(self, sampler: SA) -> Builder<D, SA, C>
+    where
+        SA: Sampler,
+    {
         Builder {
             dataset: self.dataset,
             batch_sampler: BatchSampler {
@@ -120,46 +271,137 @@ where
             },
 
             collate_fn: self.collate_fn,
+            seed: self.seed,
             #[cfg(feature = "rayon")]
             num_threads: self.num_threads,
+            #[cfg(feature = "rayon")]
+            thread_pool: self.thread_pool,
+            #[cfg(feature = "rayon")]
+            timeout: self.timeout,
+            #[cfg(feature = "rayon")]
+            timeout_retries: self.timeout_retries,
+            #[cfg(feature = "rayon")]
+            out_of_order: self.out_of_order,
+            #[cfg(feature = "rayon")]
+            worker_init: self.worker_init,
+            #[cfg(feature = "async")]
+            max_concurrency: self.max_concurrency,
         }
     }
     /// Create a `Dataloader` from a [`Builder`].
     pub fn build(self) -> DataLoader<D, S, C> {
+        // The pool is only created when the first samples are fetched on it.
         #[cfg(feature = "rayon")]
-        if let Some(pool) = THREAD_POOL.get() {
-            if pool.current_num_threads() != self.num_threads {
-                // We reset the threadpool because we can't modify the number of
-                // threads of an existing thread pool.
-                #[cfg(feature = "rayon")]
-                THREAD_POOL
-                    .set(
-                        rayon::ThreadPoolBuilder::new()
-                            .num_threads(self.num_threads)
-                            .build()
-                            .expect("could not spawn threads"),
-                    )
-                    .ok();
-            }
-        } else {
-            THREAD_POOL
-                .set(
-                    rayon::ThreadPoolBuilder::new()
-                        .num_threads(self.num_threads)
-                        .build()
-                        .expect("could not spawn threads"),
-                )
-                .ok();
-        }
+        let thread_pool = Arc::new(match self.thread_pool {
+            Some(thread_pool) => WorkerPool::with_pool(thread_pool),
+            None => WorkerPool::new(
+                self.num_threads,
+                self.seed.unwrap_or_else(rand::random),
+                self.worker_init,
+            ),
+        });
 
         DataLoader {
-            dataset: self.dataset,
+            dataset: Arc::new(self.dataset),
             batch_sampler: self.batch_sampler,
             collate_fn: self.collate_fn,
+            seed: self.seed,
+            epoch: 0,
+            iterations: Iterations::default(),
+            #[cfg(feature = "rayon")]
+            thread_pool,
+            #[cfg(feature = "rayon")]
+            timeout: self.timeout.map(|timeout| Timeout {
+                retries: self.timeout_retries,
+                ..timeout
+            }),
+            #[cfg(feature = "rayon")]
+            out_of_order: self.out_of_order,
+            #[cfg(feature = "async")]
+            max_concurrency: self.max_concurrency,
         }
     }
 }
 
+#[cfg(feature = "rayon")]
+impl<D, S, C> Builder<D, S, C>
+where
+    D: Dataset + Send + Sync + 'static,
+    D::Sample: Send + 'static,
+    S: Sampler,
+{
+    /// Give up on a batch if its samples aren't all fetched after `timeout`, instead of waiting forever for a hung
+    /// read. The iterator then panics, or yields a `TimeoutError` with the indices of the pending samples with
+    /// `DataLoader::try_iter`.
+    ///
+    /// With a timeout, the samples are dispatched one by one in the thread pool so the loader can stop waiting for
+    /// them. A hung sample keeps its thread busy, as it can't be interrupted.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(Timeout::new(timeout));
+        self
+    }
+
+    /// Yield the batches in the order of the sampler (the default), or as soon as they are ready with `false`, so a
+    /// slow sample only holds back its own batch.
+    ///
+    /// Out of order, one batch per thread of the pool is fetched concurrently, with its samples dispatched one by one,
+    /// and the iterators yield the first one whose samples are all fetched. Each batch keeps its indices and its
+    /// `step` in the epoch, given by `DataLoader::iter_with_meta`, so every sample of the epoch is still yielded
+    /// exactly once. The samples and their random transforms are the same in both modes, only the order of the
+    /// batches changes. With a `timeout`, it runs from the dispatch of the batch.
+    ///
+    /// An epoch yielded out of order can't be resumed from a [`LoaderState`](crate::LoaderState): its `step` counts
+    /// the batches yielded, which aren't the first ones of the sampler, so `Loader::restore` panics if it isn't zero.
+    #[allow(clippy::fn_params_excessive_bools)]
+    pub fn in_order(mut self, in_order: bool) -> Self {
+        self.out_of_order = (!in_order).then(OutOfOrder::new);
+        self
+    }
+}
+
+impl<D, S, C> Builder<D, S, C>
+where
+    D: Dataset,
+    S: Sampler,
+    C: Collate<D::Sample>,
+{
+    /// Set a custom collate function.
+    pub fn collate_fn<CF>(self, collate_fn: CF) -> Builder<D, S, CF>
+    where
+        CF: Collate<D::Sample>,
+    {
+        self.with_collate_fn(collate_fn)
+    }
+}
+
+impl<D, S, C> Builder<D, S, C>
+where
+    D: GetSampleRef + Len,
+    S: Sampler,
+{
+    /// Set a custom collate function, for a dataset lending its samples.
+    ///
+    /// The collate function must implement `Collate<D::SampleRef<'_>>` to iterate with [`DataLoader::iter_ref`].
+    pub fn collate_fn_ref<CF>(self, collate_fn: CF) -> Builder<D, S, CF> {
+        self.with_collate_fn(collate_fn)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<D, S, C> Builder<D, S, C>
+where
+    D: AsyncGetSample + Len,
+    S: Sampler,
+{
+    /// Set a custom collate function, for an async dataset.
+    pub fn collate_fn_async<CF>(self, collate_fn: CF) -> Builder<D, S, CF>
+    where
+        CF: Collate<D::Sample>,
+    {
+        self.with_collate_fn(collate_fn)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -213,4 +455,40 @@ mod tests {
             .batch_size(2)
             .build();
     }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn thread_pools() {
+        let train = Builder::new(vec![1, 2, 3, 4]).num_threads(3).build();
+        let eval = Builder::new(vec![1, 2, 3, 4]).num_threads(1).build();
+        assert_eq!(train.thread_pool().current_num_threads(), 3);
+        assert_eq!(eval.thread_pool().current_num_threads(), 1);
+
+        let pool = Arc::new(
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(2)
+                .build()
+                .unwrap(),
+        );
+        let first = Builder::new(vec![1, 2, 3, 4])
+            .num_threads(5)
+            .thread_pool(Arc::clone(&pool))
+            .build();
+        let second = Builder::new(vec![1, 2, 3, 4])
+            .thread_pool(Arc::clone(&pool))
+            .build();
+        assert!(Arc::ptr_eq(&first.thread_pool(), &second.thread_pool()));
+        assert_eq!(first.thread_pool().current_num_threads(), 2);
+        assert_eq!(
+            first.iter().collect::<Vec<_>>(),
+            second.iter().collect::<Vec<_>>()
+        );
+
+        let owned = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+        let loader = Builder::new(vec![1, 2, 3, 4]).thread_pool(owned).build();
+        assert_eq!(loader.thread_pool().current_num_threads(), 1);
+    }
 }
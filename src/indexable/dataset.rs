@@ -1,11 +1,54 @@
 use std::collections::VecDeque;
 
+#[cfg(feature = "async")]
+mod async_get_sample;
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub use async_get_sample::AsyncGetSample;
+#[cfg(feature = "burn")]
+mod burn_dataset;
+#[cfg(feature = "burn")]
+#[cfg_attr(docsrs, doc(cfg(feature = "burn")))]
+pub use burn_dataset::{FromBurn, ToBurn};
+mod cached_dataset;
+pub use cached_dataset::CachedDataset;
+#[cfg(feature = "cache")]
+mod disk_cached_dataset;
+#[cfg(feature = "cache")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cache")))]
+pub use disk_cached_dataset::DiskCachedDataset;
 mod len;
 pub use len::Len;
 mod ndarray_dataset;
+#[cfg(feature = "process")]
+mod process_dataset;
+#[cfg(feature = "process")]
+#[cfg_attr(docsrs, doc(cfg(feature = "process")))]
+pub use process_dataset::{run_worker, ProcessDataset};
+mod sharded_dataset;
 pub use ndarray_dataset::NdarrayDataset;
+pub use sharded_dataset::ShardedDataset;
 mod get_sample;
 pub use get_sample::GetSample;
+mod get_sample_ref;
+pub use get_sample_ref::GetSampleRef;
+#[cfg(feature = "json")]
+mod json_lines_dataset;
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+pub use json_lines_dataset::{JsonLines, JsonLinesDataset};
+#[cfg(feature = "parquet")]
+mod parquet_dataset;
+#[cfg(feature = "parquet")]
+#[cfg_attr(docsrs, doc(cfg(feature = "parquet")))]
+pub use parquet_dataset::ParquetDataset;
+#[cfg(feature = "tfrecord")]
+mod tfrecord_dataset;
+#[cfg(feature = "tfrecord")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tfrecord")))]
+pub use tfrecord_dataset::{Example, Feature, TfRecordDataset, TfRecordIter};
+#[cfg(any(feature = "json", feature = "tfrecord"))]
+mod read_at;
 
 /// A dataset is just something that has a length and is indexable.
 /// A `Vec` of `dataset` collate output must also be collatable.
@@ -38,6 +81,7 @@ pub trait Dataset: Len + GetSample {}
 
 /// Dataset could become something like that when functor trait will be available.
 #[doc(hidden)]
+#[allow(dead_code)]
 trait FunctorDataset<F>: Len + GetSample
 where
     F: Fn(Vec<Self::Sample>) -> Self::CollateOutput,
@@ -0,0 +1,59 @@
+use std::future::{self, Future, Ready};
+
+/// Return a future resolving to a sample of the dataset at a given index, for datasets whose samples are fetched
+/// with async I/O, from an object store for instance.
+///
+/// Iterating with [`DataLoader::stream_async`](crate::indexable::DataLoader::stream_async) or
+/// [`DataLoader::iter_async`](crate::indexable::DataLoader::iter_async), the futures of a batch are polled
+/// concurrently, and the samples are collated in the order of the sampler.
+///
+/// ```
+/// use ai_dataloader::indexable::{AsyncGetSample, DataLoader, Len};
+/// use futures_util::future::BoxFuture;
+///
+/// struct Remote(Vec<String>);
+///
+/// impl Len for Remote {
+///     fn len(&self) -> usize {
+///         self.0.len()
+///     }
+/// }
+///
+/// impl AsyncGetSample for Remote {
+///     type Sample = String;
+///     type Future<'a> = BoxFuture<'a, String>;
+///     fn get_sample_async(&self, index: usize) -> Self::Future<'_> {
+///         Box::pin(async move {
+///             // A request to an async client would be awaited here.
+///             self.0[index].clone()
+///         })
+///     }
+/// }
+///
+/// let loader = DataLoader::builder_async(Remote(vec!["a".into(), "b".into()])).batch_size(2).build();
+/// let runtime = tokio::runtime::Builder::new_multi_thread().build().unwrap();
+/// for batch in loader.iter_async(runtime.handle().clone()) {
+///     assert_eq!(batch, ["a", "b"]);
+/// }
+/// ```
+pub trait AsyncGetSample {
+    /// Type of one sample of the dataset.
+    type Sample;
+    /// Future resolving to a sample, which may borrow from the dataset.
+    type Future<'a>: Future<Output = Self::Sample> + 'a
+    where
+        Self: 'a;
+    /// Return a future resolving to the dataset sample corresponding to the index.
+    fn get_sample_async(&self, index: usize) -> Self::Future<'_>;
+}
+
+impl<T: Clone> AsyncGetSample for Vec<T> {
+    type Sample = T;
+    type Future<'a>
+        = Ready<T>
+    where
+        T: 'a;
+    fn get_sample_async(&self, index: usize) -> Self::Future<'_> {
+        future::ready(self[index].clone())
+    }
+}
@@ -0,0 +1,207 @@
+use super::{Dataset, GetSample};
+use crate::Len;
+use std::{fmt, marker::PhantomData};
+
+/// Dataset wrapper making a dataset of this crate usable as a `burn` [`Dataset`](burn::data::dataset::Dataset), by a
+/// burn `DataLoader` for instance.
+///
+/// `get` returns `None` past the end of the dataset. The samples are read with [`GetSample::get_sample`], so the random
+/// transforms aren't seeded by burn.
+///
+/// ```
+/// use ai_dataloader::indexable::ToBurn;
+/// use burn::data::dataset::Dataset;
+///
+/// let dataset = ToBurn::new(vec![(0, "hola"), (1, "hello")]);
+/// assert_eq!(dataset.get(1), Some((1, "hello")));
+/// assert_eq!(dataset.get(2), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ToBurn<D> {
+    /// The wrapped dataset.
+    dataset: D,
+}
+
+impl<D> ToBurn<D> {
+    /// Wrap a dataset of this crate.
+    pub fn new(dataset: D) -> Self {
+        Self { dataset }
+    }
+
+    /// Return a reference to the wrapped dataset.
+    pub fn get_ref(&self) -> &D {
+        &self.dataset
+    }
+
+    /// Unwrap the dataset.
+    pub fn into_inner(self) -> D {
+        self.dataset
+    }
+}
+
+impl<D> burn::data::dataset::Dataset<D::Sample> for ToBurn<D>
+where
+    D: Dataset + Send + Sync,
+{
+    fn get(&self, index: usize) -> Option<D::Sample> {
+        (index < self.dataset.len()).then(|| self.dataset.get_sample(index))
+    }
+
+    fn len(&self) -> usize {
+        self.dataset.len()
+    }
+}
+
+/// Dataset wrapper making a `burn` [`Dataset`](burn::data::dataset::Dataset) of items `I` usable with the indexable
+/// [`DataLoader`](crate::indexable::DataLoader), to use the samplers and the parallel fetching of this crate.
+///
+/// Combined with a [`BatcherCollate`](crate::collate::BatcherCollate), the batches are created by a burn `Batcher`.
+///
+/// ```
+/// use ai_dataloader::indexable::{DataLoader, FromBurn};
+/// use burn::data::dataset::InMemDataset;
+///
+/// let dataset = FromBurn::new(InMemDataset::new(vec![1_i32, 2, 3, 4]));
+/// let loader = DataLoader::builder(dataset).batch_size(2).build();
+/// assert_eq!(loader.iter().map(|batch| batch.sum()).collect::<Vec<_>>(), [3, 7]);
+/// ```
+pub struct FromBurn<D, I> {
+    /// The wrapped dataset.
+    dataset: D,
+    _item: PhantomData<fn() -> I>,
+}
+
+impl<D, I> FromBurn<D, I>
+where
+    D: burn::data::dataset::Dataset<I>,
+{
+    /// Wrap a `burn` dataset.
+    pub fn new(dataset: D) -> Self {
+        Self {
+            dataset,
+            _item: PhantomData,
+        }
+    }
+}
+
+impl<D, I> FromBurn<D, I> {
+    /// Return a reference to the wrapped dataset.
+    pub fn get_ref(&self) -> &D {
+        &self.dataset
+    }
+
+    /// Unwrap the dataset.
+    pub fn into_inner(self) -> D {
+        self.dataset
+    }
+}
+
+impl<D: Clone, I> Clone for FromBurn<D, I> {
+    fn clone(&self) -> Self {
+        Self {
+            dataset: self.dataset.clone(),
+            _item: PhantomData,
+        }
+    }
+}
+
+impl<D: fmt::Debug, I> fmt::Debug for FromBurn<D, I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FromBurn")
+            .field("dataset", &self.dataset)
+            .finish()
+    }
+}
+
+impl<D, I> Len for FromBurn<D, I>
+where
+    D: burn::data::dataset::Dataset<I>,
+{
+    fn len(&self) -> usize {
+        self.dataset.len()
+    }
+}
+
+impl<D, I> GetSample for FromBurn<D, I>
+where
+    D: burn::data::dataset::Dataset<I>,
+{
+    type Sample = I;
+    /// # Panics
+    ///
+    /// Panics if the `burn` dataset has no item at `index`.
+    fn get_sample(&self, index: usize) -> Self::Sample {
+        self.dataset.get(index).unwrap_or_else(|| {
+            panic!(
+                "index {index} out of bounds for a burn dataset of {} items",
+                self.dataset.len()
+            )
+        })
+    }
+}
+
+impl<D, I> Dataset for FromBurn<D, I> where D: burn::data::dataset::Dataset<I> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "_test-burn-ndarray")]
+    use crate::{collate::BatcherCollate, indexable::DataLoader};
+    use burn::data::dataset::InMemDataset;
+    #[cfg(feature = "_test-burn-ndarray")]
+    use burn::{
+        backend::{ndarray::NdArrayDevice, NdArray},
+        data::dataloader::batcher::Batcher,
+        tensor::{backend::Backend, Tensor, TensorData},
+    };
+
+    /// Stack the items into a tensor of the batch.
+    #[cfg(feature = "_test-burn-ndarray")]
+    #[derive(Debug, Clone)]
+    struct Stack;
+
+    #[cfg(feature = "_test-burn-ndarray")]
+    impl<B: Backend> Batcher<B, f32, Tensor<B, 1>> for Stack {
+        fn batch(&self, items: Vec<f32>, device: &B::Device) -> Tensor<B, 1> {
+            Tensor::from_data(TensorData::from(items.as_slice()), device)
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let dataset = ToBurn::new(vec![0_i32, 1, 2, 3, 4]);
+        assert_eq!(burn::data::dataset::Dataset::len(&dataset), 5);
+        let items: Vec<_> = burn::data::dataset::Dataset::iter(&dataset).collect();
+        assert_eq!(items, [0, 1, 2, 3, 4]);
+
+        let dataset = FromBurn::new(dataset);
+        assert_eq!(dataset.len(), 5);
+        assert_eq!(dataset.get_sample(3), 3);
+        assert_eq!(dataset.into_inner().into_inner().len(), 5);
+    }
+
+    #[test]
+    #[cfg(feature = "_test-burn-ndarray")]
+    fn batcher_collate() {
+        let dataset = FromBurn::new(InMemDataset::new(vec![0_f32, 1., 2., 3., 4.]));
+        let loader = DataLoader::builder(dataset)
+            .batch_size(2)
+            .collate_fn(BatcherCollate::<_, NdArray, _>::new(
+                Stack,
+                NdArrayDevice::Cpu,
+            ))
+            .build();
+        let batches: Vec<Vec<f32>> = loader
+            .iter()
+            .map(|batch| batch.into_data().to_vec().unwrap())
+            .collect();
+        assert_eq!(batches, [vec![0., 1.], vec![2., 3.], vec![4.]]);
+    }
+
+    #[test]
+    #[should_panic(expected = "index 5 out of bounds for a burn dataset of 5 items")]
+    fn out_of_bounds() {
+        let dataset = FromBurn::new(InMemDataset::new(vec![0_i32; 5]));
+        let _ = dataset.get_sample(5);
+    }
+}
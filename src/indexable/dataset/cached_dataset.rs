@@ -0,0 +1,242 @@
+use super::{Dataset, GetSample};
+use crate::Len;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex, MutexGuard, PoisonError,
+    },
+};
+
+/// Dataset wrapper memoizing the samples of another dataset in a least recently used cache.
+///
+/// The cache is shared between the threads of the loader and holds at most `capacity` samples, the least recently
+/// used sample being evicted first. A sample fetched concurrently by two threads before being cached may be computed
+/// twice.
+///
+/// Only [`GetSample::get_sample`] is cached, the random transforms of a [`Transformed`](crate::transforms::Transformed)
+/// dataset wrapping the cache are still applied on each epoch.
+///
+/// ```
+/// use ai_dataloader::{indexable::CachedDataset, GetSample};
+///
+/// let dataset = CachedDataset::new(vec![1, 2, 3, 4], 2);
+/// assert_eq!(dataset.get_sample(0), 1);
+/// assert_eq!(dataset.get_sample(0), 1);
+/// assert_eq!((dataset.hits(), dataset.misses()), (1, 1));
+/// ```
+pub struct CachedDataset<D: GetSample> {
+    /// The wrapped dataset.
+    dataset: D,
+    /// Maximum number of samples in the cache.
+    capacity: usize,
+    cache: Mutex<Lru<D::Sample>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<D: GetSample> CachedDataset<D> {
+    /// Wrap a dataset in a cache holding at most `capacity` samples.
+    pub fn new(dataset: D, capacity: usize) -> Self {
+        Self {
+            dataset,
+            capacity,
+            cache: Mutex::new(Lru::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Return the maximum number of samples in the cache.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Return the number of samples currently in the cache.
+    pub fn cached(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Return the number of samples served from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Return the number of samples fetched from the wrapped dataset.
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Remove all the samples from the cache.
+    pub fn clear(&self) {
+        *self.lock() = Lru::default();
+    }
+
+    /// Return a reference to the wrapped dataset.
+    pub fn get_ref(&self) -> &D {
+        &self.dataset
+    }
+
+    /// Unwrap the dataset, dropping the cache.
+    pub fn into_inner(self) -> D {
+        self.dataset
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Lru<D::Sample>> {
+        // A panic while holding the lock can't leave the cache in an inconsistent state.
+        self.cache.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<D> fmt::Debug for CachedDataset<D>
+where
+    D: GetSample + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CachedDataset")
+            .field("dataset", &self.dataset)
+            .field("capacity", &self.capacity)
+            .field("cached", &self.cached())
+            .field("hits", &self.hits())
+            .field("misses", &self.misses())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<D: GetSample + Len> Len for CachedDataset<D> {
+    fn len(&self) -> usize {
+        self.dataset.len()
+    }
+}
+
+impl<D> GetSample for CachedDataset<D>
+where
+    D: GetSample,
+    D::Sample: Clone,
+{
+    type Sample = D::Sample;
+    fn get_sample(&self, index: usize) -> Self::Sample {
+        if let Some(sample) = self.lock().get(index) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return sample;
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        // The lock isn't held while fetching, so the other threads aren't blocked by a slow sample.
+        let sample = self.dataset.get_sample(index);
+        if self.capacity > 0 {
+            self.lock().insert(index, sample.clone(), self.capacity);
+        }
+        sample
+    }
+}
+
+impl<D> Dataset for CachedDataset<D>
+where
+    D: Dataset,
+    D::Sample: Clone,
+{
+}
+
+/// Least recently used cache, keyed by sample index.
+struct Lru<T> {
+    /// Sample and last use of each cached index.
+    entries: HashMap<usize, (T, u64)>,
+    /// Cached index of each last use, the least recent first.
+    recency: BTreeMap<u64, usize>,
+    /// Incremented on each use.
+    clock: u64,
+}
+
+impl<T> Default for Lru<T> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            clock: 0,
+        }
+    }
+}
+
+impl<T: Clone> Lru<T> {
+    fn get(&mut self, index: usize) -> Option<T> {
+        let (sample, last_use) = self.entries.get_mut(&index)?;
+        self.recency.remove(last_use);
+        self.clock += 1;
+        *last_use = self.clock;
+        self.recency.insert(self.clock, index);
+        Some(sample.clone())
+    }
+
+    fn insert(&mut self, index: usize, sample: T, capacity: usize) {
+        self.clock += 1;
+        if let Some((_, last_use)) = self.entries.insert(index, (sample, self.clock)) {
+            self.recency.remove(&last_use);
+        }
+        self.recency.insert(self.clock, index);
+        while self.entries.len() > capacity {
+            let Some((&last_use, &evicted)) = self.recency.iter().next() else {
+                break;
+            };
+            self.recency.remove(&last_use);
+            self.entries.remove(&evicted);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexable::DataLoader;
+    use std::sync::{atomic::AtomicUsize, Arc};
+
+    /// Count the calls to `get_sample`.
+    #[derive(Debug)]
+    struct Counted(Arc<AtomicUsize>);
+
+    impl Len for Counted {
+        fn len(&self) -> usize {
+            5
+        }
+    }
+    impl GetSample for Counted {
+        type Sample = usize;
+        fn get_sample(&self, index: usize) -> usize {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            index
+        }
+    }
+    impl Dataset for Counted {}
+
+    #[test]
+    fn lru_eviction() {
+        let dataset = CachedDataset::new(vec![0, 1, 2, 3], 2);
+        dataset.get_sample(0);
+        dataset.get_sample(1);
+        // 0 becomes the most recently used, so 1 is evicted.
+        dataset.get_sample(0);
+        dataset.get_sample(2);
+        assert_eq!(dataset.cached(), 2);
+        assert_eq!((dataset.hits(), dataset.misses()), (1, 3));
+
+        dataset.get_sample(0);
+        assert_eq!(dataset.hits(), 2);
+        dataset.get_sample(1);
+        assert_eq!(dataset.misses(), 4);
+
+        dataset.clear();
+        assert_eq!(dataset.cached(), 0);
+    }
+
+    #[test]
+    fn loader() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let dataset = CachedDataset::new(Counted(Arc::clone(&calls)), 10);
+        let loader = DataLoader::builder(dataset).batch_size(2).build();
+        for _ in 0..3 {
+            let batches: Vec<_> = loader.iter().collect();
+            assert_eq!(batches.len(), 3);
+        }
+        assert_eq!(calls.load(Ordering::Relaxed), 5);
+    }
+}
@@ -0,0 +1,261 @@
+use super::{Dataset, GetSample};
+use crate::Len;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    fmt, fs,
+    io::{self, BufReader, BufWriter},
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Name of the file storing the fingerprint of the cached dataset.
+const FINGERPRINT_FILE: &str = "FINGERPRINT";
+/// Extension of the cached samples.
+const SAMPLE_EXTENSION: &str = "bin";
+
+/// Dataset wrapper caching the samples of another dataset on disk.
+///
+/// Each sample is serialized with [`bincode`] into its own file of the cache directory the first time it is fetched,
+/// and read back from it afterwards, including by the next runs. The cache is tied to a fingerprint supplied by the
+/// user, for instance a hash of the raw files and of the decoding parameters: when it changes, the cached samples are
+/// removed.
+///
+/// Writing to the cache is best-effort: if a sample can't be written it is still returned, and fetched again from the
+/// wrapped dataset next time. A cached sample that can't be read back is fetched again and overwritten.
+///
+/// Only [`GetSample::get_sample`] is cached, the random transforms of a [`Transformed`](crate::transforms::Transformed)
+/// dataset wrapping the cache are still applied on each epoch.
+///
+/// ```no_run
+/// use ai_dataloader::indexable::{DataLoader, DiskCachedDataset};
+///
+/// # fn main() -> std::io::Result<()> {
+/// let dataset = vec![(0, "I'm happy".to_owned()), (1, "I'm sad".to_owned())];
+/// let dataset = DiskCachedDataset::open(dataset, "cache/reviews", "reviews-v1")?;
+/// let loader = DataLoader::builder(dataset).batch_size(2).shuffle().build();
+///
+/// for (label, text) in &loader {
+///     println!("Label {label:?}");
+///     println!("Text {text:?}");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct DiskCachedDataset<D> {
+    /// The wrapped dataset.
+    dataset: D,
+    /// Directory of the cached samples.
+    dir: PathBuf,
+    /// Fingerprint of the cached samples.
+    fingerprint: String,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    /// Used to give a unique name to the temporary files.
+    writes: AtomicU64,
+}
+
+impl<D> DiskCachedDataset<D> {
+    /// Wrap a dataset in a cache stored in the directory `dir`, which is created if needed.
+    ///
+    /// If the directory holds samples cached with another fingerprint, they are removed.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the directory can't be created, or if the stale samples can't be removed.
+    pub fn open<P: AsRef<Path>>(
+        dataset: D,
+        dir: P,
+        fingerprint: impl Into<String>,
+    ) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        let fingerprint = fingerprint.into();
+        fs::create_dir_all(&dir)?;
+        let fingerprint_path = dir.join(FINGERPRINT_FILE);
+        let cached = match fs::read_to_string(&fingerprint_path) {
+            Ok(cached) => Some(cached),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+            Err(err) => return Err(err),
+        };
+        let cache = Self {
+            dataset,
+            dir,
+            fingerprint,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            writes: AtomicU64::new(0),
+        };
+        if cached.as_deref() != Some(cache.fingerprint.as_str()) {
+            cache.clear()?;
+            fs::write(&fingerprint_path, &cache.fingerprint)?;
+        }
+        Ok(cache)
+    }
+
+    /// Return the directory of the cached samples.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Return the fingerprint of the cached samples.
+    pub fn fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+
+    /// Return the number of samples read from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Return the number of samples fetched from the wrapped dataset.
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Remove all the cached samples.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the cache directory can't be read or if a sample can't be removed.
+    pub fn clear(&self) -> io::Result<()> {
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .map_or(false, |ext| ext == SAMPLE_EXTENSION)
+            {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Return a reference to the wrapped dataset.
+    pub fn get_ref(&self) -> &D {
+        &self.dataset
+    }
+
+    /// Unwrap the dataset. The cached samples are kept on disk.
+    pub fn into_inner(self) -> D {
+        self.dataset
+    }
+
+    /// Return the path of the cached sample at the given index.
+    fn sample_path(&self, index: usize) -> PathBuf {
+        self.dir.join(format!("{index}.{SAMPLE_EXTENSION}"))
+    }
+}
+
+impl<D> DiskCachedDataset<D>
+where
+    D: GetSample,
+    D::Sample: Serialize + DeserializeOwned,
+{
+    fn read(&self, index: usize) -> Option<D::Sample> {
+        let file = fs::File::open(self.sample_path(index)).ok()?;
+        bincode::deserialize_from(BufReader::new(file)).ok()
+    }
+
+    fn write(&self, index: usize, sample: &D::Sample) -> io::Result<()> {
+        // The sample is written to a temporary file then renamed, so a sample being written by a thread (or another
+        // process) is never read partially.
+        let tmp = self.dir.join(format!(
+            "{index}.{}-{}.tmp",
+            process::id(),
+            self.writes.fetch_add(1, Ordering::Relaxed)
+        ));
+        let result = fs::File::create(&tmp).and_then(|file| {
+            bincode::serialize_into(BufWriter::new(file), sample)
+                .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+            fs::rename(&tmp, self.sample_path(index))
+        });
+        if result.is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+        result
+    }
+}
+
+impl<D: fmt::Debug> fmt::Debug for DiskCachedDataset<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DiskCachedDataset")
+            .field("dataset", &self.dataset)
+            .field("dir", &self.dir)
+            .field("fingerprint", &self.fingerprint)
+            .field("hits", &self.hits())
+            .field("misses", &self.misses())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<D: Len> Len for DiskCachedDataset<D> {
+    fn len(&self) -> usize {
+        self.dataset.len()
+    }
+}
+
+impl<D> GetSample for DiskCachedDataset<D>
+where
+    D: GetSample,
+    D::Sample: Serialize + DeserializeOwned,
+{
+    type Sample = D::Sample;
+    fn get_sample(&self, index: usize) -> Self::Sample {
+        if let Some(sample) = self.read(index) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return sample;
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let sample = self.dataset.get_sample(index);
+        // The cache is best-effort, the sample will be fetched again next time.
+        let _ = self.write(index, &sample);
+        sample
+    }
+}
+
+impl<D> Dataset for DiskCachedDataset<D>
+where
+    D: Dataset,
+    D::Sample: Serialize + DeserializeOwned,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexable::DataLoader;
+    use ndarray::{arr1, Array1};
+
+    #[test]
+    fn fingerprint() {
+        let dir = tempfile::tempdir().unwrap();
+        let samples = vec![arr1(&[1., 2.]), arr1(&[3., 4.]), arr1(&[5., 6.])];
+
+        let dataset = DiskCachedDataset::open(samples.clone(), dir.path(), "v1").unwrap();
+        let loader = DataLoader::builder(dataset).batch_size(2).build();
+        let first: Vec<_> = loader.iter().collect();
+
+        // The samples are read back from the disk, even by a new dataset.
+        let dataset = DiskCachedDataset::open(Vec::<Array1<f64>>::new(), dir.path(), "v1").unwrap();
+        for (index, sample) in samples.iter().enumerate() {
+            assert_eq!(&dataset.get_sample(index), sample);
+        }
+        assert_eq!((dataset.hits(), dataset.misses()), (3, 0));
+        let loader = DataLoader::builder(
+            DiskCachedDataset::open(samples.clone(), dir.path(), "v1").unwrap(),
+        )
+        .batch_size(2)
+        .build();
+        assert_eq!(loader.iter().collect::<Vec<_>>(), first);
+
+        // A new fingerprint invalidates the cache.
+        let changed = vec![arr1(&[0., 0.]); 3];
+        let dataset = DiskCachedDataset::open(changed.clone(), dir.path(), "v2").unwrap();
+        assert_eq!(dataset.get_sample(1), changed[1]);
+        assert_eq!((dataset.hits(), dataset.misses()), (0, 1));
+        assert_eq!(
+            fs::read_to_string(dir.path().join(FINGERPRINT_FILE)).unwrap(),
+            "v2"
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use rand::rngs::StdRng;
 use std::collections::VecDeque;
 
 /// Return a sample from the dataset at a given index.
@@ -6,6 +7,14 @@ pub trait GetSample {
     type Sample: Sized;
     /// Return the dataset sample corresponding to the index.
     fn get_sample(&self, index: usize) -> Self::Sample;
+    /// Return the dataset sample corresponding to the index, drawing any randomness (data augmentation for instance)
+    /// from `rng`.
+    ///
+    /// The indexable `DataLoader` calls this method with a generator seeded from its seed, the current epoch, the
+    /// iteration in the epoch and the index, so that random transforms are reproducible. By default `rng` is ignored.
+    fn get_sample_with_rng(&self, index: usize, _rng: &mut StdRng) -> Self::Sample {
+        self.get_sample(index)
+    }
 }
 
 impl<T: Clone> GetSample for Vec<T> {
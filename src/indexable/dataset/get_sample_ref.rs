@@ -0,0 +1,107 @@
+use super::NdarrayDataset;
+use ndarray::{ArrayView, Axis, Dimension, RemoveAxis};
+use std::collections::VecDeque;
+
+/// Return a sample borrowed from the dataset at a given index.
+///
+/// Unlike [`GetSample`](super::GetSample), the sample can borrow from the dataset, for instance a reference or an
+/// `ArrayView`. Iterating with [`DataLoader::iter_ref`](crate::indexable::DataLoader::iter_ref), the collate function
+/// builds the batch straight from the borrowed samples, without copying them into owned samples first.
+///
+/// The default collate stacks `ArrayView` directly, and collates the references (`&T`) to the types implementing
+/// [`CollateRef`](crate::collate::CollateRef) without cloning them, borrowed arrays being stacked from their views.
+///
+/// ```
+/// use ai_dataloader::indexable::{DataLoader, GetSampleRef, Len};
+/// use ndarray::{Array2, ArrayView1, Axis};
+///
+/// struct Embeddings(Array2<f32>);
+///
+/// impl Len for Embeddings {
+///     fn len(&self) -> usize {
+///         self.0.nrows()
+///     }
+/// }
+///
+/// impl GetSampleRef for Embeddings {
+///     type SampleRef<'a> = ArrayView1<'a, f32>;
+///     fn get_sample_ref(&self, index: usize) -> Self::SampleRef<'_> {
+///         self.0.index_axis(Axis(0), index)
+///     }
+/// }
+///
+/// let loader = DataLoader::builder_ref(Embeddings(Array2::ones((4, 8)))).batch_size(2).build();
+/// for batch in loader.iter_ref() {
+///     assert_eq!(batch.shape(), [2, 8]);
+/// }
+/// ```
+pub trait GetSampleRef {
+    /// Type of one sample of the dataset, borrowing from it.
+    type SampleRef<'a>
+    where
+        Self: 'a;
+    /// Return the dataset sample corresponding to the index.
+    fn get_sample_ref(&self, index: usize) -> Self::SampleRef<'_>;
+}
+
+impl<T> GetSampleRef for Vec<T> {
+    type SampleRef<'a>
+        = &'a T
+    where
+        T: 'a;
+    fn get_sample_ref(&self, index: usize) -> Self::SampleRef<'_> {
+        &self[index]
+    }
+}
+
+impl<T> GetSampleRef for VecDeque<T> {
+    type SampleRef<'a>
+        = &'a T
+    where
+        T: 'a;
+    fn get_sample_ref(&self, index: usize) -> Self::SampleRef<'_> {
+        &self[index]
+    }
+}
+
+impl<A1, A2, D1, D2> GetSampleRef for NdarrayDataset<A1, A2, D1, D2>
+where
+    A1: Clone,
+    A2: Clone,
+    D1: Dimension + RemoveAxis,
+    D2: Dimension + RemoveAxis,
+{
+    type SampleRef<'a>
+        = (
+        ArrayView<'a, A1, <D1 as Dimension>::Smaller>,
+        ArrayView<'a, A2, <D2 as Dimension>::Smaller>,
+    )
+    where
+        Self: 'a;
+    fn get_sample_ref(&self, index: usize) -> Self::SampleRef<'_> {
+        (
+            self.ndarrays.0.index_axis(Axis(0), index),
+            self.ndarrays.1.index_axis(Axis(0), index),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    #[test]
+    fn borrowed_samples() {
+        let dataset = vec![String::from("a"), String::from("b")];
+        assert!(std::ptr::eq(dataset.get_sample_ref(1), &dataset[1]));
+
+        let dataset = NdarrayDataset {
+            ndarrays: (array![[1, 2], [3, 4]], array![0, 1]),
+        };
+        let (features, label) = dataset.get_sample_ref(1);
+        assert_eq!(features, array![3, 4]);
+        assert_eq!(label, ndarray::arr0(1));
+        assert_eq!(features.as_ptr(), dataset.ndarrays.0.row(1).as_ptr());
+    }
+}
@@ -0,0 +1,518 @@
+use super::{read_at::read_exact_at, Dataset, GetSample};
+use crate::Len;
+use serde::de::DeserializeOwned;
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+/// Header of the index cache file, followed by the format version.
+const INDEX_MAGIC: &[u8; 8] = b"AIDLJSL1";
+
+/// Dataset backed by a [JSON Lines](https://jsonlines.org/) file.
+///
+/// The byte offset of each line is indexed once when the dataset is opened, then each sample is read and
+/// deserialized on demand. Blank lines are skipped. The sample type can be any type implementing
+/// [`serde::Deserialize`], or a dynamic [`serde_json::Value`] (the default).
+///
+/// The dataset can also be used as a streaming source for the [iterable `DataLoader`](crate::iterable::DataLoader),
+/// in which case the file is read sequentially. If you don't need random access at all, [`JsonLines`] streams a file
+/// without indexing it.
+///
+/// ```no_run
+/// use ai_dataloader::{indexable::DataLoader, JsonLinesDataset};
+///
+/// # fn main() -> std::io::Result<()> {
+/// // Each line looks like `[0, "I'm happy"]`.
+/// let dataset = JsonLinesDataset::<(i32, String)>::open_cached("reviews.jsonl")?;
+/// let loader = DataLoader::builder(dataset).batch_size(2).shuffle().build();
+///
+/// for (label, text) in &loader {
+///     println!("Label {label:?}");
+///     println!("Text {text:?}");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct JsonLinesDataset<T = serde_json::Value> {
+    /// Path of the JSON Lines file.
+    path: PathBuf,
+    /// Handle used to read the lines.
+    file: File,
+    /// Byte offset and length of each non-blank line.
+    index: Vec<(u64, u64)>,
+    _sample: PhantomData<fn() -> T>,
+}
+
+impl<T> JsonLinesDataset<T> {
+    /// Open a JSON Lines file and index the offset of its lines.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the file can't be read.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::open(&path)?;
+        let index = build_index(&file)?;
+        Ok(Self {
+            path,
+            file,
+            index,
+            _sample: PhantomData,
+        })
+    }
+
+    /// Open a JSON Lines file, reusing the index cached next to it.
+    ///
+    /// The index is stored in a file with the same name and an additional `.idx` extension
+    /// (`data.jsonl.idx` for `data.jsonl`). It is rebuilt and written again if it is missing, or if the
+    /// size or the modification time of the JSON Lines file has changed.
+    ///
+    /// Writing the index is best-effort: if it can't be written (for instance because the dataset lives in a
+    /// read-only directory), the index built in memory is used and will be rebuilt on the next opening.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the JSON Lines file can't be read.
+    pub fn open_cached<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::open(&path)?;
+        let fingerprint = fingerprint(&file)?;
+        let index_path = Self::index_path(&path);
+
+        let index = if let Some(index) = read_index(&index_path, fingerprint) {
+            index
+        } else {
+            let index = build_index(&file)?;
+            // A missing or partially written cache is detected and rebuilt by `read_index`.
+            let _ = write_index(&index_path, fingerprint, &index);
+            index
+        };
+        Ok(Self {
+            path,
+            file,
+            index,
+            _sample: PhantomData,
+        })
+    }
+
+    /// Return the path of the index cache for a given JSON Lines file.
+    pub fn index_path<P: AsRef<Path>>(path: P) -> PathBuf {
+        let mut index_path = path.as_ref().as_os_str().to_owned();
+        index_path.push(".idx");
+        PathBuf::from(index_path)
+    }
+
+    /// Return the path of the underlying JSON Lines file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read the raw bytes of the line at the given index, without the line terminator.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the line can't be read.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn read_line(&self, index: usize) -> io::Result<Vec<u8>> {
+        let (offset, len) = self.index[index];
+        let mut buf = vec![0; usize::try_from(len).expect("line fits in memory")];
+        read_exact_at(&self.file, &mut buf, offset)?;
+        Ok(buf)
+    }
+}
+
+impl<T: DeserializeOwned> JsonLinesDataset<T> {
+    /// Read and deserialize the line at the given index.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the line can't be read or deserialized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn read_sample(&self, index: usize) -> io::Result<T> {
+        let line = self.read_line(index)?;
+        Ok(serde_json::from_slice(&line)?)
+    }
+
+    /// Stream the samples in order, reading the file sequentially.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the file can't be opened anymore.
+    #[must_use]
+    pub fn iter(&self) -> JsonLines<T> {
+        JsonLines::open(&self.path)
+            .unwrap_or_else(|err| panic!("could not open `{}`: {err}", self.path.display()))
+    }
+}
+
+impl<T> fmt::Debug for JsonLinesDataset<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonLinesDataset")
+            .field("path", &self.path)
+            .field("len", &self.index.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Len for JsonLinesDataset<T> {
+    fn len(&self) -> usize {
+        self.index.len()
+    }
+}
+
+impl<T: DeserializeOwned> GetSample for JsonLinesDataset<T> {
+    type Sample = T;
+    fn get_sample(&self, index: usize) -> Self::Sample {
+        self.read_sample(index).unwrap_or_else(|err| {
+            panic!(
+                "could not read sample {index} from `{}`: {err}",
+                self.path.display()
+            )
+        })
+    }
+}
+
+impl<T: DeserializeOwned> Dataset for JsonLinesDataset<T> {}
+
+impl<T: DeserializeOwned> IntoIterator for &JsonLinesDataset<T> {
+    type Item = T;
+    type IntoIter = JsonLines<T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: DeserializeOwned> IntoIterator for JsonLinesDataset<T> {
+    type Item = T;
+    type IntoIter = JsonLines<T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator that streams the samples of a JSON Lines file in order, without indexing it.
+///
+/// Blank lines are skipped. The iterator panics if a line can't be read or deserialized.
+pub struct JsonLines<T = serde_json::Value> {
+    /// Path of the JSON Lines file, used in error messages.
+    path: PathBuf,
+    /// The underlying reader.
+    reader: BufReader<File>,
+    /// Reused buffer for the current line.
+    line: Vec<u8>,
+    /// Number of line read so far.
+    line_number: usize,
+    _sample: PhantomData<fn() -> T>,
+}
+
+impl<T> JsonLines<T> {
+    /// Open a JSON Lines file for streaming.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the file can't be opened.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let reader = BufReader::new(File::open(&path)?);
+        Ok(Self {
+            path,
+            reader,
+            line: Vec::new(),
+            line_number: 0,
+            _sample: PhantomData,
+        })
+    }
+}
+
+impl<T> fmt::Debug for JsonLines<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonLines")
+            .field("path", &self.path)
+            .field("line_number", &self.line_number)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: DeserializeOwned> Iterator for JsonLines<T> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.line.clear();
+            let read = self
+                .reader
+                .read_until(b'\n', &mut self.line)
+                .unwrap_or_else(|err| panic!("could not read `{}`: {err}", self.path.display()));
+            if read == 0 {
+                return None;
+            }
+            self.line_number += 1;
+            if self.line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            let sample = serde_json::from_slice(&self.line).unwrap_or_else(|err| {
+                panic!(
+                    "could not deserialize line {} of `{}`: {err}",
+                    self.line_number,
+                    self.path.display()
+                )
+            });
+            return Some(sample);
+        }
+    }
+}
+
+/// Size and modification time of the file, used to detect a stale index.
+fn fingerprint(file: &File) -> io::Result<(u64, u128)> {
+    let metadata = file.metadata()?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |duration| duration.as_nanos());
+    Ok((metadata.len(), modified))
+}
+
+/// Scan the file and return the offset and the length, without the terminator, of each non-blank line.
+fn build_index(file: &File) -> io::Result<Vec<(u64, u64)>> {
+    let mut reader = BufReader::new(file);
+    let mut index = Vec::new();
+    let mut line = Vec::new();
+    let mut offset = 0;
+    loop {
+        line.clear();
+        let read = reader.read_until(b'\n', &mut line)? as u64;
+        if read == 0 {
+            return Ok(index);
+        }
+        if !line.iter().all(u8::is_ascii_whitespace) {
+            index.push((offset, line_len(&line)));
+        }
+        offset += read;
+    }
+}
+
+/// Length of a line without its `\n` or `\r\n` terminator.
+fn line_len(line: &[u8]) -> u64 {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    line.len() as u64
+}
+
+/// Read a cached index, returning `None` if it's missing, corrupted or stale.
+fn read_index(index_path: &Path, fingerprint: (u64, u128)) -> Option<Vec<(u64, u64)>> {
+    let mut reader = BufReader::new(File::open(index_path).ok()?);
+    let mut magic = [0; 8];
+    reader.read_exact(&mut magic).ok()?;
+    if &magic != INDEX_MAGIC {
+        return None;
+    }
+    let mut u64_buf = [0; 8];
+    let mut u128_buf = [0; 16];
+    reader.read_exact(&mut u64_buf).ok()?;
+    reader.read_exact(&mut u128_buf).ok()?;
+    if (u64::from_le_bytes(u64_buf), u128::from_le_bytes(u128_buf)) != fingerprint {
+        return None;
+    }
+    reader.read_exact(&mut u64_buf).ok()?;
+    let len = u64::from_le_bytes(u64_buf);
+    // The length isn't trusted for the allocation, a corrupted cache must not abort the process.
+    let mut index = Vec::new();
+    for _ in 0..len {
+        reader.read_exact(&mut u64_buf).ok()?;
+        let offset = u64::from_le_bytes(u64_buf);
+        reader.read_exact(&mut u64_buf).ok()?;
+        index.push((offset, u64::from_le_bytes(u64_buf)));
+    }
+    Some(index)
+}
+
+/// Write the index next to the JSON Lines file.
+fn write_index(
+    index_path: &Path,
+    fingerprint: (u64, u128),
+    index: &[(u64, u64)],
+) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(index_path)?);
+    writer.write_all(INDEX_MAGIC)?;
+    writer.write_all(&fingerprint.0.to_le_bytes())?;
+    writer.write_all(&fingerprint.1.to_le_bytes())?;
+    writer.write_all(&(index.len() as u64).to_le_bytes())?;
+    for (offset, len) in index {
+        writer.write_all(&offset.to_le_bytes())?;
+        writer.write_all(&len.to_le_bytes())?;
+    }
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{indexable, iterable};
+    use ndarray::array;
+    use serde::Deserialize;
+    use std::io::Write;
+
+    #[derive(Debug, Clone, PartialEq, Deserialize)]
+    struct Sample {
+        label: i32,
+        text: String,
+    }
+
+    fn jsonl_file() -> tempfile::NamedTempFile {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, r#"{{"label": 0, "text": "hola"}}"#).unwrap();
+        writeln!(file, r#"{{"label": 1, "text": "hello"}}"#).unwrap();
+        writeln!(file).unwrap();
+        write!(file, "{{\"label\": 2, \"text\": \"hallo\"}}\r\n").unwrap();
+        write!(file, r#"{{"label": 3, "text": "bonjour"}}"#).unwrap();
+        file
+    }
+
+    #[test]
+    fn random_access() {
+        let file = jsonl_file();
+        let dataset = JsonLinesDataset::<Sample>::open(file.path()).unwrap();
+        assert_eq!(dataset.len(), 4);
+        assert_eq!(
+            dataset.get_sample(3),
+            Sample {
+                label: 3,
+                text: String::from("bonjour")
+            }
+        );
+        assert_eq!(dataset.get_sample(2).label, 2);
+        assert_eq!(dataset.get_sample(0).text, "hola");
+    }
+
+    #[test]
+    fn line_terminators() {
+        let file = jsonl_file();
+        let dataset = JsonLinesDataset::<Sample>::open(file.path()).unwrap();
+        assert_eq!(
+            dataset.read_line(0).unwrap(),
+            br#"{"label": 0, "text": "hola"}"#
+        );
+        assert_eq!(
+            dataset.read_line(2).unwrap(),
+            br#"{"label": 2, "text": "hallo"}"#
+        );
+        assert_eq!(
+            dataset.read_line(3).unwrap(),
+            br#"{"label": 3, "text": "bonjour"}"#
+        );
+    }
+
+    #[test]
+    fn corrupted_index_cache() {
+        let file = jsonl_file();
+        let index_path = JsonLinesDataset::<Sample>::index_path(file.path());
+        let fingerprint = fingerprint(&File::open(file.path()).unwrap()).unwrap();
+        // A huge number of entries with a truncated body.
+        let mut cache = INDEX_MAGIC.to_vec();
+        cache.extend_from_slice(&fingerprint.0.to_le_bytes());
+        cache.extend_from_slice(&fingerprint.1.to_le_bytes());
+        cache.extend_from_slice(&u64::MAX.to_le_bytes());
+        cache.extend_from_slice(&[0; 8]);
+        std::fs::write(&index_path, cache).unwrap();
+        assert_eq!(read_index(&index_path, fingerprint), None);
+
+        let dataset = JsonLinesDataset::<Sample>::open_cached(file.path()).unwrap();
+        assert_eq!(dataset.len(), 4);
+        std::fs::remove_file(index_path).unwrap();
+    }
+
+    #[test]
+    fn dynamic_value() {
+        let file = jsonl_file();
+        let dataset = JsonLinesDataset::<serde_json::Value>::open(file.path()).unwrap();
+        assert_eq!(dataset.get_sample(1)["text"], "hello");
+    }
+
+    #[test]
+    fn index_cache() {
+        let file = jsonl_file();
+        let index_path = JsonLinesDataset::<Sample>::index_path(file.path());
+        let dataset = JsonLinesDataset::<Sample>::open_cached(file.path()).unwrap();
+        assert!(index_path.exists());
+        assert_eq!(dataset.len(), 4);
+
+        // Second opening reuse the cache.
+        let fingerprint = fingerprint(&File::open(file.path()).unwrap()).unwrap();
+        assert_eq!(read_index(&index_path, fingerprint), Some(dataset.index));
+
+        // A stale cache is ignored.
+        assert_eq!(read_index(&index_path, (0, 0)), None);
+
+        // Appending lines invalidate the cache.
+        writeln!(file.as_file(), "\n{{\"label\": 4, \"text\": \"ciao\"}}").unwrap();
+        let dataset = JsonLinesDataset::<Sample>::open_cached(file.path()).unwrap();
+        assert_eq!(dataset.len(), 5);
+        assert_eq!(dataset.get_sample(4).text, "ciao");
+        std::fs::remove_file(index_path).unwrap();
+    }
+
+    #[test]
+    fn unwritable_index_cache() {
+        let file = jsonl_file();
+        let index_path = JsonLinesDataset::<Sample>::index_path(file.path());
+        // A directory in place of the index makes writing it fail, even with elevated permissions.
+        std::fs::create_dir(&index_path).unwrap();
+        let dataset = JsonLinesDataset::<Sample>::open_cached(file.path()).unwrap();
+        assert_eq!(dataset.len(), 4);
+        assert_eq!(dataset.get_sample(3).label, 3);
+        std::fs::remove_dir(index_path).unwrap();
+    }
+
+    #[test]
+    fn indexable_loader() {
+        let file = jsonl_file();
+        let dataset = JsonLinesDataset::<(i32, f64)>::open(file.path());
+        // Tuples are deserialized from arrays, not objects.
+        assert!(dataset.unwrap().read_sample(0).is_err());
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "[0, 1.5]\n[1, 2.5]\n[0, 3.5]").unwrap();
+        let dataset = JsonLinesDataset::<(i32, f64)>::open(file.path()).unwrap();
+        let loader = indexable::DataLoader::builder(dataset)
+            .batch_size(2)
+            .build();
+        let mut iter = loader.iter();
+        assert_eq!(iter.next(), Some((array![0, 1], array![1.5, 2.5])));
+        assert_eq!(iter.next(), Some((array![0], array![3.5])));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn iterable_loader() {
+        let file = jsonl_file();
+        let dataset = JsonLinesDataset::<serde_json::Value>::open(file.path()).unwrap();
+        let loader = iterable::DataLoader::builder(dataset).batch_size(3).build();
+        for _ in 0..2 {
+            let mut iter = (&loader).into_iter();
+            let batch = iter.next().unwrap();
+            assert_eq!(batch.len(), 3);
+            assert_eq!(batch[2]["label"], 2);
+            assert_eq!(iter.next().unwrap()[0]["text"], "bonjour");
+            assert!(iter.next().is_none());
+        }
+
+        let loader = iterable::DataLoader::builder(
+            JsonLines::<serde_json::Value>::open(file.path()).unwrap(),
+        )
+        .batch_size(4)
+        .build();
+        assert_eq!(loader.into_iter().next().unwrap().len(), 4);
+    }
+}
@@ -0,0 +1,442 @@
+use super::{Dataset, GetSample};
+use crate::Len;
+use arrow::{
+    compute::concat_batches,
+    datatypes::SchemaRef,
+    record_batch::{RecordBatch, RecordBatchReader},
+};
+use parquet::{
+    arrow::{
+        arrow_reader::{
+            ArrowReaderMetadata, ArrowReaderOptions, ParquetRecordBatchReaderBuilder, RowSelection,
+            RowSelector,
+        },
+        ProjectionMask,
+    },
+    errors::{ParquetError, Result},
+};
+use std::{
+    collections::VecDeque,
+    fmt,
+    fs::File,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+    thread,
+};
+
+/// Columnar dataset backed by a [Parquet](https://parquet.apache.org/) file.
+///
+/// Only the file metadata is read when the dataset is opened. A global row index is mapped to a row group and an
+/// offset inside of it, and rows are decoded lazily, only for the selected columns.
+///
+/// By default, whole row groups are decoded and the least recently used ones are evicted from an in-memory cache
+/// holding one row group per available thread, so each row group is decoded once when the rows are read in order,
+/// or with a [`BlockShuffleSampler`](crate::sampler::BlockShuffleSampler) whose blocks match the row groups. For a
+/// fully random access, [`with_cached_row_groups(0)`](Self::with_cached_row_groups) reads each row on its own
+/// instead, paying the cost of opening the file and seeking to the row for every sample.
+///
+/// Each sample is an Arrow [`RecordBatch`] containing a single row: a zero-copy slice of its cached row group, or
+/// a row decoded on its own when row groups aren't cached.
+/// The [`DefaultCollate`](crate::collate::DefaultCollate) concatenates them into a [`RecordBatch`], and
+/// [`ColumnsCollate`](crate::collate::ColumnsCollate) turns them into one `ndarray` per column.
+///
+/// ```no_run
+/// use ai_dataloader::sampler::{BlockShuffleSampler, Sampler};
+/// use ai_dataloader::{indexable::DataLoader, Len, ParquetDataset};
+///
+/// # fn main() -> parquet::errors::Result<()> {
+/// // Row groups of 8192 rows, shuffled 4 at a time.
+/// let dataset = ParquetDataset::open_with_columns("features.parquet", &["age", "label"])?
+///     .with_cached_row_groups(4);
+/// let sampler = BlockShuffleSampler::new(dataset.len()).block_size(8192).window(4);
+/// let loader = DataLoader::builder(dataset).with_sampler(sampler).batch_size(256).build();
+///
+/// for batch in &loader {
+///     println!("{} rows", batch.num_rows());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct ParquetDataset {
+    /// Path of the Parquet file.
+    path: PathBuf,
+    /// Parquet and Arrow metadata of the file.
+    metadata: ArrowReaderMetadata,
+    /// Columns to read.
+    projection: ProjectionMask,
+    /// Schema of the yielded samples, after projection.
+    schema: SchemaRef,
+    /// Index of the first row of each row group, followed by the total number of rows.
+    row_group_offsets: Vec<usize>,
+    /// Maximum number of decoded row groups kept in memory, rows being read one by one if zero.
+    cached_row_groups: usize,
+    /// Recently used row groups, the most recently used being at the back.
+    cache: Mutex<VecDeque<(usize, RecordBatch)>>,
+}
+
+impl ParquetDataset {
+    /// Open a Parquet file, reading all of its columns.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the file can't be opened or if its metadata are invalid.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let metadata = ArrowReaderMetadata::load(&File::open(&path)?, ArrowReaderOptions::new())?;
+        Self::with_projection(path, metadata, ProjectionMask::all())
+    }
+
+    /// Open a Parquet file, reading only the given top-level columns.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the file can't be opened, if its metadata are invalid or if a column doesn't exist.
+    pub fn open_with_columns<P: AsRef<Path>>(path: P, columns: &[&str]) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let metadata = ArrowReaderMetadata::load(&File::open(&path)?, ArrowReaderOptions::new())?;
+        let indices = columns
+            .iter()
+            .map(|column| {
+                metadata.schema().index_of(column).map_err(|_| {
+                    ParquetError::General(format!(
+                        "column `{column}` not found in `{}`",
+                        path.display()
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let projection = ProjectionMask::roots(metadata.parquet_schema(), indices);
+        Self::with_projection(path, metadata, projection)
+    }
+
+    fn with_projection(
+        path: PathBuf,
+        metadata: ArrowReaderMetadata,
+        projection: ProjectionMask,
+    ) -> Result<Self> {
+        let mut row_group_offsets = vec![0];
+        for row_group in metadata.metadata().row_groups() {
+            let num_rows = usize::try_from(row_group.num_rows())
+                .map_err(|_| ParquetError::General(String::from("negative number of rows")))?;
+            row_group_offsets.push(row_group_offsets.last().unwrap() + num_rows);
+        }
+        // Decoding nothing gives the projected schema.
+        let schema = ParquetRecordBatchReaderBuilder::new_with_metadata(
+            File::open(&path)?,
+            metadata.clone(),
+        )
+        .with_projection(projection.clone())
+        .with_row_groups(Vec::new())
+        .build()?
+        .schema();
+
+        Ok(Self {
+            path,
+            metadata,
+            projection,
+            schema,
+            row_group_offsets,
+            cached_row_groups: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+            cache: Mutex::new(VecDeque::new()),
+        })
+    }
+
+    /// Keep up to `cached_row_groups` decoded row groups in memory, or read each row on its own if 0.
+    ///
+    /// The default is the number of available threads, enough for a sequential sampler whose batches are fetched
+    /// concurrently. With a [`BlockShuffleSampler`](crate::sampler::BlockShuffleSampler), it shouldn't be less than
+    /// its `window`. With a fully random access pattern, almost every read would decode a whole row group, and
+    /// reading single rows is cheaper.
+    #[must_use]
+    pub fn with_cached_row_groups(mut self, cached_row_groups: usize) -> Self {
+        self.cached_row_groups = cached_row_groups;
+        self
+    }
+
+    /// Return the schema of the samples, after the column selection.
+    #[must_use]
+    pub fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+
+    /// Return the number of row groups of the file.
+    #[must_use]
+    pub fn num_row_groups(&self) -> usize {
+        self.row_group_offsets.len() - 1
+    }
+
+    /// Map a global row index to a row group and the offset of the row inside of it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    #[must_use]
+    pub fn locate(&self, index: usize) -> (usize, usize) {
+        assert!(
+            index < self.len(),
+            "index {index} is out of bounds for a dataset of length {}",
+            self.len()
+        );
+        let row_group = self
+            .row_group_offsets
+            .partition_point(|&offset| offset <= index)
+            - 1;
+        (row_group, index - self.row_group_offsets[row_group])
+    }
+
+    /// Read the row at the given index.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the row, or the row group containing it when row groups are cached, can't be decoded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn read_row(&self, index: usize) -> Result<RecordBatch> {
+        let (row_group, offset) = self.locate(index);
+        if self.cached_row_groups == 0 {
+            let selection =
+                RowSelection::from(vec![RowSelector::skip(offset), RowSelector::select(1)]);
+            return self.decode(row_group, Some(selection), 1);
+        }
+        Ok(self.row_group(row_group)?.slice(offset, 1))
+    }
+
+    /// Return a decoded row group, from the cache if possible.
+    fn row_group(&self, row_group: usize) -> Result<RecordBatch> {
+        {
+            let mut cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+            if let Some(position) = cache.iter().position(|(cached, _)| *cached == row_group) {
+                let entry = cache.remove(position).expect("position is in bounds");
+                let batch = entry.1.clone();
+                cache.push_back(entry);
+                return Ok(batch);
+            }
+        }
+
+        let num_rows = self.row_group_offsets[row_group + 1] - self.row_group_offsets[row_group];
+        let batch = self.decode(row_group, None, num_rows)?;
+
+        let mut cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+        if !cache.iter().any(|(cached, _)| *cached == row_group) {
+            if cache.len() == self.cached_row_groups {
+                cache.pop_front();
+            }
+            cache.push_back((row_group, batch.clone()));
+        }
+        Ok(batch)
+    }
+
+    /// Decode the selected rows of a row group into a single [`RecordBatch`].
+    fn decode(
+        &self,
+        row_group: usize,
+        selection: Option<RowSelection>,
+        num_rows: usize,
+    ) -> Result<RecordBatch> {
+        // We open the file again, because readers cloned from the same `File` would share their cursor.
+        let mut builder = ParquetRecordBatchReaderBuilder::new_with_metadata(
+            File::open(&self.path)?,
+            self.metadata.clone(),
+        )
+        .with_projection(self.projection.clone())
+        .with_row_groups(vec![row_group])
+        .with_batch_size(num_rows.max(1));
+        if let Some(selection) = selection {
+            builder = builder.with_row_selection(selection);
+        }
+        let batches = builder
+            .build()?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(concat_batches(&self.schema, &batches)?)
+    }
+}
+
+impl fmt::Debug for ParquetDataset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParquetDataset")
+            .field("path", &self.path)
+            .field("schema", &self.schema)
+            .field("row_group_offsets", &self.row_group_offsets)
+            .field("cached_row_groups", &self.cached_row_groups)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Len for ParquetDataset {
+    fn len(&self) -> usize {
+        *self
+            .row_group_offsets
+            .last()
+            .expect("offsets contain at least one element")
+    }
+}
+
+impl GetSample for ParquetDataset {
+    type Sample = RecordBatch;
+    fn get_sample(&self, index: usize) -> Self::Sample {
+        self.read_row(index).unwrap_or_else(|err| {
+            panic!(
+                "could not read row {index} from `{}`: {err}",
+                self.path.display()
+            )
+        })
+    }
+}
+
+impl Dataset for ParquetDataset {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{collate::ColumnsCollate, indexable::DataLoader};
+    use arrow::{
+        array::{AsArray, Float32Array, Int64Array, StringArray},
+        datatypes::{Float32Type, Int64Type},
+    };
+    use parquet::{arrow::ArrowWriter, file::properties::WriterProperties};
+    use std::sync::Arc;
+
+    /// Write a Parquet file with 10 rows, split in row groups of 3 rows.
+    fn parquet_file() -> tempfile::NamedTempFile {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let batch = RecordBatch::try_from_iter([
+            ("id", Arc::new(Int64Array::from_iter_values(0..10)) as _),
+            (
+                "score",
+                Arc::new(Float32Array::from_iter_values(
+                    (0..10_i16).map(|i| f32::from(i) / 2.),
+                )) as _,
+            ),
+            (
+                "name",
+                Arc::new(StringArray::from_iter_values(
+                    (0..10).map(|i| format!("row {i}")),
+                )) as _,
+            ),
+        ])
+        .unwrap();
+        let properties = WriterProperties::builder()
+            .set_max_row_group_size(3)
+            .build();
+        let mut writer =
+            ArrowWriter::try_new(file.reopen().unwrap(), batch.schema(), Some(properties)).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+        file
+    }
+
+    #[test]
+    fn row_groups() {
+        let file = parquet_file();
+        let dataset = ParquetDataset::open(file.path())
+            .unwrap()
+            .with_cached_row_groups(2);
+        assert_eq!(dataset.len(), 10);
+        assert_eq!(dataset.num_row_groups(), 4);
+        assert_eq!(dataset.locate(0), (0, 0));
+        assert_eq!(dataset.locate(2), (0, 2));
+        assert_eq!(dataset.locate(3), (1, 0));
+        assert_eq!(dataset.locate(9), (3, 0));
+
+        for index in [9, 0, 4, 5, 3] {
+            let row = dataset.get_sample(index);
+            assert_eq!(row.num_rows(), 1);
+            assert_eq!(
+                row.column(0).as_primitive::<Int64Type>().value(0),
+                i64::try_from(index).unwrap()
+            );
+        }
+        assert_eq!(dataset.cache.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn least_recently_used() {
+        let file = parquet_file();
+        let dataset = ParquetDataset::open(file.path())
+            .unwrap()
+            .with_cached_row_groups(2);
+        for index in [0, 3, 1, 6] {
+            dataset.get_sample(index);
+        }
+        // Row group 0 was used after row group 1, so row group 1 is evicted.
+        let cached: Vec<_> = dataset
+            .cache
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(row_group, _)| *row_group)
+            .collect();
+        assert_eq!(cached, [0, 2]);
+    }
+
+    #[test]
+    fn single_rows() {
+        let file = parquet_file();
+        let dataset = ParquetDataset::open(file.path())
+            .unwrap()
+            .with_cached_row_groups(0);
+        for index in [9, 0, 4, 5, 3, 8] {
+            let row = dataset.get_sample(index);
+            assert_eq!(row.num_rows(), 1);
+            assert_eq!(
+                row.column(0).as_primitive::<Int64Type>().value(0),
+                i64::try_from(index).unwrap()
+            );
+            assert_eq!(
+                row.column(2).as_string::<i32>().value(0),
+                format!("row {index}")
+            );
+        }
+        assert!(dataset.cache.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn out_of_bounds() {
+        let file = parquet_file();
+        let _ = ParquetDataset::open(file.path()).unwrap().get_sample(10);
+    }
+
+    #[test]
+    fn projection() {
+        let file = parquet_file();
+        let dataset = ParquetDataset::open_with_columns(file.path(), &["name", "id"]).unwrap();
+        // Columns keep the order of the file.
+        let names: Vec<_> = dataset.schema().fields().iter().map(|f| f.name()).collect();
+        assert_eq!(names, ["id", "name"]);
+        assert_eq!(dataset.get_sample(4).num_columns(), 2);
+
+        assert!(ParquetDataset::open_with_columns(file.path(), &["missing"]).is_err());
+    }
+
+    #[test]
+    fn loader() {
+        let file = parquet_file();
+        let dataset = ParquetDataset::open_with_columns(file.path(), &["id"]).unwrap();
+        let loader = DataLoader::builder(dataset).batch_size(4).build();
+        let batches: Vec<_> = loader.iter().collect();
+        assert_eq!(batches.len(), 3);
+        assert_eq!(batches[1].num_rows(), 4);
+        assert_eq!(
+            batches[1].column(0).as_primitive::<Int64Type>().values(),
+            &[4, 5, 6, 7]
+        );
+        assert_eq!(batches[2].num_rows(), 2);
+    }
+
+    #[test]
+    fn columns_collate() {
+        let file = parquet_file();
+        let dataset = ParquetDataset::open_with_columns(file.path(), &["id", "score"]).unwrap();
+        let loader = DataLoader::builder(dataset)
+            .batch_size(4)
+            .collate_fn(ColumnsCollate::<Float32Type>::new())
+            .build();
+        let last = loader.iter().last().unwrap();
+        assert_eq!(last["id"], ndarray::array![8., 9.].into_dyn());
+        assert_eq!(last["score"], ndarray::array![4., 4.5].into_dyn());
+    }
+}
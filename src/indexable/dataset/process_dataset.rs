@@ -0,0 +1,470 @@
+use super::{Dataset, GetSample};
+use crate::{validation::panic_message, Len};
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    env,
+    ffi::OsString,
+    fmt,
+    io::{self, BufReader, BufWriter, Read, Write},
+    marker::PhantomData,
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    process::{self, Child, ChildStdin, ChildStdout, Command, Stdio},
+    sync::{Condvar, Mutex, MutexGuard, PoisonError},
+};
+
+/// Environment variable holding the name of the dataset served by a worker process.
+const WORKER_ENV: &str = "AI_DATALOADER_WORKER";
+/// Written by a worker before its first message, the bytes written before it on the standard output are ignored.
+const HANDSHAKE_MAGIC: &[u8; 8] = b"AIDLWRK1";
+
+/// Dataset fetching its samples from worker processes, for datasets that can't be shared between threads.
+///
+/// Each worker is a process launched from the current executable, which instantiates its own copy of the dataset
+/// with the factory given to [`run_worker`]. The indices are sent to the workers through their standard input and
+/// the samples come back serialized with [`bincode`] through their standard output, so the dataset doesn't need to
+/// be `Send` nor `Sync`: only its samples need to be serializable. As a `ProcessDataset` is `Sync`, the indexable
+/// `DataLoader` fetches the samples of a batch in parallel, one per worker.
+///
+/// The generator given to [`GetSample::get_sample_with_rng`] seeds the generator of the worker, so the random
+/// transforms stay reproducible. A panic of `get_sample` in a worker is propagated with its message, and a worker
+/// that crashes is reported by a panic with its exit status, then replaced by a new one.
+///
+/// The executable must call [`run_worker`] at the start of `main`, before anything is written to its standard
+/// output: in a worker process it serves the samples then exits, otherwise it returns immediately. The dataset
+/// shouldn't write to the standard output either.
+///
+/// ```no_run
+/// use ai_dataloader::indexable::{run_worker, DataLoader, ProcessDataset};
+/// use ai_dataloader::{Dataset, GetSample, Len};
+/// use std::rc::Rc;
+///
+/// /// A dataset that can't be shared between threads.
+/// struct Corpus(Rc<Vec<String>>);
+///
+/// impl Len for Corpus {
+///     fn len(&self) -> usize {
+///         self.0.len()
+///     }
+/// }
+/// impl GetSample for Corpus {
+///     type Sample = String;
+///     fn get_sample(&self, index: usize) -> String {
+///         self.0[index].clone()
+///     }
+/// }
+/// impl Dataset for Corpus {}
+///
+/// fn main() -> std::io::Result<()> {
+///     run_worker("corpus", || Corpus(Rc::new(vec!["a".to_owned(), "b".to_owned()])));
+///
+///     let dataset = ProcessDataset::<String>::spawn("corpus", 4)?;
+///     let loader = DataLoader::builder(dataset).batch_size(2).build();
+///     for batch in &loader {
+///         println!("{batch:?}");
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct ProcessDataset<T> {
+    /// How the workers are launched.
+    command: WorkerCommand,
+    /// Length of the dataset, reported by the workers.
+    len: usize,
+    /// Idle workers.
+    idle: Mutex<Workers>,
+    /// Notified when a worker becomes idle or is lost.
+    available: Condvar,
+    _sample: PhantomData<fn() -> T>,
+}
+
+/// Program and arguments of the worker processes.
+#[derive(Debug)]
+struct WorkerCommand {
+    name: String,
+    program: PathBuf,
+    args: Vec<OsString>,
+}
+
+struct Workers {
+    idle: Vec<Worker>,
+    /// Number of workers, idle or busy.
+    alive: usize,
+}
+
+impl<T: DeserializeOwned> ProcessDataset<T> {
+    /// Launch `num_workers` worker processes serving the dataset registered under `name` with [`run_worker`].
+    ///
+    /// The workers are launched from the current executable with the same arguments.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if a worker can't be launched, or if it exits before serving the dataset.
+    pub fn spawn(name: &str, num_workers: usize) -> io::Result<Self> {
+        Self::spawn_with_args(name, num_workers, env::args_os().skip(1))
+    }
+
+    /// Launch `num_workers` worker processes serving the dataset registered under `name` with [`run_worker`],
+    /// passing them the given arguments.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if a worker can't be launched, or if it exits before serving the dataset.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_workers` is zero.
+    pub fn spawn_with_args<I, A>(name: &str, num_workers: usize, args: I) -> io::Result<Self>
+    where
+        I: IntoIterator<Item = A>,
+        A: Into<OsString>,
+    {
+        assert!(num_workers > 0, "the number of workers should be positive");
+        if env::var_os(WORKER_ENV).is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "can't launch workers from a worker process, `run_worker` should be called at the start of `main`",
+            ));
+        }
+        let command = WorkerCommand {
+            name: name.to_owned(),
+            program: env::current_exe()?,
+            args: args.into_iter().map(Into::into).collect(),
+        };
+        let mut idle = Vec::with_capacity(num_workers);
+        let mut len = None;
+        for _ in 0..num_workers {
+            let (worker, worker_len) = command.spawn()?;
+            if *len.get_or_insert(worker_len) != worker_len {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "the workers of `{name}` disagree on its length: {} and {worker_len}",
+                        len.unwrap_or_default()
+                    ),
+                ));
+            }
+            idle.push(worker);
+        }
+        Ok(Self {
+            command,
+            len: len.unwrap_or_default(),
+            idle: Mutex::new(Workers {
+                idle,
+                alive: num_workers,
+            }),
+            available: Condvar::new(),
+            _sample: PhantomData,
+        })
+    }
+
+    /// Return the number of live workers.
+    pub fn num_workers(&self) -> usize {
+        self.lock().alive
+    }
+
+    /// Fetch a sample from an idle worker, waiting for one if they are all busy.
+    fn fetch(&self, index: usize, seed: Option<u64>) -> T {
+        let mut worker = {
+            let mut workers = self.lock();
+            loop {
+                if let Some(worker) = workers.idle.pop() {
+                    break worker;
+                }
+                assert!(
+                    workers.alive > 0,
+                    "all the workers of `{}` are lost",
+                    self.command.name
+                );
+                workers = self
+                    .available
+                    .wait(workers)
+                    .unwrap_or_else(PoisonError::into_inner);
+            }
+        };
+        match worker.fetch(index, seed) {
+            Ok(Ok(sample)) => {
+                self.release(worker);
+                sample
+            }
+            Ok(Err(message)) => {
+                self.release(worker);
+                panic!("{message}");
+            }
+            Err(err) => {
+                let status = worker.kill();
+                // Replace the crashed worker, so the next samples can still be fetched.
+                let replacement = self.command.spawn();
+                if let Ok((replacement, _)) = replacement {
+                    self.release(replacement);
+                } else {
+                    self.lock().alive -= 1;
+                    self.available.notify_all();
+                }
+                panic!(
+                    "worker of `{}` crashed while fetching sample {index} ({err}), {status}",
+                    self.command.name
+                );
+            }
+        }
+    }
+
+    fn release(&self, worker: Worker) {
+        self.lock().idle.push(worker);
+        self.available.notify_one();
+    }
+}
+
+impl<T> ProcessDataset<T> {
+    fn lock(&self) -> MutexGuard<'_, Workers> {
+        // A panic while holding the lock can't leave the workers in an inconsistent state.
+        self.idle.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T> fmt::Debug for ProcessDataset<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProcessDataset")
+            .field("command", &self.command)
+            .field("len", &self.len)
+            .field("num_workers", &self.lock().alive)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Len for ProcessDataset<T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<T: DeserializeOwned> GetSample for ProcessDataset<T> {
+    type Sample = T;
+    fn get_sample(&self, index: usize) -> Self::Sample {
+        self.fetch(index, None)
+    }
+    fn get_sample_with_rng(&self, index: usize, rng: &mut StdRng) -> Self::Sample {
+        self.fetch(index, Some(rng.next_u64()))
+    }
+}
+
+impl<T: DeserializeOwned> Dataset for ProcessDataset<T> {}
+
+impl WorkerCommand {
+    /// Launch a worker and wait for it to report the length of the dataset.
+    fn spawn(&self) -> io::Result<(Worker, usize)> {
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .env(WORKER_ENV, &self.name)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let mut worker = Worker {
+            child,
+            stdin: BufWriter::new(stdin),
+            stdout: BufReader::new(stdout),
+        };
+        let len = worker.handshake().map_err(|err| {
+            let status = worker.kill();
+            io::Error::new(
+                err.kind(),
+                format!(
+                    "worker of `{}` exited before serving the dataset ({err}), {status}, `run_worker` should be called \
+                    at the start of `main`",
+                    self.name
+                ),
+            )
+        })?;
+        Ok((worker, len))
+    }
+}
+
+/// A worker process, with the pipes to its standard input and output.
+struct Worker {
+    child: Child,
+    stdin: BufWriter<ChildStdin>,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl Worker {
+    /// Skip the output of the worker until the handshake, and return the length of the dataset.
+    fn handshake(&mut self) -> io::Result<usize> {
+        let mut window = [0; HANDSHAKE_MAGIC.len()];
+        let mut byte = [0];
+        while &window != HANDSHAKE_MAGIC {
+            self.stdout.read_exact(&mut byte)?;
+            window.rotate_left(1);
+            window[window.len() - 1] = byte[0];
+        }
+        let len: u64 =
+            bincode::deserialize_from(&mut self.stdout).map_err(|err| into_io_error(*err))?;
+        usize::try_from(len).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Send an index to the worker and wait for the sample, or the message of the panic of `get_sample`.
+    fn fetch<T: DeserializeOwned>(
+        &mut self,
+        index: usize,
+        seed: Option<u64>,
+    ) -> io::Result<Result<T, String>> {
+        bincode::serialize_into(&mut self.stdin, &(index as u64, seed))
+            .map_err(|err| into_io_error(*err))?;
+        self.stdin.flush()?;
+        bincode::deserialize_from(&mut self.stdout).map_err(|err| into_io_error(*err))
+    }
+
+    /// Kill the worker if it's still running, and describe its exit status.
+    fn kill(&mut self) -> String {
+        let _ = self.child.kill();
+        match self.child.wait() {
+            Ok(status) => format!("worker {status}"),
+            Err(err) => format!("could not wait for the worker: {err}"),
+        }
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        self.kill();
+    }
+}
+
+fn into_io_error(err: bincode::ErrorKind) -> io::Error {
+    match err {
+        bincode::ErrorKind::Io(err) => err,
+        err => io::Error::new(io::ErrorKind::InvalidData, err),
+    }
+}
+
+/// Serve the dataset registered under `name` if the current process is a worker of a [`ProcessDataset`], then exit.
+///
+/// Must be called at the start of `main`. In a worker process launched for `name`, the dataset is created with
+/// `factory` and its samples are served until the [`ProcessDataset`] is dropped, then the process exits without
+/// returning. Otherwise the function returns immediately, without calling `factory`.
+pub fn run_worker<D, F>(name: &str, factory: F)
+where
+    D: Dataset,
+    D::Sample: Serialize,
+    F: FnOnce() -> D,
+{
+    if env::var_os(WORKER_ENV).map_or(true, |worker| worker != name) {
+        return;
+    }
+    let dataset = factory();
+    let code = match serve(&dataset) {
+        Ok(()) => 0,
+        Err(err) => {
+            eprintln!("worker of `{name}` failed: {err}");
+            1
+        }
+    };
+    process::exit(code);
+}
+
+/// Answer the requests of the parent process until its end of the pipe is closed.
+fn serve<D>(dataset: &D) -> io::Result<()>
+where
+    D: Dataset,
+    D::Sample: Serialize,
+{
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let mut output = BufWriter::new(io::stdout().lock());
+    output.write_all(HANDSHAKE_MAGIC)?;
+    bincode::serialize_into(&mut output, &(dataset.len() as u64))
+        .map_err(|err| into_io_error(*err))?;
+    output.flush()?;
+    loop {
+        let (index, seed): (u64, Option<u64>) = match bincode::deserialize_from(&mut input) {
+            Ok(request) => request,
+            Err(err) => match *err {
+                bincode::ErrorKind::Io(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                    return Ok(())
+                }
+                err => return Err(into_io_error(err)),
+            },
+        };
+        let index = usize::try_from(index)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        let sample = panic::catch_unwind(AssertUnwindSafe(|| match seed {
+            Some(seed) => dataset.get_sample_with_rng(index, &mut StdRng::seed_from_u64(seed)),
+            None => dataset.get_sample(index),
+        }))
+        .map_err(panic_message);
+        bincode::serialize_into(&mut output, &sample).map_err(|err| into_io_error(*err))?;
+        output.flush()?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexable::{DataLoader, FetchError};
+    use rand::Rng;
+    use std::{cell::RefCell, rc::Rc};
+
+    /// Path of the test in the test executable, to run only this test in the workers.
+    const TEST: &str = "indexable::dataset::process_dataset::tests::workers";
+
+    /// A dataset that isn't `Send` nor `Sync`, which panics on the sample 3 and crashes on the sample 5.
+    struct Local(Rc<RefCell<Vec<u32>>>);
+
+    impl Len for Local {
+        fn len(&self) -> usize {
+            self.0.borrow().len()
+        }
+    }
+    impl GetSample for Local {
+        type Sample = (u32, u32);
+        fn get_sample(&self, index: usize) -> (u32, u32) {
+            self.get_sample_with_rng(index, &mut StdRng::seed_from_u64(0))
+        }
+        fn get_sample_with_rng(&self, index: usize, rng: &mut StdRng) -> (u32, u32) {
+            match index {
+                3 => panic!("corrupted sample"),
+                5 => process::exit(3),
+                _ => (self.0.borrow()[index], rng.gen_range(0..1000)),
+            }
+        }
+    }
+    impl Dataset for Local {}
+
+    #[test]
+    fn workers() {
+        run_worker("local", || Local(Rc::new(RefCell::new((0..8).collect()))));
+
+        let dataset =
+            ProcessDataset::<(u32, u32)>::spawn_with_args("local", 2, [TEST, "--exact"]).unwrap();
+        assert_eq!(dataset.len(), 8);
+        assert_eq!(dataset.get_sample(2).0, 2);
+
+        let mut loader = DataLoader::builder(dataset).batch_size(2).seed(0).build();
+        let batches: Vec<_> = loader.try_iter().collect();
+        assert_eq!(batches.len(), 4);
+        assert!(batches[0].is_ok());
+        match &batches[1] {
+            Err(FetchError::Panicked(error)) => {
+                assert_eq!(error.index, 3);
+                assert_eq!(error.message, "corrupted sample");
+            }
+            other => panic!("expected a panic, got {other:?}"),
+        }
+        match &batches[2] {
+            Err(FetchError::Panicked(error)) => {
+                assert_eq!(error.index, 5);
+                assert!(error.message.contains("crashed"), "{}", error.message);
+            }
+            other => panic!("expected a crash, got {other:?}"),
+        }
+        assert!(batches[3].is_ok());
+
+        // The crashed worker is replaced, and the random transforms of the epoch are reproducible.
+        loader.set_epoch(0);
+        let again: Vec<_> = loader.try_iter().collect();
+        assert_eq!(again[0].as_ref().ok(), batches[0].as_ref().ok());
+        assert_eq!(again[3].as_ref().ok(), batches[3].as_ref().ok());
+    }
+}
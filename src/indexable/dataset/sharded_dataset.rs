@@ -0,0 +1,304 @@
+use super::{Dataset, GetSample};
+use crate::{sampler::ShardLayout, Len};
+use rand::rngs::StdRng;
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+/// Default maximum number of shards opened at the same time.
+const DEFAULT_MAX_OPEN_SHARDS: usize = 16;
+
+/// Function opening a shard from its path.
+type OpenShard<D> = Box<dyn Fn(&Path) -> io::Result<D> + Send + Sync>;
+
+/// Dataset made of several shards, each one being a dataset, indexed as a whole.
+///
+/// The global indices follow the order of the shards: the samples of the first shard come first, then the samples of
+/// the second one and so on. A global index is mapped to its shard and its index within the shard with the prefix
+/// sums of the shard lengths, exposed by [`ShardedDataset::layout`] to the shard-aware samplers such as the
+/// [`ShardShuffleSampler`](crate::sampler::ShardShuffleSampler).
+///
+/// The shards are either given already opened to [`ShardedDataset::new`], or opened lazily from their path with
+/// [`ShardedDataset::open`]. In the latter case, at most [`max_open_shards`](ShardedDataset::max_open_shards) shards
+/// are kept open, the least recently used one being closed first.
+///
+/// ```no_run
+/// use ai_dataloader::{indexable::{DataLoader, ShardedDataset}, sampler::ShardShuffleSampler};
+/// use std::{fs, io, path::Path};
+///
+/// // One sentence per line.
+/// fn read_lines(path: &Path) -> io::Result<Vec<String>> {
+///     Ok(fs::read_to_string(path)?.lines().map(String::from).collect())
+/// }
+///
+/// # fn main() -> io::Result<()> {
+/// let paths = (0..100).map(|shard| format!("corpus/shard-{shard:03}.txt"));
+/// let dataset = ShardedDataset::open(paths, read_lines)?.max_open_shards(8);
+/// let sampler = ShardShuffleSampler::from_layout(dataset.layout().clone()).window(4);
+/// let loader = DataLoader::builder(dataset).with_sampler(sampler).batch_size(32).build();
+/// # Ok(())
+/// # }
+/// ```
+pub struct ShardedDataset<D> {
+    /// Prefix sums of the shard lengths.
+    layout: ShardLayout,
+    shards: Shards<D>,
+}
+
+enum Shards<D> {
+    /// Shards given already opened.
+    Opened(Vec<D>),
+    /// Shards opened on demand from their path.
+    Lazy {
+        paths: Vec<PathBuf>,
+        open: OpenShard<D>,
+        max_open: usize,
+        /// Open shards, the most recently used last.
+        opened: Mutex<Vec<(usize, Arc<D>)>>,
+    },
+}
+
+impl<D: Len> ShardedDataset<D> {
+    /// Create a dataset from already opened shards.
+    pub fn new(shards: Vec<D>) -> Self {
+        Self {
+            layout: ShardLayout::from_lens(shards.iter().map(Len::len)),
+            shards: Shards::Opened(shards),
+        }
+    }
+
+    /// Create a dataset from the paths of its shards and a function opening a shard.
+    ///
+    /// Each shard is opened once to read its length, then closed. Use [`ShardedDataset::open_with_lens`] if the
+    /// lengths are already known.
+    ///
+    /// # Errors
+    ///
+    /// Return the first error of `open`.
+    pub fn open<I, P, F>(paths: I, open: F) -> io::Result<Self>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+        F: Fn(&Path) -> io::Result<D> + Send + Sync + 'static,
+    {
+        let paths: Vec<PathBuf> = paths
+            .into_iter()
+            .map(|path| path.as_ref().to_path_buf())
+            .collect();
+        let lens = paths
+            .iter()
+            .map(|path| open(path).map(|shard| shard.len()))
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(Self::open_with_lens(paths.into_iter().zip(lens), open))
+    }
+}
+
+impl<D> ShardedDataset<D> {
+    /// Create a dataset from the paths of its shards with their length, and a function opening a shard.
+    ///
+    /// No shard is opened until a sample is read from it.
+    pub fn open_with_lens<I, P, F>(shards: I, open: F) -> Self
+    where
+        I: IntoIterator<Item = (P, usize)>,
+        P: AsRef<Path>,
+        F: Fn(&Path) -> io::Result<D> + Send + Sync + 'static,
+    {
+        let (paths, lens): (Vec<PathBuf>, Vec<usize>) = shards
+            .into_iter()
+            .map(|(path, len)| (path.as_ref().to_path_buf(), len))
+            .unzip();
+        Self {
+            layout: ShardLayout::from_lens(lens),
+            shards: Shards::Lazy {
+                paths,
+                open: Box::new(open),
+                max_open: DEFAULT_MAX_OPEN_SHARDS,
+                opened: Mutex::new(Vec::new()),
+            },
+        }
+    }
+
+    /// Set the maximum number of shards opened at the same time, 16 by default. Only used by the shards opened
+    /// from their path.
+    ///
+    /// A shard still read by a thread isn't closed before the end of the read, so this limit can be exceeded by the
+    /// number of threads of the loader.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_open_shards` is zero.
+    #[must_use]
+    pub fn max_open_shards(mut self, max_open_shards: usize) -> Self {
+        assert!(
+            max_open_shards > 0,
+            "the maximum number of open shards should be positive"
+        );
+        if let Shards::Lazy { max_open, .. } = &mut self.shards {
+            *max_open = max_open_shards;
+        }
+        self
+    }
+
+    /// Return the layout of the shards, to create a shard-aware sampler.
+    pub fn layout(&self) -> &ShardLayout {
+        &self.layout
+    }
+
+    /// Return the number of shards.
+    pub fn num_shards(&self) -> usize {
+        self.layout.num_shards()
+    }
+
+    /// Return the number of shards currently open.
+    pub fn open_shards(&self) -> usize {
+        match &self.shards {
+            Shards::Opened(shards) => shards.len(),
+            Shards::Lazy { opened, .. } => lock(opened).len(),
+        }
+    }
+
+    /// Call `f` on the shard of a global index with the index within the shard, opening the shard if needed.
+    fn with_shard<T>(&self, index: usize, f: impl FnOnce(&D, usize) -> T) -> T {
+        let (shard, local) = self.layout.locate(index);
+        match &self.shards {
+            Shards::Opened(shards) => f(&shards[shard], local),
+            Shards::Lazy {
+                paths,
+                open,
+                max_open,
+                opened,
+            } => {
+                let cached = {
+                    let mut opened = lock(opened);
+                    opened
+                        .iter()
+                        .position(|&(open, _)| open == shard)
+                        .map(|position| {
+                            let entry = opened.remove(position);
+                            let dataset = Arc::clone(&entry.1);
+                            opened.push(entry);
+                            dataset
+                        })
+                };
+                let dataset = cached.unwrap_or_else(|| {
+                    // The shard is opened without holding the lock, so the other shards can still be read.
+                    let path = &paths[shard];
+                    let dataset = Arc::new(open(path).unwrap_or_else(|err| {
+                        panic!("could not open shard `{}`: {err}", path.display())
+                    }));
+                    let mut opened = lock(opened);
+                    // Another thread may have opened the shard meanwhile.
+                    if let Some((_, dataset)) = opened.iter().find(|&&(open, _)| open == shard) {
+                        return Arc::clone(dataset);
+                    }
+                    opened.push((shard, Arc::clone(&dataset)));
+                    if opened.len() > *max_open {
+                        opened.remove(0);
+                    }
+                    dataset
+                });
+                f(&dataset, local)
+            }
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // A panic while holding the lock can't leave the open shards in an inconsistent state.
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl<D: fmt::Debug> fmt::Debug for ShardedDataset<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("ShardedDataset");
+        debug.field("layout", &self.layout);
+        match &self.shards {
+            Shards::Opened(shards) => debug.field("shards", shards),
+            Shards::Lazy {
+                paths, max_open, ..
+            } => debug
+                .field("paths", paths)
+                .field("max_open_shards", max_open)
+                .field("open_shards", &self.open_shards()),
+        };
+        debug.finish_non_exhaustive()
+    }
+}
+
+impl<D> Len for ShardedDataset<D> {
+    fn len(&self) -> usize {
+        self.layout.len()
+    }
+}
+
+impl<D: GetSample> GetSample for ShardedDataset<D> {
+    type Sample = D::Sample;
+    fn get_sample(&self, index: usize) -> Self::Sample {
+        self.with_shard(index, GetSample::get_sample)
+    }
+    fn get_sample_with_rng(&self, index: usize, rng: &mut StdRng) -> Self::Sample {
+        self.with_shard(index, |shard, local| shard.get_sample_with_rng(local, rng))
+    }
+}
+
+impl<D: Dataset> Dataset for ShardedDataset<D> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexable::DataLoader;
+    use crate::sampler::ShardShuffleSampler;
+    use std::fs;
+
+    #[test]
+    fn global_index() {
+        let dataset = ShardedDataset::new(vec![vec![0, 1], vec![], vec![2, 3, 4]]);
+        assert_eq!(dataset.len(), 5);
+        assert_eq!(dataset.num_shards(), 3);
+        let samples: Vec<_> = (0..5).map(|index| dataset.get_sample(index)).collect();
+        assert_eq!(samples, [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn lazy_shards() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut paths = Vec::new();
+        for (shard, len) in [3, 5, 2, 4].into_iter().enumerate() {
+            let path = dir.path().join(format!("shard-{shard}.txt"));
+            let lines: Vec<_> = (0..len).map(|line| format!("{shard}-{line}")).collect();
+            fs::write(&path, lines.join("\n")).unwrap();
+            paths.push(path);
+        }
+        let read = |path: &Path| -> io::Result<Vec<String>> {
+            Ok(fs::read_to_string(path)?
+                .lines()
+                .map(String::from)
+                .collect())
+        };
+
+        let dataset = ShardedDataset::open(&paths, read)
+            .unwrap()
+            .max_open_shards(2);
+        assert_eq!(dataset.len(), 14);
+        assert_eq!(dataset.open_shards(), 0);
+        assert_eq!(dataset.get_sample(4), "1-1");
+        assert_eq!(dataset.get_sample(13), "3-3");
+        assert_eq!(dataset.get_sample(0), "0-0");
+        assert_eq!(dataset.open_shards(), 2);
+
+        let sampler = ShardShuffleSampler::from_layout(dataset.layout().clone()).window(2);
+        let shuffled = DataLoader::builder(dataset)
+            .with_sampler(sampler)
+            .batch_size(3)
+            .collate_fn(|batch| batch)
+            .seed(0)
+            .build();
+        let mut lines: Vec<String> = shuffled.iter().flatten().collect();
+        lines.sort_unstable();
+        let mut expected: Vec<String> = paths.iter().flat_map(|path| read(path).unwrap()).collect();
+        expected.sort_unstable();
+        assert_eq!(lines, expected);
+    }
+}
@@ -0,0 +1,668 @@
+use super::{read_at::read_exact_at, Dataset, GetSample};
+use crate::Len;
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::File,
+    io::{self, BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+};
+
+/// Size of the header of a record: its length followed by the checksum of the length.
+const HEADER_LEN: u64 = 12;
+/// Size of the checksum following the data of a record.
+const FOOTER_LEN: u64 = 4;
+
+/// A [`tf.train.Example`](https://www.tensorflow.org/tutorials/load_data/tfrecord), mapping feature names to
+/// their value.
+///
+/// Examples are collated by the [`DefaultCollate`](crate::collate::DefaultCollate) through its map implementation.
+pub type Example = HashMap<String, Feature>;
+
+/// A `tf.train.Feature`, a list of bytes, floats or integers.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Feature {
+    /// A `tf.train.BytesList`.
+    Bytes(Vec<Vec<u8>>),
+    /// A `tf.train.FloatList`.
+    Float(Vec<f32>),
+    /// A `tf.train.Int64List`.
+    Int64(Vec<i64>),
+}
+
+/// Indexable dataset backed by a [`TFRecord`](https://www.tensorflow.org/tutorials/load_data/tfrecord) file of
+/// serialized `tf.train.Example`.
+///
+/// The offset of each record is either computed by scanning the file when it is opened, or read from a precomputed
+/// index. Records are then read on demand, and both their length and data CRC32C checksums are validated.
+///
+/// The dataset can also be used as a streaming source for the [iterable `DataLoader`](crate::iterable::DataLoader),
+/// in which case the file is read sequentially. [`TfRecordIter`] streams a file without indexing it.
+///
+/// ```no_run
+/// use ai_dataloader::{indexable::DataLoader, TfRecordDataset};
+///
+/// # fn main() -> std::io::Result<()> {
+/// let dataset = TfRecordDataset::open("train.tfrecord")?;
+/// let loader = DataLoader::builder(dataset).batch_size(64).shuffle().build();
+///
+/// for batch in &loader {
+///     println!("Labels {:?}", batch["label"]);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct TfRecordDataset {
+    /// Path of the `TFRecord` file.
+    path: PathBuf,
+    /// Handle used to read the records.
+    file: File,
+    /// Byte offset and data length of each record.
+    index: Vec<(u64, u64)>,
+}
+
+impl TfRecordDataset {
+    /// Open a `TFRecord` file and index its records by scanning their headers.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the file can't be read, or if a record length is corrupted.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::open(&path)?;
+        let mut reader = BufReader::new(&file);
+        let mut index = Vec::new();
+        let mut offset = 0_u64;
+        while let Some(len) = read_header(&mut reader)? {
+            index.push((offset, len));
+            let too_large = || invalid_data("record length is too large");
+            let data_len = len.checked_add(FOOTER_LEN).ok_or_else(too_large)?;
+            reader.seek_relative(i64::try_from(data_len).map_err(|_| too_large())?)?;
+            offset = offset
+                .checked_add(HEADER_LEN + data_len)
+                .ok_or_else(too_large)?;
+        }
+        if offset > file.metadata()?.len() {
+            return Err(invalid_data("truncated record"));
+        }
+        Ok(Self { path, file, index })
+    }
+
+    /// Open a `TFRecord` file with a precomputed index.
+    ///
+    /// The index is a text file with one line per record, containing the offset of the record and its total
+    /// size, separated by a space. It's the format produced by the `tfrecord2idx` script of NVIDIA DALI.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if one of the files can't be read, if the index is malformed or if it points past the end of
+    /// the file.
+    pub fn open_with_index<P, I>(path: P, index_path: I) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+        I: AsRef<Path>,
+    {
+        let path = path.as_ref().to_path_buf();
+        let file = File::open(&path)?;
+        let file_len = file.metadata()?.len();
+        let mut index = Vec::new();
+        for line in BufReader::new(File::open(index_path)?).lines() {
+            let line = line?;
+            let mut fields = line.split_whitespace().map(str::parse::<u64>);
+            match (fields.next(), fields.next()) {
+                (Some(Ok(offset)), Some(Ok(size))) if size >= HEADER_LEN + FOOTER_LEN => {
+                    if offset.checked_add(size).map_or(true, |end| end > file_len) {
+                        return Err(invalid_data(format!(
+                            "index line `{line}` points past the end of the file"
+                        )));
+                    }
+                    index.push((offset, size - HEADER_LEN - FOOTER_LEN));
+                }
+                (None, _) => {}
+                _ => return Err(invalid_data(format!("malformed index line `{line}`"))),
+            }
+        }
+        Ok(Self { path, file, index })
+    }
+
+    /// Return the path of the underlying `TFRecord` file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read the raw data of the record at the given index, validating its checksums.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the record can't be read or if a checksum doesn't match.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn read_record(&self, index: usize) -> io::Result<Vec<u8>> {
+        let (offset, len) = self.index[index];
+        let total_len = usize::try_from(HEADER_LEN + len + FOOTER_LEN)
+            .map_err(|_| invalid_data("record length is too large"))?;
+        let mut buf = vec![0; total_len];
+        read_exact_at(&self.file, &mut buf, offset)?;
+        let mut reader = buf.as_slice();
+        if read_header(&mut reader)? != Some(len) {
+            return Err(invalid_data("record length doesn't match the index"));
+        }
+        read_data(&mut reader, len)
+    }
+
+    /// Read and decode the `tf.train.Example` at the given index.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the record can't be read or isn't a valid `tf.train.Example`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn read_example(&self, index: usize) -> io::Result<Example> {
+        decode_example(&self.read_record(index)?)
+    }
+
+    /// Stream the examples in order, reading the file sequentially.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the file can't be opened anymore.
+    #[must_use]
+    pub fn iter(&self) -> TfRecordIter {
+        TfRecordIter::open(&self.path)
+            .unwrap_or_else(|err| panic!("could not open `{}`: {err}", self.path.display()))
+    }
+}
+
+impl fmt::Debug for TfRecordDataset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TfRecordDataset")
+            .field("path", &self.path)
+            .field("len", &self.index.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Len for TfRecordDataset {
+    fn len(&self) -> usize {
+        self.index.len()
+    }
+}
+
+impl GetSample for TfRecordDataset {
+    type Sample = Example;
+    fn get_sample(&self, index: usize) -> Self::Sample {
+        self.read_example(index).unwrap_or_else(|err| {
+            panic!(
+                "could not read record {index} from `{}`: {err}",
+                self.path.display()
+            )
+        })
+    }
+}
+
+impl Dataset for TfRecordDataset {}
+
+impl IntoIterator for &TfRecordDataset {
+    type Item = Example;
+    type IntoIter = TfRecordIter;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for TfRecordDataset {
+    type Item = Example;
+    type IntoIter = TfRecordIter;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator that streams the `tf.train.Example` of a `TFRecord` file in order, without indexing it.
+///
+/// The checksums of each record are validated. The iterator panics if a record can't be read or decoded.
+pub struct TfRecordIter {
+    /// Path of the `TFRecord` file, used in error messages.
+    path: PathBuf,
+    /// The underlying reader.
+    reader: BufReader<File>,
+    /// Number of record read so far.
+    num_read: usize,
+}
+
+impl TfRecordIter {
+    /// Open a `TFRecord` file for streaming.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the file can't be opened.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let reader = BufReader::new(File::open(&path)?);
+        Ok(Self {
+            path,
+            reader,
+            num_read: 0,
+        })
+    }
+
+    /// Read the next record.
+    fn next_example(&mut self) -> io::Result<Option<Example>> {
+        match read_header(&mut self.reader)? {
+            Some(len) => decode_example(&read_data(&mut self.reader, len)?).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+impl fmt::Debug for TfRecordIter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TfRecordIter")
+            .field("path", &self.path)
+            .field("num_read", &self.num_read)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Iterator for TfRecordIter {
+    type Item = Example;
+    fn next(&mut self) -> Option<Self::Item> {
+        let example = self.next_example().unwrap_or_else(|err| {
+            panic!(
+                "could not read record {} from `{}`: {err}",
+                self.num_read,
+                self.path.display()
+            )
+        });
+        self.num_read += 1;
+        example
+    }
+}
+
+fn invalid_data<E>(error: E) -> io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+/// Checksum of `TFRecord`, a CRC32C rotated and offset by a constant.
+fn masked_crc(data: &[u8]) -> u32 {
+    let crc = crc32c::crc32c(data);
+    crc.rotate_right(15).wrapping_add(0xa282_ead8)
+}
+
+/// Read and validate the header of a record, returning the data length or `None` at the end of the file.
+fn read_header(reader: &mut impl Read) -> io::Result<Option<u64>> {
+    let mut header = [0; 12];
+    let mut filled = 0;
+    while filled < header.len() {
+        match reader.read(&mut header[filled..]) {
+            Ok(0) if filled == 0 => return Ok(None),
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => filled += n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    let (len, crc) = header.split_at(8);
+    if masked_crc(len) != u32::from_le_bytes(crc.try_into().expect("4 bytes checksum")) {
+        return Err(invalid_data("corrupted record length"));
+    }
+    Ok(Some(u64::from_le_bytes(
+        len.try_into().expect("8 bytes length"),
+    )))
+}
+
+/// Read and validate the data of a record following its header.
+fn read_data(reader: &mut impl Read, len: u64) -> io::Result<Vec<u8>> {
+    let len = usize::try_from(len).map_err(|_| invalid_data("record length is too large"))?;
+    let mut data = vec![0; len];
+    reader.read_exact(&mut data)?;
+    let mut crc = [0; 4];
+    reader.read_exact(&mut crc)?;
+    if masked_crc(&data) != u32::from_le_bytes(crc) {
+        return Err(invalid_data("corrupted record data"));
+    }
+    Ok(data)
+}
+
+/// Value of a protobuf field.
+enum WireValue<'a> {
+    Varint(u64),
+    Fixed64,
+    LengthDelimited(&'a [u8]),
+    Fixed32(u32),
+}
+
+/// Iterator over the fields of a protobuf message, yielding their number and value.
+struct Fields<'a>(&'a [u8]);
+
+impl<'a> Fields<'a> {
+    fn read_varint(&mut self) -> io::Result<u64> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let (&byte, rest) = self
+                .0
+                .split_first()
+                .ok_or_else(|| invalid_data("truncated varint"))?;
+            self.0 = rest;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid_data("varint is too long"))
+    }
+
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if len > self.0.len() {
+            return Err(invalid_data("truncated field"));
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn next_field(&mut self) -> io::Result<(u64, WireValue<'a>)> {
+        let key = self.read_varint()?;
+        let value = match key & 0x7 {
+            0 => WireValue::Varint(self.read_varint()?),
+            1 => {
+                self.take(8)?;
+                WireValue::Fixed64
+            }
+            2 => {
+                let len = usize::try_from(self.read_varint()?)
+                    .map_err(|_| invalid_data("field is too large"))?;
+                WireValue::LengthDelimited(self.take(len)?)
+            }
+            5 => WireValue::Fixed32(u32::from_le_bytes(
+                self.take(4)?.try_into().expect("4 bytes"),
+            )),
+            wire_type => return Err(invalid_data(format!("unsupported wire type {wire_type}"))),
+        };
+        Ok((key >> 3, value))
+    }
+}
+
+impl<'a> Iterator for Fields<'a> {
+    type Item = io::Result<(u64, WireValue<'a>)>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0.is_empty() {
+            None
+        } else {
+            Some(self.next_field())
+        }
+    }
+}
+
+/// Decode a serialized `tf.train.Example`.
+fn decode_example(data: &[u8]) -> io::Result<Example> {
+    let mut example = Example::new();
+    // message Example { Features features = 1; }
+    for field in Fields(data) {
+        if let (1, WireValue::LengthDelimited(features)) = field? {
+            // message Features { map<string, Feature> feature = 1; }
+            for field in Fields(features) {
+                if let (1, WireValue::LengthDelimited(entry)) = field? {
+                    let (name, feature) = decode_entry(entry)?;
+                    example.insert(name, feature);
+                }
+            }
+        }
+    }
+    Ok(example)
+}
+
+/// Decode an entry of the feature map.
+fn decode_entry(entry: &[u8]) -> io::Result<(String, Feature)> {
+    let mut name = String::new();
+    // A missing value is an empty `Feature`, whose kind is unknown.
+    let mut feature = Feature::Bytes(Vec::new());
+    for field in Fields(entry) {
+        match field? {
+            (1, WireValue::LengthDelimited(key)) => {
+                name = String::from_utf8(key.to_vec())
+                    .map_err(|_| invalid_data("feature name isn't valid UTF-8"))?;
+            }
+            (2, WireValue::LengthDelimited(value)) => feature = decode_feature(value)?,
+            _ => {}
+        }
+    }
+    Ok((name, feature))
+}
+
+/// Decode a `tf.train.Feature`, whose lists can be packed or not.
+fn decode_feature(data: &[u8]) -> io::Result<Feature> {
+    let mut feature = Feature::Bytes(Vec::new());
+    for field in Fields(data) {
+        let (kind @ 1..=3, WireValue::LengthDelimited(list)) = field? else {
+            continue;
+        };
+        feature = match kind {
+            1 => {
+                let mut values = Vec::new();
+                for field in Fields(list) {
+                    if let (1, WireValue::LengthDelimited(bytes)) = field? {
+                        values.push(bytes.to_vec());
+                    }
+                }
+                Feature::Bytes(values)
+            }
+            2 => {
+                let mut values = Vec::new();
+                for field in Fields(list) {
+                    match field? {
+                        (1, WireValue::Fixed32(value)) => values.push(f32::from_bits(value)),
+                        (1, WireValue::LengthDelimited(packed)) => {
+                            if packed.len() % 4 != 0 {
+                                return Err(invalid_data("malformed packed float list"));
+                            }
+                            values.extend(packed.chunks_exact(4).map(|bytes| {
+                                f32::from_le_bytes(bytes.try_into().expect("4 bytes"))
+                            }));
+                        }
+                        _ => {}
+                    }
+                }
+                Feature::Float(values)
+            }
+            _ => {
+                let mut values = Vec::new();
+                for field in Fields(list) {
+                    match field? {
+                        #[allow(clippy::cast_possible_wrap)]
+                        (1, WireValue::Varint(value)) => values.push(value as i64),
+                        (1, WireValue::LengthDelimited(packed)) => {
+                            let mut packed = Fields(packed);
+                            while !packed.0.is_empty() {
+                                #[allow(clippy::cast_possible_wrap)]
+                                values.push(packed.read_varint()? as i64);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                Feature::Int64(values)
+            }
+        };
+    }
+    Ok(feature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{collate::FeatureBatch, indexable::DataLoader, iterable};
+    use ndarray::array;
+    use std::io::Write;
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn varint(mut value: u64, out: &mut Vec<u8>) {
+        while value >= 0x80 {
+            out.push((value & 0x7f) as u8 | 0x80);
+            value >>= 7;
+        }
+        out.push(value as u8);
+    }
+
+    fn length_delimited(field: u64, data: &[u8], out: &mut Vec<u8>) {
+        varint(field << 3 | 2, out);
+        varint(data.len() as u64, out);
+        out.extend_from_slice(data);
+    }
+
+    /// Serialize an example with an unpacked label, packed floats and some bytes.
+    fn encode_example(label: i64, values: &[f32], text: &str) -> Vec<u8> {
+        let mut features = Vec::new();
+
+        let mut int64_list = Vec::new();
+        varint(1 << 3, &mut int64_list);
+        varint(u64::from_ne_bytes(label.to_ne_bytes()), &mut int64_list);
+        let mut feature = Vec::new();
+        length_delimited(3, &int64_list, &mut feature);
+        let mut entry = Vec::new();
+        length_delimited(1, b"label", &mut entry);
+        length_delimited(2, &feature, &mut entry);
+        length_delimited(1, &entry, &mut features);
+
+        let packed: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        let mut float_list = Vec::new();
+        length_delimited(1, &packed, &mut float_list);
+        let mut feature = Vec::new();
+        length_delimited(2, &float_list, &mut feature);
+        let mut entry = Vec::new();
+        length_delimited(1, b"values", &mut entry);
+        length_delimited(2, &feature, &mut entry);
+        length_delimited(1, &entry, &mut features);
+
+        let mut bytes_list = Vec::new();
+        length_delimited(1, text.as_bytes(), &mut bytes_list);
+        let mut feature = Vec::new();
+        length_delimited(1, &bytes_list, &mut feature);
+        let mut entry = Vec::new();
+        length_delimited(1, b"text", &mut entry);
+        length_delimited(2, &feature, &mut entry);
+        length_delimited(1, &entry, &mut features);
+
+        let mut example = Vec::new();
+        length_delimited(1, &features, &mut example);
+        example
+    }
+
+    fn write_record(data: &[u8], out: &mut impl Write) {
+        let len = (data.len() as u64).to_le_bytes();
+        out.write_all(&len).unwrap();
+        out.write_all(&masked_crc(&len).to_le_bytes()).unwrap();
+        out.write_all(data).unwrap();
+        out.write_all(&masked_crc(data).to_le_bytes()).unwrap();
+    }
+
+    fn tfrecord_file() -> tempfile::NamedTempFile {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write_record(&encode_example(0, &[0.5, 1.5], "zero"), &mut file);
+        write_record(&encode_example(-1, &[2.5, 3.5], "one"), &mut file);
+        write_record(&encode_example(2, &[4.5, 5.5], "two"), &mut file);
+        file
+    }
+
+    #[test]
+    fn masked_crc32c() {
+        assert_eq!(crc32c::crc32c(b"123456789"), 0xe306_9283);
+        assert_eq!(
+            masked_crc(b"123456789"),
+            0x2507_c60d_u32.wrapping_add(0xa282_ead8)
+        );
+    }
+
+    #[test]
+    fn random_access() {
+        let file = tfrecord_file();
+        let dataset = TfRecordDataset::open(file.path()).unwrap();
+        assert_eq!(dataset.len(), 3);
+        let example = dataset.get_sample(1);
+        assert_eq!(example["label"], Feature::Int64(vec![-1]));
+        assert_eq!(example["values"], Feature::Float(vec![2.5, 3.5]));
+        assert_eq!(example["text"], Feature::Bytes(vec![b"one".to_vec()]));
+    }
+
+    #[test]
+    fn precomputed_index() {
+        let file = tfrecord_file();
+        let scanned = TfRecordDataset::open(file.path()).unwrap();
+        let mut index_file = tempfile::NamedTempFile::new().unwrap();
+        for (offset, len) in &scanned.index {
+            writeln!(index_file, "{offset} {}", len + HEADER_LEN + FOOTER_LEN).unwrap();
+        }
+        let dataset = TfRecordDataset::open_with_index(file.path(), index_file.path()).unwrap();
+        assert_eq!(dataset.index, scanned.index);
+        assert_eq!(dataset.get_sample(2)["label"], Feature::Int64(vec![2]));
+
+        writeln!(index_file, "12 nope").unwrap();
+        assert!(TfRecordDataset::open_with_index(file.path(), index_file.path()).is_err());
+
+        let mut index_file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(index_file, "0 {}", u64::MAX).unwrap();
+        assert!(TfRecordDataset::open_with_index(file.path(), index_file.path()).is_err());
+    }
+
+    #[test]
+    fn corrupted() {
+        let file = tfrecord_file();
+        let mut content = std::fs::read(file.path()).unwrap();
+        let last = content.len() - 10;
+        content[last] ^= 0xff;
+        std::fs::write(file.path(), &content).unwrap();
+
+        let dataset = TfRecordDataset::open(file.path()).unwrap();
+        assert!(dataset.read_example(1).is_ok());
+        let err = dataset.read_example(2).unwrap_err();
+        assert_eq!(err.to_string(), "corrupted record data");
+
+        content.truncate(last);
+        std::fs::write(file.path(), &content).unwrap();
+        assert!(TfRecordDataset::open(file.path()).is_err());
+    }
+
+    #[test]
+    fn overflowing_length() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let len = (u64::MAX - 1).to_le_bytes();
+        let mut header = len.to_vec();
+        header.extend_from_slice(&masked_crc(&len).to_le_bytes());
+        std::fs::write(file.path(), header).unwrap();
+        let err = TfRecordDataset::open(file.path()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn loaders() {
+        let file = tfrecord_file();
+        let loader = DataLoader::builder(TfRecordDataset::open(file.path()).unwrap())
+            .batch_size(2)
+            .build();
+        let batch = loader.iter().next().unwrap();
+        assert_eq!(batch["label"], FeatureBatch::Int64(array![[0], [-1]]));
+        assert_eq!(
+            batch["values"],
+            FeatureBatch::Float(array![[0.5, 1.5], [2.5, 3.5]])
+        );
+        assert_eq!(
+            batch["text"],
+            FeatureBatch::Bytes(vec![vec![b"zero".to_vec()], vec![b"one".to_vec()]])
+        );
+
+        let loader = iterable::DataLoader::builder(TfRecordIter::open(file.path()).unwrap())
+            .batch_size(2)
+            .build();
+        let batches: Vec<_> = loader.into_iter().collect();
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[1]["label"], FeatureBatch::Int64(array![[2]]));
+    }
+}
@@ -1,30 +1,41 @@
 use crate::{
     collate::{Collate, DefaultCollate},
+    indexable::GetSampleRef,
+    sampler::splitmix64,
+    validation::panic_message,
     Dataset,
 };
-
+use rand::{rngs::StdRng, SeedableRng};
 #[cfg(feature = "rayon")]
-use crate::THREAD_POOL;
+use std::{
+    fmt,
+    sync::mpsc,
+    time::{Duration, Instant},
+};
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::Arc,
+    thread,
+};
 
 #[cfg(feature = "rayon")]
-use rayon::iter::ParallelIterator;
+use super::dataloader::{Batch, TimeoutError};
+use super::dataloader::{FetchError, SampleError};
 
+#[cfg(feature = "async")]
+use crate::indexable::AsyncGetSample;
+#[cfg(feature = "rayon")]
+use crate::GetSample;
+#[cfg(feature = "async")]
+use futures_util::{stream, StreamExt};
 #[cfg(feature = "rayon")]
-use rayon::prelude::IntoParallelIterator;
+use rayon::ThreadPool;
 
-// FIXME: a fetcher trait doesn't make sens anymore.
+#[cfg(feature = "rayon")]
+use rayon::iter::ParallelIterator;
 
-/// A Fetcher will fetch data from the dataset.
-/// Fetcher will be implemented for `MapDataset` (i.e. indexable dataset)
-/// and for iterable dataset.
-pub(crate) trait Fetcher<D, C = DefaultCollate>
-where
-    D: Dataset,
-    C: Collate<D::Sample>,
-{
-    /// Given a batch of index, return the result of the collate function on them.
-    fn fetch(&self, possibly_batched_index: Vec<usize>) -> C::Output;
-}
+#[cfg(feature = "rayon")]
+use rayon::prelude::{IntoParallelIterator, IntoParallelRefIterator};
 
 /// Fetcher for map-style dataset. Simply call the collate function on all the batch of elements.
 #[derive(Debug)]
@@ -34,36 +45,631 @@ where
     C: Collate<D::Sample>,
 {
     /// The dataset data will be fetch from.
-    pub(crate) dataset: &'dataset D,
+    pub(crate) dataset: &'dataset Arc<D>,
     /// The function (generic struct) used to collate data together.
     pub(crate) collate_fn: &'dataset C,
+    /// Seed of the dataloader, from which the per-sample generators are derived.
+    pub(crate) seed: u64,
+    /// Current epoch of the dataloader.
+    pub(crate) epoch: u64,
+    /// Whether the samples whose `get_sample` panics are left out of their batch.
+    pub(crate) skip_failed: bool,
+    /// Thread pool of the dataloader, in which the samples are fetched.
+    #[cfg(feature = "rayon")]
+    pub(crate) thread_pool: Arc<ThreadPool>,
+    /// Maximum duration of the fetch of a batch.
+    #[cfg(feature = "rayon")]
+    pub(crate) timeout: Option<&'dataset Timeout<D>>,
 }
 
-impl<'dataset, D, C> Fetcher<D, C> for MapDatasetFetcher<'dataset, D, C>
+impl<D, C> MapDatasetFetcher<'_, D, C>
 where
     D: Dataset + Sync,
     C: Collate<D::Sample>,
     D::Sample: Send,
 {
-    fn fetch(&self, possibly_batched_index: Vec<usize>) -> C::Output {
+    /// Given a batch of index, return the result of the collate function on them.
+    ///
+    /// The panics of `get_sample` are caught and turned into a [`SampleError`] recording the index of the sample, the
+    /// `batch` number and the epoch. If the failed samples are skipped, they are pushed into `skipped` and removed
+    /// from `indices`, and `None` is returned if no sample is left.
+    pub(crate) fn fetch(
+        &self,
+        indices: &mut Vec<usize>,
+        batch: usize,
+        skipped: &mut Vec<SampleError>,
+    ) -> Option<Result<C::Output, FetchError>> {
         // As the batch length can vary depending on if the last element is dropped or not, we can't use a fix len array to
         // collect the data.
+        let (dataset, seed, epoch) = (&**self.dataset, self.seed, self.epoch);
+        let get_sample = |idx: usize| {
+            panic::catch_unwind(AssertUnwindSafe(|| {
+                dataset.get_sample_with_rng(idx, &mut sample_rng(seed, epoch, idx))
+            }))
+        };
         #[cfg(feature = "rayon")]
-        let data = THREAD_POOL
-            .get()
-            .expect("thread pool is initialized")
-            .install(|| {
-                possibly_batched_index
-                    .into_par_iter()
-                    .map(|idx| self.dataset.get_sample(idx))
-                    .collect()
+        let samples: Vec<_> = match self.timeout {
+            Some(timeout) => match self.fetch_with_timeout(timeout, indices, batch) {
+                Ok(samples) => samples,
+                Err(error) => return Some(Err(FetchError::TimedOut(error))),
+            },
+            None => self
+                .thread_pool
+                .install(|| indices.par_iter().copied().map(get_sample).collect()),
+        };
+        #[cfg(not(feature = "rayon"))]
+        let samples: Vec<_> = indices.iter().copied().map(get_sample).collect();
+
+        self.collate_samples(indices, samples, batch, skipped)
+    }
+
+    /// Collate the fetched samples of a batch, turning the panics of `get_sample` into a [`SampleError`] or leaving
+    /// the samples out of the batch, as in [`MapDatasetFetcher::fetch`].
+    fn collate_samples(
+        &self,
+        indices: &mut Vec<usize>,
+        samples: Vec<thread::Result<D::Sample>>,
+        batch: usize,
+        skipped: &mut Vec<SampleError>,
+    ) -> Option<Result<C::Output, FetchError>> {
+        // Without any panic, the indices are kept as they are.
+        if samples.iter().all(Result::is_ok) {
+            if samples.is_empty() {
+                return None;
+            }
+            return Some(Ok(self
+                .collate_fn
+                .collate(samples.into_iter().flatten().collect())));
+        }
+
+        let mut data = Vec::with_capacity(samples.len());
+        let mut fetched = Vec::with_capacity(samples.len());
+        for (&idx, sample) in indices.iter().zip(samples) {
+            match sample {
+                Ok(sample) => {
+                    data.push(sample);
+                    fetched.push(idx);
+                }
+                Err(payload) => {
+                    let error = SampleError {
+                        index: idx,
+                        batch,
+                        epoch: self.epoch,
+                        message: panic_message(payload),
+                    };
+                    if !self.skip_failed {
+                        return Some(Err(FetchError::Panicked(error)));
+                    }
+                    skipped.push(error);
+                }
+            }
+        }
+        *indices = fetched;
+        if data.is_empty() {
+            return None;
+        }
+        Some(Ok(self.collate_fn.collate(data)))
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<D, C> MapDatasetFetcher<'_, D, C>
+where
+    D: Dataset + Sync,
+    C: Collate<D::Sample>,
+    D::Sample: Send,
+{
+    /// Dispatch each sample to the thread pool and wait for them until the timeout expires, dispatching the pending
+    /// samples again for each retry.
+    ///
+    /// The samples still pending are left running in the pool, as a thread can't be interrupted. The retries are
+    /// dispatched to the same pool, so they only run on the threads which aren't stuck.
+    fn fetch_with_timeout(
+        &self,
+        timeout: &Timeout<D>,
+        indices: &[usize],
+        batch: usize,
+    ) -> Result<Vec<thread::Result<D::Sample>>, TimeoutError> {
+        let (sender, receiver) = mpsc::channel();
+        let mut samples: Vec<_> = indices.iter().map(|_| None).collect();
+        let mut pending = indices.len();
+        for _ in 0..=timeout.retries {
+            for (position, sample) in samples.iter().enumerate() {
+                if sample.is_none() {
+                    timeout.spawner.spawn(
+                        self.dataset,
+                        &self.thread_pool,
+                        SampleTask {
+                            batch,
+                            position,
+                            index: indices[position],
+                            seed: self.seed,
+                            epoch: self.epoch,
+                            sender: sender.clone(),
+                        },
+                    );
+                }
+            }
+            let deadline = Instant::now() + timeout.duration;
+            while pending > 0 {
+                let Some(remaining) = deadline.checked_duration_since(Instant::now()) else {
+                    break;
+                };
+                // The channel can't be disconnected as we hold a sender.
+                let Ok((_, position, sample)) = receiver.recv_timeout(remaining) else {
+                    break;
+                };
+                // A sample dispatched several times is only kept once.
+                if samples[position].is_none() {
+                    samples[position] = Some(sample);
+                    pending -= 1;
+                }
+            }
+            if pending == 0 {
+                return Ok(samples.into_iter().flatten().collect());
+            }
+        }
+        Err(TimeoutError {
+            indices: indices
+                .iter()
+                .zip(&samples)
+                .filter(|(_, sample)| sample.is_none())
+                .map(|(&index, _)| index)
+                .collect(),
+            batch,
+            epoch: self.epoch,
+            timeout: timeout.duration,
+        })
+    }
+}
+
+/// Timeout of the fetch of a batch.
+#[cfg(feature = "rayon")]
+pub(crate) struct Timeout<D> {
+    /// Maximum duration to wait for the samples of a batch.
+    pub(crate) duration: Duration,
+    /// Number of times the pending samples are dispatched again when the timeout expires.
+    pub(crate) retries: usize,
+    /// Dispatch the samples to the thread pool.
+    pub(crate) spawner: Arc<dyn SpawnSample<D>>,
+}
+
+#[cfg(feature = "rayon")]
+impl<D> Timeout<D>
+where
+    D: GetSample + Send + Sync + 'static,
+    D::Sample: Send + 'static,
+{
+    pub(crate) fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            retries: 0,
+            spawner: Arc::new(SampleSpawner),
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<D> Clone for Timeout<D> {
+    fn clone(&self) -> Self {
+        Self {
+            duration: self.duration,
+            retries: self.retries,
+            spawner: Arc::clone(&self.spawner),
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<D> fmt::Debug for Timeout<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Timeout")
+            .field("duration", &self.duration)
+            .field("retries", &self.retries)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Fetch of a sample dispatched to the thread pool.
+#[cfg(feature = "rayon")]
+pub(crate) struct SampleTask<T> {
+    /// Position of the batch of the sample in the epoch.
+    batch: usize,
+    /// Position of the sample in its batch.
+    position: usize,
+    /// Index of the sample in the dataset.
+    index: usize,
+    /// Seed of the dataloader.
+    seed: u64,
+    /// Current epoch of the dataloader.
+    epoch: u64,
+    /// Where to send the sample, or the panic of `get_sample`, along with its batch and position.
+    sender: mpsc::Sender<(usize, usize, thread::Result<T>)>,
+}
+
+/// Dispatch the fetch of a sample to the thread pool, without waiting for it.
+///
+/// The task must be `'static` as it can outlive the iteration, this trait hides the `'static` bounds from the loader.
+#[cfg(feature = "rayon")]
+pub(crate) trait SpawnSample<D>: Send + Sync {
+    fn spawn(&self, dataset: &Arc<D>, thread_pool: &ThreadPool, task: SampleTask<D::Sample>)
+    where
+        D: GetSample;
+}
+
+#[cfg(feature = "rayon")]
+struct SampleSpawner;
+
+#[cfg(feature = "rayon")]
+impl<D> SpawnSample<D> for SampleSpawner
+where
+    D: GetSample + Send + Sync + 'static,
+    D::Sample: Send + 'static,
+{
+    fn spawn(&self, dataset: &Arc<D>, thread_pool: &ThreadPool, task: SampleTask<D::Sample>) {
+        let dataset = Arc::clone(dataset);
+        thread_pool.spawn(move || {
+            let sample = panic::catch_unwind(AssertUnwindSafe(|| {
+                dataset.get_sample_with_rng(
+                    task.index,
+                    &mut sample_rng(task.seed, task.epoch, task.index),
+                )
+            }));
+            // The receiver is gone if the batch timed out.
+            let _ = task.sender.send((task.batch, task.position, sample));
+        });
+    }
+}
+
+/// Fetch of the batches out of order, set with the builder `in_order(false)`.
+#[cfg(feature = "rayon")]
+pub(crate) struct OutOfOrder<D> {
+    /// Dispatch the samples to the thread pool.
+    spawner: Arc<dyn SpawnSample<D>>,
+}
+
+#[cfg(feature = "rayon")]
+impl<D> OutOfOrder<D>
+where
+    D: GetSample + Send + Sync + 'static,
+    D::Sample: Send + 'static,
+{
+    pub(crate) fn new() -> Self {
+        Self {
+            spawner: Arc::new(SampleSpawner),
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<D> Clone for OutOfOrder<D> {
+    fn clone(&self) -> Self {
+        Self {
+            spawner: Arc::clone(&self.spawner),
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<D> fmt::Debug for OutOfOrder<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OutOfOrder").finish_non_exhaustive()
+    }
+}
+
+/// Batches dispatched to the thread pool by an iteration out of order, whose samples aren't all collated yet.
+#[cfg(feature = "rayon")]
+pub(crate) struct InFlight<D: GetSample> {
+    spawner: Arc<dyn SpawnSample<D>>,
+    sender: mpsc::Sender<(usize, usize, thread::Result<D::Sample>)>,
+    receiver: mpsc::Receiver<(usize, usize, thread::Result<D::Sample>)>,
+    /// The batches in flight, in the order they were dispatched.
+    batches: Vec<PendingBatch<D::Sample>>,
+}
+
+/// Batch whose samples are being fetched in the thread pool.
+#[cfg(feature = "rayon")]
+struct PendingBatch<T> {
+    /// Position of the batch in the epoch.
+    step: usize,
+    /// Indices in the dataset of the samples of the batch.
+    indices: Vec<usize>,
+    /// The samples fetched so far.
+    samples: Vec<Option<thread::Result<T>>>,
+    /// Number of samples not fetched yet.
+    pending: usize,
+    /// When the current attempt times out, if the loader has a timeout.
+    deadline: Option<Instant>,
+    /// Number of times the pending samples can still be dispatched again.
+    retries: usize,
+}
+
+#[cfg(feature = "rayon")]
+impl<T> PendingBatch<T> {
+    /// Return the position in the batch of the samples not fetched yet.
+    fn pending_positions(&self) -> Vec<usize> {
+        (0..self.samples.len())
+            .filter(|&position| self.samples[position].is_none())
+            .collect()
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<D: GetSample> InFlight<D> {
+    pub(crate) fn new(out_of_order: &OutOfOrder<D>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            spawner: Arc::clone(&out_of_order.spawner),
+            sender,
+            receiver,
+            batches: Vec::new(),
+        }
+    }
+
+    /// Return the number of batches in flight.
+    pub(crate) fn len(&self) -> usize {
+        self.batches.len()
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<D: GetSample> fmt::Debug for InFlight<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InFlight")
+            .field(
+                "steps",
+                &self
+                    .batches
+                    .iter()
+                    .map(|batch| batch.step)
+                    .collect::<Vec<_>>(),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<D, C> MapDatasetFetcher<'_, D, C>
+where
+    D: Dataset + Sync,
+    C: Collate<D::Sample>,
+    D::Sample: Send,
+{
+    /// Return the first batch ready, keeping one batch per thread of the pool in flight.
+    ///
+    /// The batches of indices are drawn from `batches` and dispatched with their step in the epoch, counted by
+    /// `num_dispatched`.
+    pub(crate) fn next_out_of_order(
+        &self,
+        in_flight: &mut InFlight<D>,
+        batches: &mut impl Iterator<Item = Vec<usize>>,
+        num_dispatched: &mut usize,
+        skipped: &mut Vec<SampleError>,
+    ) -> Option<Batch<Result<C::Output, FetchError>>> {
+        loop {
+            while in_flight.len() < self.thread_pool.current_num_threads() {
+                let Some(indices) = batches.next() else {
+                    break;
+                };
+                self.dispatch(in_flight, indices, *num_dispatched);
+                *num_dispatched += 1;
+            }
+            if in_flight.len() == 0 {
+                return None;
+            }
+            if let Some(batch) = self.next_ready(in_flight, skipped) {
+                return Some(batch);
+            }
+        }
+    }
+
+    /// Dispatch the samples of a batch to the thread pool, without waiting for them.
+    fn dispatch(&self, in_flight: &mut InFlight<D>, indices: Vec<usize>, step: usize) {
+        let mut batch = PendingBatch {
+            step,
+            samples: indices.iter().map(|_| None).collect(),
+            pending: indices.len(),
+            indices,
+            deadline: None,
+            retries: self.timeout.map_or(0, |timeout| timeout.retries),
+        };
+        self.dispatch_pending(in_flight, &mut batch);
+        in_flight.batches.push(batch);
+    }
+
+    /// Dispatch the pending samples of a batch, and start the timeout of this attempt.
+    fn dispatch_pending(&self, in_flight: &InFlight<D>, batch: &mut PendingBatch<D::Sample>) {
+        for position in batch.pending_positions() {
+            in_flight.spawner.spawn(
+                self.dataset,
+                &self.thread_pool,
+                SampleTask {
+                    batch: batch.step,
+                    position,
+                    index: batch.indices[position],
+                    seed: self.seed,
+                    epoch: self.epoch,
+                    sender: in_flight.sender.clone(),
+                },
+            );
+        }
+        batch.deadline = self
+            .timeout
+            .map(|timeout| Instant::now() + timeout.duration);
+    }
+
+    /// Wait for the first batch in flight whose samples are all fetched, and collate it.
+    ///
+    /// Return `None` if all the samples of the batch were skipped. A batch still pending when its timeout expires is
+    /// yielded as a [`TimeoutError`] once it can't be dispatched again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no batch is in flight.
+    fn next_ready(
+        &self,
+        in_flight: &mut InFlight<D>,
+        skipped: &mut Vec<SampleError>,
+    ) -> Option<Batch<Result<C::Output, FetchError>>> {
+        assert!(in_flight.len() > 0, "no batch in flight");
+        loop {
+            if let Some(ready) = in_flight
+                .batches
+                .iter()
+                .position(|batch| batch.pending == 0)
+            {
+                let batch = in_flight.batches.remove(ready);
+                let mut indices = batch.indices;
+                let samples = batch.samples.into_iter().flatten().collect();
+                let data = self.collate_samples(&mut indices, samples, batch.step, skipped)?;
+                return Some(Batch {
+                    data,
+                    indices,
+                    epoch: self.epoch,
+                    step: batch.step,
+                });
+            }
+            let deadline = in_flight
+                .batches
+                .iter()
+                .filter_map(|batch| batch.deadline)
+                .min();
+            // The channel can't be disconnected as we hold a sender.
+            let received = match deadline {
+                Some(deadline) => in_flight
+                    .receiver
+                    .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                    .ok(),
+                None => in_flight.receiver.recv().ok(),
+            };
+            if let Some((step, position, sample)) = received {
+                // The samples of a batch which timed out, or dispatched several times, are dropped.
+                if let Some(batch) = in_flight
+                    .batches
+                    .iter_mut()
+                    .find(|batch| batch.step == step)
+                {
+                    if batch.samples[position].is_none() {
+                        batch.samples[position] = Some(sample);
+                        batch.pending -= 1;
+                    }
+                }
+                continue;
+            }
+            let now = Instant::now();
+            let Some(expired) = in_flight.batches.iter().position(|batch| {
+                batch.pending > 0 && batch.deadline.map_or(false, |deadline| deadline <= now)
+            }) else {
+                continue;
+            };
+            if in_flight.batches[expired].retries > 0 {
+                let mut batch = in_flight.batches.remove(expired);
+                batch.retries -= 1;
+                self.dispatch_pending(in_flight, &mut batch);
+                in_flight.batches.insert(expired, batch);
+                continue;
+            }
+            let batch = in_flight.batches.remove(expired);
+            let error = TimeoutError {
+                indices: batch
+                    .pending_positions()
+                    .into_iter()
+                    .map(|position| batch.indices[position])
+                    .collect(),
+                batch: batch.step,
+                epoch: self.epoch,
+                timeout: self
+                    .timeout
+                    .map_or(Duration::ZERO, |timeout| timeout.duration),
+            };
+            return Some(Batch {
+                data: Err(FetchError::TimedOut(error)),
+                indices: batch.indices,
+                epoch: self.epoch,
+                step: batch.step,
             });
+        }
+    }
+}
+
+/// Fetcher for map-style dataset lending their samples. Call the collate function on the borrowed samples.
+#[derive(Debug)]
+pub(crate) struct MapDatasetRefFetcher<'dataset, D, C = DefaultCollate> {
+    /// The dataset data will be borrowed from.
+    pub(crate) dataset: &'dataset D,
+    /// The function (generic struct) used to collate data together.
+    pub(crate) collate_fn: &'dataset C,
+    /// Thread pool of the dataloader, in which the samples are fetched.
+    #[cfg(feature = "rayon")]
+    pub(crate) thread_pool: Arc<ThreadPool>,
+}
+
+impl<'dataset, D, C> MapDatasetRefFetcher<'dataset, D, C>
+where
+    D: GetSampleRef + Sync,
+    C: Collate<D::SampleRef<'dataset>>,
+    D::SampleRef<'dataset>: Send,
+{
+    /// Given a batch of index, return the result of the collate function on the borrowed samples.
+    pub(crate) fn fetch(&self, possibly_batched_index: Vec<usize>) -> C::Output {
+        let dataset = self.dataset;
+        #[cfg(feature = "rayon")]
+        let data = self.thread_pool.install(|| {
+            possibly_batched_index
+                .into_par_iter()
+                .map(|idx| dataset.get_sample_ref(idx))
+                .collect()
+        });
         #[cfg(not(feature = "rayon"))]
         let data = possibly_batched_index
             .into_iter()
-            .map(|idx| self.dataset.get_sample(idx))
+            .map(|idx| dataset.get_sample_ref(idx))
             .collect();
 
         self.collate_fn.collate(data)
     }
 }
+
+/// Fetcher for async datasets. Poll the futures of the samples concurrently, then call the collate function on the
+/// samples in the order of the indices.
+#[cfg(feature = "async")]
+#[derive(Debug)]
+pub(crate) struct AsyncDatasetFetcher<'dataset, D, C = DefaultCollate> {
+    /// The dataset data will be fetch from.
+    pub(crate) dataset: &'dataset D,
+    /// The function (generic struct) used to collate data together.
+    pub(crate) collate_fn: &'dataset C,
+    /// Maximum number of samples fetched concurrently, the whole batch if not set.
+    pub(crate) max_concurrency: Option<usize>,
+}
+
+#[cfg(feature = "async")]
+impl<D, C> AsyncDatasetFetcher<'_, D, C>
+where
+    D: AsyncGetSample,
+    C: Collate<D::Sample>,
+{
+    /// Given a batch of index, return the result of the collate function on them.
+    pub(crate) async fn fetch(&self, indices: Vec<usize>) -> C::Output {
+        let max_concurrency = self.max_concurrency.unwrap_or(indices.len()).max(1);
+        let dataset = self.dataset;
+        let samples = stream::iter(indices)
+            .map(|idx| dataset.get_sample_async(idx))
+            .buffered(max_concurrency)
+            .collect()
+            .await;
+        self.collate_fn.collate(samples)
+    }
+}
+
+/// Return the generator of a sample, derived from the loader seed, the epoch and the sample index.
+///
+/// It doesn't depend on the thread fetching the sample nor on the position of the sample in the batch,
+/// so the random transforms are reproducible.
+pub(crate) fn sample_rng(seed: u64, epoch: u64, index: usize) -> StdRng {
+    let mut state = seed;
+    for value in [epoch, index as u64] {
+        state = splitmix64(state ^ value);
+    }
+    StdRng::seed_from_u64(state)
+}
@@ -4,16 +4,49 @@
 use crate::Len;
 
 mod batch_sampler;
+mod block_shuffle_sampler;
+mod permutation_sampler;
 mod random_sampler;
 mod sequential_sampler;
+mod shard_shuffle_sampler;
 
 pub use batch_sampler::{BatchIterator, BatchSampler};
+pub use block_shuffle_sampler::{BlockShuffleSampler, BlockShuffleSamplerIter};
+pub use permutation_sampler::{PermutationSampler, PermutationSamplerIter};
 pub use random_sampler::RandomSampler;
 pub use sequential_sampler::SequentialSampler;
+pub use shard_shuffle_sampler::{ShardLayout, ShardShuffleSampler, ShardShuffleSamplerIter};
 
-// TODO: maybe copy is too restrictive and should be replaced by Clone?
 /// Every Sampler is iterable and has a length.
-pub trait Sampler: Len + IntoIterator<Item = usize> + Copy {
+///
+/// The sampler is cloned at the start of each iteration.
+pub trait Sampler: Len + IntoIterator<Item = usize> + Clone {
     /// Create a new sampler form the dataset length.
     fn new(data_source_len: usize) -> Self;
+    /// Seed the randomness of the sampler.
+    ///
+    /// The indexable `DataLoader` calls it on the copy of the sampler of each iteration, with a seed derived from its
+    /// own seed, the current epoch and the number of iterations started in the epoch, so the order of the samples is
+    /// reproducible and changes from one iteration to another. By default the seed is ignored.
+    fn set_seed(&mut self, _seed: u64) {}
+    /// Return whether the order of the indices is random.
+    ///
+    /// The indexable `DataLoader` can't resume an epoch of a shuffling sampler from a step without a seed, as the
+    /// order of the epoch can't be drawn again. By default a sampler isn't shuffling.
+    fn is_shuffling(&self) -> bool {
+        false
+    }
+}
+
+/// Return the seed of the sampler for an epoch, derived from the dataloader seed.
+pub(crate) fn epoch_seed(seed: u64, epoch: u64) -> u64 {
+    splitmix64(splitmix64(seed) ^ epoch)
+}
+
+/// `SplitMix64` finalizer, scrambling the bits of `x`.
+pub(crate) fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
 }
@@ -53,7 +53,18 @@ impl<S: Sampler> BatchSampler<S> {
     /// Return an iterator over the [`BatchSampler`].
     pub fn iter(&self) -> BatchIterator<S::IntoIter> {
         BatchIterator {
-            sampler: self.sampler.into_iter(),
+            sampler: self.sampler.clone().into_iter(),
+            batch_size: self.batch_size,
+            drop_last: self.drop_last,
+        }
+    }
+
+    /// Return an iterator over the [`BatchSampler`], seeding the sampler first.
+    pub(crate) fn iter_with_seed(&self, seed: u64) -> BatchIterator<S::IntoIter> {
+        let mut sampler = self.sampler.clone();
+        sampler.set_seed(seed);
+        BatchIterator {
+            sampler: sampler.into_iter(),
             batch_size: self.batch_size,
             drop_last: self.drop_last,
         }
@@ -141,6 +152,7 @@ mod tests {
         assert_eq!(iter.next(), Some(vec![6, 7, 8]));
     }
     #[test]
+    #[allow(clippy::single_range_in_vec_init)]
     fn batch_sampler() {
         // TODO : test from pytorch, need to support custom batch sampler
         let mut batches = Vec::new();
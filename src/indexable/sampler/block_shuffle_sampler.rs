@@ -0,0 +1,236 @@
+use super::{Len, Sampler};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use std::ops::Range;
+
+/// Default number of indices in a block.
+const DEFAULT_BLOCK_SIZE: usize = 1024;
+/// Default number of blocks shuffled together.
+const DEFAULT_WINDOW: usize = 8;
+
+/// Sampler shuffling blocks of contiguous indices, for datasets backed by files where random reads are costly.
+///
+/// The indices are split into contiguous blocks of `block_size` indices. The order of the blocks is shuffled, then
+/// the indices are drawn randomly from a sliding window of `window` blocks: each time a block worth of indices has
+/// been drawn, the next block enters the window. The reads stay within `window` blocks at a time, which trades
+/// randomness for locality. With a `window` of one, blocks are read one after the other, each shuffled in place.
+///
+/// ```
+/// use ai_dataloader::indexable::DataLoader;
+/// use ai_dataloader::sampler::{BlockShuffleSampler, Sampler};
+///
+/// let dataset: Vec<i32> = (0..10_000).collect();
+/// let sampler = BlockShuffleSampler::new(dataset.len()).block_size(256).window(4);
+/// let loader = DataLoader::builder(dataset).with_sampler(sampler).batch_size(32).seed(0).build();
+/// assert_eq!(loader.iter().map(|batch| batch.len()).sum::<usize>(), 10_000);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Hash, Eq, Ord)]
+pub struct BlockShuffleSampler {
+    /// The length of the data source.
+    data_source_len: usize,
+    /// Number of indices in a block.
+    block_size: usize,
+    /// Number of blocks shuffled together.
+    window: usize,
+    /// Seed of the shuffle, drawn randomly if not set.
+    seed: Option<u64>,
+}
+
+impl BlockShuffleSampler {
+    /// Set the number of indices in a block. By default a block contains 1024 indices.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block_size` is zero.
+    #[must_use]
+    pub fn block_size(mut self, block_size: usize) -> Self {
+        assert!(block_size > 0, "block size should be positive");
+        self.block_size = block_size;
+        self
+    }
+
+    /// Set the number of blocks shuffled together. By default 8 blocks are shuffled together.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window` is zero.
+    #[must_use]
+    pub fn window(mut self, window: usize) -> Self {
+        assert!(window > 0, "window should be positive");
+        self.window = window;
+        self
+    }
+}
+
+impl Sampler for BlockShuffleSampler {
+    fn new(data_source_len: usize) -> Self {
+        Self {
+            data_source_len,
+            block_size: DEFAULT_BLOCK_SIZE,
+            window: DEFAULT_WINDOW,
+            seed: None,
+        }
+    }
+    fn set_seed(&mut self, seed: u64) {
+        self.seed = Some(seed);
+    }
+    fn is_shuffling(&self) -> bool {
+        true
+    }
+}
+
+impl Len for BlockShuffleSampler {
+    fn len(&self) -> usize {
+        self.data_source_len
+    }
+}
+
+impl IntoIterator for BlockShuffleSampler {
+    type Item = usize;
+    type IntoIter = BlockShuffleSamplerIter;
+    fn into_iter(self) -> Self::IntoIter {
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let num_blocks = (self.data_source_len + self.block_size - 1) / self.block_size;
+        let mut blocks: Vec<usize> = (0..num_blocks).collect();
+        blocks.shuffle(&mut rng);
+
+        let mut iter = BlockShuffleSamplerIter {
+            sampler: self,
+            rng,
+            blocks: blocks.into_iter(),
+            window: Vec::with_capacity(self.window * self.block_size),
+            remaining: self.data_source_len,
+        };
+        for _ in 0..self.window {
+            iter.push_next_block();
+        }
+        iter
+    }
+}
+
+/// Iterator over the indices of a [`BlockShuffleSampler`].
+#[derive(Debug)]
+pub struct BlockShuffleSamplerIter {
+    /// The sampler configuration.
+    sampler: BlockShuffleSampler,
+    /// Generator drawing the indices from the window.
+    rng: StdRng,
+    /// Blocks that didn't enter the window yet, in shuffled order.
+    blocks: std::vec::IntoIter<usize>,
+    /// Indices of the blocks of the window that haven't been drawn yet.
+    window: Vec<usize>,
+    /// Number of indices left to draw.
+    remaining: usize,
+}
+
+impl BlockShuffleSamplerIter {
+    /// Add the indices of the next block to the window.
+    fn push_next_block(&mut self) {
+        if let Some(block) = self.blocks.next() {
+            self.window.extend(self.block_range(block));
+        }
+    }
+
+    fn block_range(&self, block: usize) -> Range<usize> {
+        let start = block * self.sampler.block_size;
+        start..(start + self.sampler.block_size).min(self.sampler.data_source_len)
+    }
+}
+
+impl Iterator for BlockShuffleSamplerIter {
+    type Item = usize;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.window.is_empty() {
+            return None;
+        }
+        let index = self
+            .window
+            .swap_remove(self.rng.gen_range(0..self.window.len()));
+        self.remaining -= 1;
+        // Slide the window once a block worth of indices has been drawn from it.
+        if self.window.len() <= (self.sampler.window - 1) * self.sampler.block_size {
+            self.push_next_block();
+        }
+        Some(index)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for BlockShuffleSamplerIter {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seeded_indices(sampler: BlockShuffleSampler) -> Vec<usize> {
+        let mut sampler = sampler;
+        sampler.set_seed(0);
+        sampler.into_iter().collect()
+    }
+
+    #[test]
+    fn permutation() {
+        for len in [0, 1, 7, 100, 1001] {
+            for (block_size, window) in [(1, 1), (10, 1), (10, 3), (64, 100)] {
+                let sampler = BlockShuffleSampler::new(len)
+                    .block_size(block_size)
+                    .window(window);
+                let mut indices = seeded_indices(sampler);
+                assert_eq!(indices.len(), len);
+                indices.sort_unstable();
+                assert_eq!(indices, (0..len).collect::<Vec<_>>());
+            }
+        }
+    }
+
+    #[test]
+    fn locality() {
+        let sampler = BlockShuffleSampler::new(1000).block_size(10).window(1);
+        let indices = seeded_indices(sampler);
+        // Each block is read entirely before the next one.
+        for chunk in indices.chunks(10) {
+            let block = chunk[0] / 10;
+            assert!(chunk.iter().all(|index| index / 10 == block));
+        }
+        assert_ne!(indices, (0..1000).collect::<Vec<_>>());
+
+        // A new block enters the window each time a block worth of indices has been drawn.
+        let sampler = BlockShuffleSampler::new(1000).block_size(10).window(3);
+        let mut first_draws = vec![None; 100];
+        for (draw, index) in seeded_indices(sampler).into_iter().enumerate() {
+            first_draws[index / 10].get_or_insert(draw);
+        }
+        let mut first_draws: Vec<usize> = first_draws.into_iter().flatten().collect();
+        first_draws.sort_unstable();
+        for (blocks, draw) in first_draws.into_iter().enumerate() {
+            assert!(blocks < 3 + draw / 10);
+        }
+    }
+
+    #[test]
+    fn seeded() {
+        let sampler = BlockShuffleSampler::new(1000).block_size(10).window(3);
+        assert_eq!(seeded_indices(sampler), seeded_indices(sampler));
+        let mut other = sampler;
+        other.set_seed(1);
+        assert_ne!(
+            seeded_indices(sampler),
+            other.into_iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn len() {
+        let sampler = BlockShuffleSampler::new(25).block_size(10);
+        assert_eq!(sampler.len(), 25);
+        let mut iter = sampler.into_iter();
+        assert_eq!(iter.len(), 25);
+        let _ = iter.next();
+        assert_eq!(iter.len(), 24);
+    }
+}
@@ -0,0 +1,244 @@
+use super::{splitmix64, Len, Sampler};
+
+/// Number of rounds of the Feistel network.
+const ROUNDS: usize = 8;
+
+/// Sampler that returns a random permutation of the indices between zero and `data_source_len`, without storing it.
+///
+/// Unlike [`RandomSampler`](super::RandomSampler) which shuffles a `Vec` of all the indices, the permutation is
+/// computed on the fly with a keyed Feistel network, so the sampler uses a constant amount of memory whatever the
+/// size of the dataset. The network permutes the smallest power of four range containing the indices; values
+/// outside of the dataset are skipped by iterating the network again (cycle walking).
+///
+/// ```
+/// use ai_dataloader::sampler::PermutationSampler;
+///
+/// let sampler = PermutationSampler::with_seed(10, 42);
+/// let mut indices: Vec<usize> = sampler.into_iter().collect();
+/// assert_eq!(indices, sampler.into_iter().collect::<Vec<_>>());
+/// indices.sort_unstable();
+/// assert_eq!(indices, (0..10).collect::<Vec<_>>());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Hash, Eq, Ord)]
+pub struct PermutationSampler {
+    /// The length of the data source.
+    data_source_len: usize,
+    /// Seed of the permutation, drawn randomly if not set.
+    seed: Option<u64>,
+}
+
+impl PermutationSampler {
+    /// Create a new [`PermutationSampler`] with a given seed.
+    #[must_use]
+    pub fn with_seed(data_source_len: usize, seed: u64) -> Self {
+        Self {
+            data_source_len,
+            seed: Some(seed),
+        }
+    }
+}
+
+impl Sampler for PermutationSampler {
+    fn new(data_source_len: usize) -> Self {
+        Self {
+            data_source_len,
+            seed: None,
+        }
+    }
+    fn set_seed(&mut self, seed: u64) {
+        self.seed = Some(seed);
+    }
+    fn is_shuffling(&self) -> bool {
+        true
+    }
+}
+
+impl Len for PermutationSampler {
+    fn len(&self) -> usize {
+        self.data_source_len
+    }
+}
+
+impl IntoIterator for PermutationSampler {
+    type Item = usize;
+    type IntoIter = PermutationSamplerIter;
+    fn into_iter(self) -> Self::IntoIter {
+        PermutationSamplerIter {
+            permutation: Feistel::new(self.data_source_len, self.seed.unwrap_or_else(rand::random)),
+            next: 0,
+            len: self.data_source_len,
+        }
+    }
+}
+
+/// Iterator over a random permutation of the indices between zero and `data_source_len`.
+#[derive(Debug)]
+pub struct PermutationSamplerIter {
+    /// The permutation.
+    permutation: Feistel,
+    /// Position of the next index in the permutation.
+    next: usize,
+    /// The length of the permutation.
+    len: usize,
+}
+
+impl Iterator for PermutationSamplerIter {
+    type Item = usize;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next < self.len {
+            self.next += 1;
+            Some(self.permutation.permute(self.next - 1))
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len - self.next;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for PermutationSamplerIter {}
+
+/// Balanced Feistel network, permuting `[0, len)` with cycle walking.
+#[derive(Debug, Clone, Copy)]
+struct Feistel {
+    /// Size of the permuted range.
+    len: u64,
+    /// Number of bits of each half of the network input.
+    half_bits: u32,
+    /// Mask of a half.
+    half_mask: u64,
+    /// Key of each round.
+    keys: [u64; ROUNDS],
+}
+
+impl Feistel {
+    fn new(len: usize, seed: u64) -> Self {
+        let len = len as u64;
+        // Number of bits to represent the indices, rounded up to an even number so the two halves have the same size.
+        let bits = (u64::BITS - len.saturating_sub(1).leading_zeros()).max(2);
+        let half_bits = (bits + 1) / 2;
+        let mut keys = [0; ROUNDS];
+        let mut state = seed;
+        for key in &mut keys {
+            state = splitmix64(state);
+            *key = state;
+        }
+        Self {
+            len,
+            half_bits,
+            half_mask: (1 << half_bits) - 1,
+            keys,
+        }
+    }
+
+    /// Return the image of `index`, walking the cycle until it lands back into `[0, len)`.
+    #[allow(clippy::cast_possible_truncation)]
+    fn permute(&self, index: usize) -> usize {
+        let mut value = index as u64;
+        loop {
+            value = self.encrypt(value);
+            if value < self.len {
+                // The value is lower than the `len`, which is an `usize`.
+                return value as usize;
+            }
+        }
+    }
+
+    fn encrypt(&self, value: u64) -> u64 {
+        let mut left = value >> self.half_bits;
+        let mut right = value & self.half_mask;
+        for key in self.keys {
+            let round = splitmix64(right ^ key) & self.half_mask;
+            (left, right) = (right, left ^ round);
+        }
+        (left << self.half_bits) | right
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bijection() {
+        for len in [0, 1, 2, 3, 5, 16, 17, 100, 1000, 4099] {
+            for seed in 0..3 {
+                let mut indices: Vec<usize> = PermutationSampler::with_seed(len, seed)
+                    .into_iter()
+                    .collect();
+                indices.sort_unstable();
+                assert_eq!(indices, (0..len).collect::<Vec<_>>());
+            }
+        }
+    }
+
+    #[test]
+    fn seeded() {
+        let sampler = PermutationSampler::with_seed(1000, 7);
+        let indices: Vec<usize> = sampler.into_iter().collect();
+        assert_eq!(indices, sampler.into_iter().collect::<Vec<_>>());
+        assert_ne!(
+            indices,
+            PermutationSampler::with_seed(1000, 8)
+                .into_iter()
+                .collect::<Vec<_>>()
+        );
+        assert_ne!(indices, (0..1000).collect::<Vec<_>>());
+
+        let mut sampler = PermutationSampler::new(1000);
+        sampler.set_seed(7);
+        assert_eq!(indices, sampler.into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn len() {
+        let sampler = PermutationSampler::new(10);
+        assert_eq!(sampler.len(), 10);
+        let mut iter = sampler.into_iter();
+        assert_eq!(iter.len(), 10);
+        let _ = iter.next();
+        assert_eq!(iter.len(), 9);
+    }
+
+    /// Each index should land at each position with the same frequency, as with `SliceRandom::shuffle`.
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn uniform() {
+        const LEN: usize = 10;
+        const PERMUTATIONS: u64 = 20_000;
+        let mut counts = [[0_u64; LEN]; LEN];
+        for seed in 0..PERMUTATIONS {
+            for (position, index) in PermutationSampler::with_seed(LEN, seed)
+                .into_iter()
+                .enumerate()
+            {
+                counts[position][index] += 1;
+            }
+        }
+
+        // Chi-squared test with 81 degrees of freedom, the 99.9% quantile is about 129.
+        let expected = PERMUTATIONS as f64 / LEN as f64;
+        let chi_squared: f64 = counts
+            .iter()
+            .flatten()
+            .map(|&count| (count as f64 - expected).powi(2) / expected)
+            .sum();
+        assert!(chi_squared < 129., "chi squared is {chi_squared}");
+
+        // Consecutive indices should not stay next to each other.
+        let adjacent = (0..PERMUTATIONS)
+            .filter(|&seed| {
+                let indices: Vec<usize> = PermutationSampler::with_seed(LEN, seed)
+                    .into_iter()
+                    .collect();
+                indices
+                    .windows(2)
+                    .all(|pair| pair[1] == (pair[0] + 1) % LEN)
+            })
+            .count();
+        assert!(adjacent < 10);
+    }
+}
@@ -1,5 +1,6 @@
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::{thread_rng, SeedableRng};
 
 use super::{Len, Sampler};
 
@@ -11,6 +12,8 @@ pub struct RandomSampler {
     /// Whether the sample is replaced or not.
     /// If it's replaced, we can have 2 times the same sample.
     replacement: bool,
+    /// Seed of the shuffle, the thread generator is used if not set.
+    seed: Option<u64>,
 }
 
 impl Sampler for RandomSampler {
@@ -18,8 +21,15 @@ impl Sampler for RandomSampler {
         Self {
             data_source_len,
             replacement: false,
+            seed: None,
         }
     }
+    fn set_seed(&mut self, seed: u64) {
+        self.seed = Some(seed);
+    }
+    fn is_shuffling(&self) -> bool {
+        true
+    }
 }
 impl Len for RandomSampler {
     fn len(&self) -> usize {
@@ -30,7 +40,7 @@ impl IntoIterator for RandomSampler {
     type Item = usize;
     type IntoIter = RandomSamplerIter;
     fn into_iter(self) -> Self::IntoIter {
-        RandomSamplerIter::new(self.data_source_len, self.replacement)
+        RandomSamplerIter::new(self.data_source_len, self.replacement, self.seed)
     }
 }
 /// Iterator that returns random index between zero and `data_source_len`.
@@ -49,14 +59,18 @@ impl RandomSamplerIter {
     ///
     /// * `data_source_len` - The length of the dataset.
     /// * `replacement` - Whether we can have the same sample twice over one iteration or not.
+    /// * `seed` - Seed of the shuffle, the thread generator is used if `None`.
     // FIXME: change this parameters in the next breaking release
     #[allow(clippy::fn_params_excessive_bools)]
-    fn new(data_source_len: usize, replacement: bool) -> Self {
+    fn new(data_source_len: usize, replacement: bool, seed: Option<u64>) -> Self {
         if replacement {
             todo!()
         } else {
             let mut vec: Vec<usize> = (0..data_source_len).collect();
-            vec.shuffle(&mut thread_rng());
+            match seed {
+                Some(seed) => vec.shuffle(&mut StdRng::seed_from_u64(seed)),
+                None => vec.shuffle(&mut thread_rng()),
+            }
             Self {
                 indexes: vec,
                 idx: 0,
@@ -93,6 +107,7 @@ mod tests {
         let random_sampler = RandomSampler {
             data_source_len: 10,
             replacement: false,
+            seed: None,
         };
         for idx in random_sampler {
             println!("{idx}");
@@ -104,6 +119,7 @@ mod tests {
         let random_sampler = RandomSampler {
             data_source_len: 10,
             replacement: false,
+            seed: None,
         };
 
         assert_eq!(random_sampler.len(), 10);
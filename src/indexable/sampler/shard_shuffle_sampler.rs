@@ -0,0 +1,323 @@
+use super::{Len, Sampler};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use std::{ops::Range, sync::Arc};
+
+/// Layout of a dataset split into shards of contiguous indices, such as a
+/// [`ShardedDataset`](crate::indexable::ShardedDataset).
+///
+/// The shards can have different lengths, including zero. The layout is cheap to clone.
+///
+/// ```
+/// use ai_dataloader::sampler::ShardLayout;
+///
+/// let layout = ShardLayout::from_lens([3, 0, 2]);
+/// assert_eq!(layout.num_shards(), 3);
+/// assert_eq!(layout.shard_range(2), 3..5);
+/// assert_eq!(layout.locate(4), (2, 1));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ShardLayout {
+    /// Global index of the first sample of each shard, followed by the total number of samples.
+    offsets: Arc<[usize]>,
+}
+
+impl ShardLayout {
+    /// Create the layout of shards of the given lengths.
+    pub fn from_lens<I: IntoIterator<Item = usize>>(lens: I) -> Self {
+        let mut total = 0;
+        let offsets = std::iter::once(0)
+            .chain(lens.into_iter().map(|len| {
+                total += len;
+                total
+            }))
+            .collect();
+        Self { offsets }
+    }
+
+    /// Return the number of shards.
+    #[must_use]
+    pub fn num_shards(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    /// Return the number of samples of a shard.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the shard is out of bounds.
+    #[must_use]
+    pub fn shard_len(&self, shard: usize) -> usize {
+        self.shard_range(shard).len()
+    }
+
+    /// Return the global indices of the samples of a shard.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the shard is out of bounds.
+    #[must_use]
+    pub fn shard_range(&self, shard: usize) -> Range<usize> {
+        self.offsets[shard]..self.offsets[shard + 1]
+    }
+
+    /// Map a global index to its shard and its index within the shard.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    #[must_use]
+    pub fn locate(&self, index: usize) -> (usize, usize) {
+        assert!(
+            index < self.len(),
+            "index {index} out of bounds for {} samples",
+            self.len()
+        );
+        // The last shard starting at or before the index, which skips the empty shards.
+        let shard = self.offsets.partition_point(|&offset| offset <= index) - 1;
+        (shard, index - self.offsets[shard])
+    }
+}
+
+impl Len for ShardLayout {
+    fn len(&self) -> usize {
+        self.offsets[self.offsets.len() - 1]
+    }
+}
+
+/// Sampler shuffling a sharded dataset shard by shard, so that only a few shards are read at a time.
+///
+/// The order of the shards is shuffled, then the indices are drawn randomly from a window of `window` shards: when
+/// all the indices of a shard have been drawn, the next shard enters the window. With a `window` of one (the
+/// default), the shards are read one after the other, each shuffled in place. Keeping `window` below the open-file
+/// limit of a [`ShardedDataset`](crate::indexable::ShardedDataset) avoids reopening the shards.
+///
+/// Created from the dataset layout with [`ShardShuffleSampler::from_layout`]. [`Sampler::new`] creates a single
+/// shard, which shuffles all the indices together.
+///
+/// ```
+/// use ai_dataloader::indexable::{DataLoader, ShardedDataset};
+/// use ai_dataloader::sampler::ShardShuffleSampler;
+///
+/// let dataset = ShardedDataset::new(vec![vec![0_i32; 100], vec![1; 50], vec![2; 80]]);
+/// let sampler = ShardShuffleSampler::from_layout(dataset.layout().clone()).window(2);
+/// let loader = DataLoader::builder(dataset).with_sampler(sampler).batch_size(10).seed(0).build();
+/// assert_eq!(loader.iter().map(|batch| batch.len()).sum::<usize>(), 230);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ShardShuffleSampler {
+    /// Layout of the shards of the dataset.
+    layout: ShardLayout,
+    /// Number of shards shuffled together.
+    window: usize,
+    /// Seed of the shuffle, drawn randomly if not set.
+    seed: Option<u64>,
+}
+
+impl ShardShuffleSampler {
+    /// Create a sampler shuffling the shards of the given layout.
+    #[must_use]
+    pub fn from_layout(layout: ShardLayout) -> Self {
+        Self {
+            layout,
+            window: 1,
+            seed: None,
+        }
+    }
+
+    /// Set the number of shards shuffled together. By default the shards are shuffled one by one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window` is zero.
+    #[must_use]
+    pub fn window(mut self, window: usize) -> Self {
+        assert!(window > 0, "window should be positive");
+        self.window = window;
+        self
+    }
+
+    /// Return the layout of the shards.
+    #[must_use]
+    pub fn layout(&self) -> &ShardLayout {
+        &self.layout
+    }
+}
+
+impl Sampler for ShardShuffleSampler {
+    fn new(data_source_len: usize) -> Self {
+        Self::from_layout(ShardLayout::from_lens([data_source_len]))
+    }
+    fn set_seed(&mut self, seed: u64) {
+        self.seed = Some(seed);
+    }
+    fn is_shuffling(&self) -> bool {
+        true
+    }
+}
+
+impl Len for ShardShuffleSampler {
+    fn len(&self) -> usize {
+        self.layout.len()
+    }
+}
+
+impl IntoIterator for ShardShuffleSampler {
+    type Item = usize;
+    type IntoIter = ShardShuffleSamplerIter;
+    fn into_iter(self) -> Self::IntoIter {
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let mut shards: Vec<usize> = (0..self.layout.num_shards()).collect();
+        shards.shuffle(&mut rng);
+
+        let mut iter = ShardShuffleSamplerIter {
+            remaining: self.layout.len(),
+            rng,
+            shards: shards.into_iter(),
+            window: Vec::new(),
+            active: Vec::with_capacity(self.window),
+            sampler: self,
+        };
+        for _ in 0..iter.sampler.window {
+            iter.push_next_shard();
+        }
+        iter
+    }
+}
+
+/// Iterator over the indices of a [`ShardShuffleSampler`].
+#[derive(Debug)]
+pub struct ShardShuffleSamplerIter {
+    /// The sampler configuration.
+    sampler: ShardShuffleSampler,
+    /// Generator drawing the indices from the window.
+    rng: StdRng,
+    /// Shards that didn't enter the window yet, in shuffled order.
+    shards: std::vec::IntoIter<usize>,
+    /// Indices of the shards of the window that haven't been drawn yet.
+    window: Vec<usize>,
+    /// Shards of the window with their number of indices left to draw.
+    active: Vec<(usize, usize)>,
+    /// Number of indices left to draw.
+    remaining: usize,
+}
+
+impl ShardShuffleSamplerIter {
+    /// Add the indices of the next non-empty shard to the window.
+    fn push_next_shard(&mut self) {
+        for shard in self.shards.by_ref() {
+            let range = self.sampler.layout.shard_range(shard);
+            if !range.is_empty() {
+                self.active.push((shard, range.len()));
+                self.window.extend(range);
+                return;
+            }
+        }
+    }
+}
+
+impl Iterator for ShardShuffleSamplerIter {
+    type Item = usize;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.window.is_empty() {
+            return None;
+        }
+        let index = self
+            .window
+            .swap_remove(self.rng.gen_range(0..self.window.len()));
+        self.remaining -= 1;
+        let (shard, _) = self.sampler.layout.locate(index);
+        let position = self
+            .active
+            .iter()
+            .position(|&(active, _)| active == shard)
+            .expect("the shards of the window are active");
+        self.active[position].1 -= 1;
+        // Slide the window once all the indices of a shard have been drawn.
+        if self.active[position].1 == 0 {
+            self.active.swap_remove(position);
+            self.push_next_shard();
+        }
+        Some(index)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for ShardShuffleSamplerIter {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seeded_indices(sampler: &ShardShuffleSampler) -> Vec<usize> {
+        let mut sampler = sampler.clone();
+        sampler.set_seed(0);
+        sampler.into_iter().collect()
+    }
+
+    #[test]
+    fn layout() {
+        let layout = ShardLayout::from_lens([2, 0, 0, 3, 1]);
+        assert_eq!(layout.len(), 6);
+        assert_eq!(layout.num_shards(), 5);
+        let located: Vec<_> = (0..6).map(|index| layout.locate(index)).collect();
+        assert_eq!(located, [(0, 0), (0, 1), (3, 0), (3, 1), (3, 2), (4, 0)]);
+        assert_eq!(layout.shard_len(1), 0);
+        assert_eq!(ShardLayout::from_lens([]).len(), 0);
+    }
+
+    #[test]
+    fn permutation() {
+        for lens in [vec![], vec![0], vec![7], vec![5, 0, 13, 1, 40]] {
+            for window in [1, 2, 10] {
+                let layout = ShardLayout::from_lens(lens.iter().copied());
+                let sampler = ShardShuffleSampler::from_layout(layout).window(window);
+                let mut indices = seeded_indices(&sampler);
+                let len = lens.iter().sum();
+                assert_eq!(indices.len(), len);
+                indices.sort_unstable();
+                assert_eq!(indices, (0..len).collect::<Vec<_>>());
+            }
+        }
+    }
+
+    #[test]
+    fn locality() {
+        let layout = ShardLayout::from_lens([10, 30, 5, 20, 15]);
+        let sampler = ShardShuffleSampler::from_layout(layout.clone());
+        let indices = seeded_indices(&sampler);
+        // Each shard is read entirely before the next one.
+        let mut shards: Vec<usize> = indices
+            .iter()
+            .map(|&index| layout.locate(index).0)
+            .collect();
+        shards.dedup();
+        assert_eq!(shards.len(), 5);
+
+        // At most two shards are read at a time.
+        let sampler = sampler.window(2);
+        let mut drawn = [0; 5];
+        for index in seeded_indices(&sampler) {
+            drawn[layout.locate(index).0] += 1;
+            let reading = (0..5)
+                .filter(|&shard| drawn[shard] > 0 && drawn[shard] < layout.shard_len(shard))
+                .count();
+            assert!(reading <= 2);
+        }
+    }
+
+    #[test]
+    fn len() {
+        let sampler = ShardShuffleSampler::new(25);
+        assert_eq!(sampler.len(), 25);
+        let mut iter = sampler.into_iter();
+        assert_eq!(iter.len(), 25);
+        let _ = iter.next();
+        assert_eq!(iter.len(), 24);
+    }
+}
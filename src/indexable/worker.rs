@@ -0,0 +1,419 @@
+//! Worker threads of the indexable `DataLoader`.
+
+use crate::{Dataset, GetSample, Len};
+use rand::rngs::StdRng;
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    fmt,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError, Weak,
+    },
+};
+
+#[cfg(feature = "rayon")]
+use crate::sampler::splitmix64;
+#[cfg(feature = "rayon")]
+use rayon::ThreadPool;
+
+thread_local! {
+    /// Information about the worker running on the current thread.
+    static WORKER_INFO: Cell<Option<WorkerInfo>> = const { Cell::new(None) };
+    /// Instances of the `PerWorker` datasets owned by the current thread, by id of the dataset.
+    static INSTANCES: RefCell<HashMap<usize, Instance>> = RefCell::new(HashMap::new());
+}
+
+/// Information about a worker thread of the indexable `DataLoader`, given to the function set with
+/// `Builder::worker_init_fn`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WorkerInfo {
+    /// Index of the worker, between zero and `num_workers`.
+    pub id: usize,
+    /// Number of workers of the dataloader.
+    pub num_workers: usize,
+    /// Seed of the worker, derived from the seed of the dataloader and the worker id, or drawn randomly if the
+    /// dataloader isn't seeded.
+    pub seed: u64,
+}
+
+#[cfg(feature = "rayon")]
+impl WorkerInfo {
+    pub(crate) fn new(id: usize, num_workers: usize, seed: u64) -> Self {
+        Self {
+            id,
+            num_workers,
+            seed: splitmix64(splitmix64(seed) ^ id as u64),
+        }
+    }
+
+    /// Record the worker running on the current thread.
+    pub(crate) fn set_current(self) {
+        WORKER_INFO.with(|info| info.set(Some(self)));
+    }
+}
+
+/// Return the information about the worker running on the current thread, or `None` if the current thread isn't a
+/// worker of a thread pool created by an indexable `DataLoader`.
+///
+/// It can be used in `get_sample` to set up resources per worker.
+pub fn worker_info() -> Option<WorkerInfo> {
+    WORKER_INFO.with(Cell::get)
+}
+
+/// Function called at the start of each worker thread.
+#[cfg(feature = "rayon")]
+#[derive(Clone)]
+pub(crate) struct WorkerInit(pub(crate) Arc<dyn Fn(&WorkerInfo) + Send + Sync>);
+
+#[cfg(feature = "rayon")]
+impl fmt::Debug for WorkerInit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WorkerInit").finish_non_exhaustive()
+    }
+}
+
+/// Thread pool of an indexable `DataLoader`, created the first time it's used so the loaders never fetching
+/// samples on it, like the ones of async datasets, don't spawn any thread.
+#[cfg(feature = "rayon")]
+pub(crate) struct WorkerPool {
+    /// Number of threads of the pool.
+    num_threads: usize,
+    /// Seed of the loader, from which the seed of each worker is derived.
+    seed: u64,
+    /// Called at the start of each worker thread.
+    worker_init: Option<WorkerInit>,
+    /// The pool, once created or if provided by the user.
+    pool: Mutex<Option<Arc<ThreadPool>>>,
+}
+
+#[cfg(feature = "rayon")]
+impl WorkerPool {
+    pub(crate) fn new(num_threads: usize, seed: u64, worker_init: Option<WorkerInit>) -> Self {
+        Self {
+            num_threads,
+            seed,
+            worker_init,
+            pool: Mutex::new(None),
+        }
+    }
+
+    /// Use a thread pool provided by the user.
+    pub(crate) fn with_pool(pool: Arc<ThreadPool>) -> Self {
+        Self {
+            num_threads: pool.current_num_threads(),
+            seed: 0,
+            worker_init: None,
+            pool: Mutex::new(Some(pool)),
+        }
+    }
+
+    /// Return the thread pool, creating it on the first call.
+    pub(crate) fn get(&self) -> Arc<ThreadPool> {
+        let mut pool = lock(&self.pool);
+        let pool = pool.get_or_insert_with(|| {
+            let (num_workers, seed) = (self.num_threads, self.seed);
+            let worker_init = self.worker_init.clone();
+            Arc::new(
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(num_workers)
+                    .start_handler(move |id| {
+                        let info = WorkerInfo::new(id, num_workers, seed);
+                        info.set_current();
+                        if let Some(worker_init) = &worker_init {
+                            (worker_init.0)(&info);
+                        }
+                    })
+                    .build()
+                    .expect("could not spawn threads"),
+            )
+        });
+        Arc::clone(pool)
+    }
+
+    /// Return `true` if the thread pool was created or provided.
+    pub(crate) fn is_created(&self) -> bool {
+        lock(&self.pool).is_some()
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl fmt::Debug for WorkerPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WorkerPool")
+            .field("num_threads", &self.num_threads)
+            .field("created", &self.is_created())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Create an instance of the dataset for a worker.
+type Factory<D> = Box<dyn Fn(Option<&WorkerInfo>) -> D + Send + Sync>;
+
+/// Id of the next `PerWorker` dataset.
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Instance of a `PerWorker` dataset, owned by a thread.
+struct Instance {
+    /// Dead once the dataset is dropped.
+    alive: Weak<()>,
+    dataset: Rc<dyn Any>,
+}
+
+/// Dataset wrapper giving each worker thread its own instance of a dataset, for datasets holding a resource that
+/// can't be shared between threads, like a file handle or a decoder context.
+///
+/// The instance of a thread is created the first time the thread fetches a sample, on the thread itself, by a factory
+/// receiving the [`WorkerInfo`] of the thread (`None` outside of the workers of a dataloader), or by cloning a
+/// template dataset. The instances are stored by their own thread and never leave it, so the dataset needs to be
+/// neither `Send` nor `Sync`. They are dropped when their thread exits, or when the thread creates another instance
+/// after the `PerWorker` was dropped.
+///
+/// ```
+/// use ai_dataloader::indexable::{DataLoader, PerWorker};
+/// use ai_dataloader::{Dataset, GetSample, Len};
+/// use std::cell::RefCell;
+///
+/// /// Reuses a buffer between samples, so it isn't `Sync`.
+/// struct Decoder {
+///     buffer: RefCell<Vec<u8>>,
+/// }
+///
+/// impl Len for Decoder {
+///     fn len(&self) -> usize {
+///         16
+///     }
+/// }
+/// impl GetSample for Decoder {
+///     type Sample = usize;
+///     fn get_sample(&self, index: usize) -> usize {
+///         let mut buffer = self.buffer.borrow_mut();
+///         buffer.clear();
+///         buffer.resize(index, 0);
+///         buffer.len()
+///     }
+/// }
+/// impl Dataset for Decoder {}
+///
+/// let dataset = PerWorker::new(16, |_worker| Decoder { buffer: RefCell::new(Vec::new()) });
+/// let loader = DataLoader::builder(dataset).batch_size(4).build();
+/// assert_eq!(loader.iter().map(|batch| batch.sum()).sum::<usize>(), 120);
+/// ```
+pub struct PerWorker<D> {
+    factory: Factory<D>,
+    /// Length of the dataset.
+    len: usize,
+    /// Key of the instances in the thread-local storage of their thread.
+    id: usize,
+    /// Only referenced weakly by the instances, which are left behind once it's dropped.
+    alive: Arc<()>,
+    /// Number of instances created.
+    num_instances: AtomicUsize,
+}
+
+impl<D: 'static> PerWorker<D> {
+    /// Create the instances of the dataset with a factory, receiving the information about the worker.
+    ///
+    /// `len` is the length of the dataset, that all the instances must have.
+    pub fn new<F>(len: usize, factory: F) -> Self
+    where
+        F: Fn(Option<&WorkerInfo>) -> D + Send + Sync + 'static,
+    {
+        Self {
+            factory: Box::new(factory),
+            len,
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            alive: Arc::new(()),
+            num_instances: AtomicUsize::new(0),
+        }
+    }
+
+    /// Create the instances of the dataset by cloning `dataset`.
+    pub fn cloned(dataset: D) -> Self
+    where
+        D: Len + Clone + Send,
+    {
+        let len = dataset.len();
+        let template = Mutex::new(dataset);
+        Self::new(len, move |_| lock(&template).clone())
+    }
+
+    /// Return the number of instances created.
+    pub fn num_instances(&self) -> usize {
+        self.num_instances.load(Ordering::Relaxed)
+    }
+
+    /// Call `f` on the instance of the current thread, creating it if needed.
+    ///
+    /// The instances aren't borrowed while `f` runs, so it can fetch from the dataset again, for example when the
+    /// thread steals the fetching of another sample while waiting in `get_sample`.
+    fn with_instance<T>(&self, f: impl FnOnce(&D) -> T) -> T {
+        let instance = INSTANCES.with(|instances| {
+            let instances = instances.borrow();
+            instances
+                .get(&self.id)
+                .map(|instance| Rc::clone(&instance.dataset))
+        });
+        let instance = instance.unwrap_or_else(|| {
+            let instance: Rc<dyn Any> = Rc::new((self.factory)(worker_info().as_ref()));
+            self.num_instances.fetch_add(1, Ordering::Relaxed);
+            let owned = Instance {
+                alive: Arc::downgrade(&self.alive),
+                dataset: Rc::clone(&instance),
+            };
+            // The instances of the dropped datasets are dropped after releasing the borrow, in case they use
+            // another `PerWorker` dataset.
+            let _dropped = INSTANCES.with(|instances| {
+                let mut instances = instances.borrow_mut();
+                let dropped: Vec<_> = instances
+                    .iter()
+                    .filter(|(_, instance)| instance.alive.strong_count() == 0)
+                    .map(|(&id, _)| id)
+                    .collect();
+                let dropped: Vec<_> = dropped
+                    .iter()
+                    .filter_map(|id| instances.remove(id))
+                    .collect();
+                instances.insert(self.id, owned);
+                dropped
+            });
+            instance
+        });
+        f(instance
+            .downcast_ref()
+            .expect("the instance has the type of the dataset"))
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // The template of `PerWorker::cloned` is only cloned, and the thread pool is only set once.
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl<D> Drop for PerWorker<D> {
+    fn drop(&mut self) {
+        // The instances of the other threads can only be dropped by their own thread.
+        let instance = INSTANCES
+            .try_with(|instances| instances.borrow_mut().remove(&self.id))
+            .ok()
+            .flatten();
+        drop(instance);
+    }
+}
+
+impl<D> fmt::Debug for PerWorker<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PerWorker")
+            .field("len", &self.len)
+            .field("num_instances", &self.num_instances.load(Ordering::Relaxed))
+            .finish_non_exhaustive()
+    }
+}
+
+impl<D> Len for PerWorker<D> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<D: GetSample + 'static> GetSample for PerWorker<D> {
+    type Sample = D::Sample;
+    fn get_sample(&self, index: usize) -> Self::Sample {
+        self.with_instance(|instance| instance.get_sample(index))
+    }
+    fn get_sample_with_rng(&self, index: usize, rng: &mut StdRng) -> Self::Sample {
+        self.with_instance(|instance| instance.get_sample_with_rng(index, rng))
+    }
+}
+
+impl<D: GetSample + 'static> Dataset for PerWorker<D> {}
+
+#[cfg(all(test, feature = "rayon"))]
+mod tests {
+    use super::*;
+    use crate::indexable::DataLoader;
+    use std::{cell::RefCell, collections::HashSet};
+
+    /// Record the worker of each sample, with a counter that can't be shared between threads.
+    #[derive(Debug, Clone)]
+    struct Local {
+        worker: Option<WorkerInfo>,
+        fetched: RefCell<usize>,
+    }
+
+    impl Len for Local {
+        fn len(&self) -> usize {
+            64
+        }
+    }
+    impl GetSample for Local {
+        type Sample = (usize, u64, usize);
+        fn get_sample(&self, _index: usize) -> Self::Sample {
+            *self.fetched.borrow_mut() += 1;
+            let worker = self.worker.expect("created by a worker");
+            (worker.id, worker.seed, worker.num_workers)
+        }
+    }
+    impl Dataset for Local {}
+
+    #[test]
+    fn per_worker_instances() {
+        let initialized = Arc::new(Mutex::new(Vec::new()));
+        let init = Arc::clone(&initialized);
+        let dataset = PerWorker::new(64, |worker| Local {
+            worker: worker.copied(),
+            fetched: RefCell::new(0),
+        });
+        let loader = DataLoader::builder(dataset)
+            .batch_size(8)
+            .num_threads(3)
+            .seed(42)
+            .collate_fn(|batch| batch)
+            .worker_init_fn(move |worker| init.lock().unwrap().push(*worker))
+            .build();
+
+        let samples: Vec<_> = loader.iter().flatten().collect();
+        assert_eq!(samples.len(), 64);
+        let workers: HashSet<_> = samples.iter().map(|&(id, _, _)| id).collect();
+        assert!(workers.iter().all(|&id| id < 3));
+        assert!(samples.iter().all(|&(_, _, num_workers)| num_workers == 3));
+
+        let mut initialized = initialized.lock().unwrap().clone();
+        initialized.sort_by_key(|worker| worker.id);
+        assert_eq!(
+            initialized
+                .iter()
+                .map(|worker| worker.id)
+                .collect::<Vec<_>>(),
+            [0, 1, 2]
+        );
+        // Each worker has its own seed, derived from the seed of the loader.
+        let seeds: HashSet<_> = initialized.iter().map(|worker| worker.seed).collect();
+        assert_eq!(seeds.len(), 3);
+        for &(id, seed, _) in &samples {
+            assert_eq!(seed, initialized[id].seed);
+        }
+        assert_eq!(initialized[1], WorkerInfo::new(1, 3, 42));
+        assert!(worker_info().is_none());
+    }
+
+    #[test]
+    fn reentrant_instances() {
+        let dataset = PerWorker::new(64, |worker| Local {
+            worker: worker.copied(),
+            fetched: RefCell::new(0),
+        });
+        // Fetching again while fetching, as a thread stealing work would, reuses the instance.
+        dataset.with_instance(|outer| {
+            dataset.with_instance(|inner| assert!(std::ptr::eq(outer, inner)));
+        });
+        assert_eq!(dataset.num_instances(), 1);
+
+        let id = dataset.id;
+        assert!(INSTANCES.with(|instances| instances.borrow().contains_key(&id)));
+        drop(dataset);
+        assert!(!INSTANCES.with(|instances| instances.borrow().contains_key(&id)));
+    }
+}
@@ -2,3 +2,9 @@
 
 mod dataloader;
 pub use dataloader::DataLoader;
+
+#[cfg(feature = "webdataset")]
+mod webdataset;
+#[cfg(feature = "webdataset")]
+#[cfg_attr(docsrs, doc(cfg(feature = "webdataset")))]
+pub use webdataset::{TarSample, TarShardDataset, TarShardIter};
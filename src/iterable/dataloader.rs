@@ -7,7 +7,10 @@ mod builder;
 use builder::Builder;
 use rand::{seq::SliceRandom, thread_rng};
 
-use crate::collate::{Collate, DefaultCollate};
+use crate::{
+    collate::{Collate, DefaultCollate},
+    Loader,
+};
 
 /// For iterable dataset, the `datalaoder` will yield until the underlying iterator is `None`.
 /// As the iteration over the dataset can be done multiple time, depending if the underlying dataset iterator consume the dataset or not.
@@ -24,6 +27,8 @@ pub struct DataLoader<D, C> {
     collate_fn: C,
     /// If `true` the sample in the batch will be shuffled
     shuffle: bool,
+    /// Current epoch, only kept for the [`Loader`] implementation.
+    epoch: u64,
 }
 
 impl<D> DataLoader<D, DefaultCollate>
@@ -152,7 +157,7 @@ where
 {
     /// Iterate over the dataloader without consuming the underlying dataset.
     /// As it make no sens to collate reference into a tensor, by default element are copied.
-    pub fn iter(&'dataset self) -> Iter<'_, <&'dataset D as IntoIterator>::IntoIter, C> {
+    pub fn iter(&'dataset self) -> Iter<'dataset, <&'dataset D as IntoIterator>::IntoIter, C> {
         Iter {
             batch_size: self.batch_size,
             dataset_iter: self.dataset.into_iter(),
@@ -163,7 +168,51 @@ where
     }
 }
 
-impl<'dataset, D, C> Iterator for Iter<'dataset, D, C>
+impl<D, C> Loader for DataLoader<D, C>
+where
+    for<'dataset> &'dataset D: IntoIterator,
+    C: for<'dataset> Collate<<&'dataset D as IntoIterator>::Item>,
+{
+    type Batch<'loader>
+        = <C as Collate<<&'loader D as IntoIterator>::Item>>::Output
+    where
+        Self: 'loader;
+    type Iter<'loader>
+        = Iter<'loader, <&'loader D as IntoIterator>::IntoIter, C>
+    where
+        Self: 'loader;
+
+    /// Skipping batches consumes the samples of the dataset iterator, without collating them.
+    fn iter_from(&self, step: usize) -> Self::Iter<'_> {
+        let mut iter = self.iter();
+        iter.dataset_iter
+            .by_ref()
+            .take(step.saturating_mul(self.batch_size))
+            .for_each(drop);
+        iter
+    }
+    /// Return the number of batches if the dataset iterator knows its exact length.
+    fn num_batches(&self) -> Option<usize> {
+        match self.dataset.into_iter().size_hint() {
+            (lower, Some(upper)) if lower == upper => Some(if self.drop_last {
+                lower / self.batch_size
+            } else {
+                (lower + self.batch_size - 1) / self.batch_size
+            }),
+            _ => None,
+        }
+    }
+    fn epoch(&self) -> u64 {
+        self.epoch
+    }
+    /// The epoch has no effect on the iteration: the dataset is iterated the same way and the batches are shuffled
+    /// with a new random generator each time.
+    fn set_epoch(&mut self, epoch: u64) {
+        self.epoch = epoch;
+    }
+}
+
+impl<D, C> Iterator for Iter<'_, D, C>
 where
     D: Iterator,
     C: Collate<D::Item>,
@@ -194,7 +243,7 @@ where
 mod tests {
     use super::*;
 
-    use crate::collate::NoOpCollate;
+    use crate::{collate::NoOpCollate, LoaderState};
     use ndarray::array;
 
     #[test]
@@ -281,6 +330,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn loader() {
+        let mut loader = DataLoader::builder(vec![0, 1, 2, 3, 4, 5, 6])
+            .batch_size(2)
+            .build();
+        assert_eq!(loader.num_batches(), Some(4));
+        let batches: Vec<_> = Loader::iter(&loader).collect();
+        assert_eq!(batches.len(), 4);
+
+        let mut iter = loader.restore(LoaderState { epoch: 3, step: 2 });
+        assert_eq!(iter.next(), Some(array![4, 5]));
+        assert_eq!(iter.next(), Some(array![6]));
+        assert_eq!(iter.next(), None);
+        assert_eq!(loader.epoch(), 3);
+        assert_eq!(loader.state(1), LoaderState { epoch: 3, step: 1 });
+
+        let loader = DataLoader::builder(vec![0, 1, 2, 3, 4, 5, 6])
+            .batch_size(2)
+            .drop_last()
+            .build();
+        assert_eq!(loader.num_batches(), Some(3));
+        let odd = std::collections::HashSet::from([1, 3, 5]);
+        let loader = DataLoader::builder(odd).batch_size(2).build();
+        assert_eq!(loader.num_batches(), Some(2));
+    }
+
     #[test]
     fn len() {
         let dataset = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
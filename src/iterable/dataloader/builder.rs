@@ -85,6 +85,7 @@ where
             drop_last: self.drop_last,
             collate_fn: self.collate_fn,
             shuffle: self.shuffle,
+            epoch: 0,
         }
     }
 }
@@ -0,0 +1,423 @@
+//! Streaming of [WebDataset](https://github.com/webdataset/webdataset) tar shards.
+
+use flate2::bufread::MultiGzDecoder;
+use rand::{seq::SliceRandom, thread_rng};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    fs::File,
+    io::{self, BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver},
+        Arc,
+    },
+    thread,
+};
+use tar::Archive;
+
+/// A sample of a tar shard, mapping each extension of its files to their content.
+///
+/// The key shared by the files is also available under `"__key__"`.
+pub type TarSample = HashMap<String, Vec<u8>>;
+
+/// Function applied to the content of the files with a given extension.
+type Decoder = Arc<dyn Fn(Vec<u8>) -> Vec<u8> + Send + Sync>;
+
+/// Iterable dataset streaming samples from [WebDataset](https://github.com/webdataset/webdataset) tar shards.
+///
+/// In a shard, consecutive files sharing the same key (their path up to the first dot of the file name) form a
+/// sample. For instance `images/0001.jpg` and `images/0001.cls` are gathered into a [`TarSample`] containing the
+/// `"jpg"` and `"cls"` extensions. The shards are read sequentially, one after the other, without being unpacked.
+///
+/// Gzip compressed shards (`.tar.gz`) are detected and decompressed on the fly. Each shard is read by a
+/// background thread, one file ahead of the samples being yielded.
+///
+/// As files don't have the same size, the samples are usually collated with a custom collate function,
+/// the [`DefaultCollate`](crate::collate::DefaultCollate) requiring files with the same length.
+///
+/// ```no_run
+/// use ai_dataloader::{collate::NoOpCollate, iterable::{DataLoader, TarShardDataset}};
+///
+/// let dataset = TarShardDataset::new(["shard-000.tar", "shard-001.tar.gz"])
+///     .shuffle_shards()
+///     .decoder("cls", |bytes| String::from_utf8_lossy(&bytes).trim().as_bytes().to_vec());
+/// let loader = DataLoader::builder(dataset)
+///     .batch_size(32)
+///     .collate_fn(NoOpCollate)
+///     .build();
+///
+/// for batch in &loader {
+///     println!("{} samples, first label {:?}", batch.len(), batch[0]["cls"]);
+/// }
+/// ```
+#[derive(Clone)]
+pub struct TarShardDataset {
+    /// Paths of the shards.
+    shards: Vec<PathBuf>,
+    /// If `true` the order of the shards is shuffled at each iteration.
+    shuffle_shards: bool,
+    /// Decoding hook for each extension.
+    decoders: HashMap<String, Decoder>,
+}
+
+impl TarShardDataset {
+    /// Create a dataset from a list of local shards.
+    pub fn new<I, P>(shards: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        Self {
+            shards: shards.into_iter().map(Into::into).collect(),
+            shuffle_shards: false,
+            decoders: HashMap::new(),
+        }
+    }
+
+    /// Shuffle the order of the shards at each iteration.
+    ///
+    /// The samples inside of a shard are still read in order, which can be combined with the shuffling
+    /// of the iterable `DataLoader` for more randomness.
+    #[must_use]
+    pub fn shuffle_shards(mut self) -> Self {
+        self.shuffle_shards = true;
+        self
+    }
+
+    /// Register a decoding hook, applied to the content of the files with the given extension.
+    ///
+    /// The extension is given without the leading dot, for instance `"jpg"` or `"seg.png"`.
+    #[must_use]
+    pub fn decoder<F>(mut self, extension: &str, decoder: F) -> Self
+    where
+        F: Fn(Vec<u8>) -> Vec<u8> + Send + Sync + 'static,
+    {
+        self.decoders
+            .insert(String::from(extension), Arc::new(decoder));
+        self
+    }
+
+    /// Return the paths of the shards.
+    #[must_use]
+    pub fn shards(&self) -> &[PathBuf] {
+        &self.shards
+    }
+
+    /// Iterate over the samples of the shards.
+    #[must_use]
+    pub fn iter(&self) -> TarShardIter {
+        let mut shards = self.shards.clone();
+        if self.shuffle_shards {
+            shards.shuffle(&mut thread_rng());
+        }
+        TarShardIter {
+            shards: shards.into(),
+            decoders: self.decoders.clone(),
+            reader: None,
+            sample: None,
+        }
+    }
+}
+
+impl fmt::Debug for TarShardDataset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TarShardDataset")
+            .field("shards", &self.shards)
+            .field("shuffle_shards", &self.shuffle_shards)
+            .field("decoders", &self.decoders.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl IntoIterator for &TarShardDataset {
+    type Item = TarSample;
+    type IntoIter = TarShardIter;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for TarShardDataset {
+    type Item = TarSample;
+    type IntoIter = TarShardIter;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the samples of tar shards.
+///
+/// It panics if a shard can't be read or isn't a valid tar archive.
+pub struct TarShardIter {
+    /// Shards not yet opened.
+    shards: VecDeque<PathBuf>,
+    /// Decoding hook for each extension.
+    decoders: HashMap<String, Decoder>,
+    /// The shard currently read.
+    reader: Option<ShardReader>,
+    /// The sample currently gathered, with its key.
+    sample: Option<(String, TarSample)>,
+}
+
+impl fmt::Debug for TarShardIter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TarShardIter")
+            .field("shards", &self.shards)
+            .field("current_shard", &self.reader.as_ref().map(|r| &r.path))
+            .finish_non_exhaustive()
+    }
+}
+
+impl Iterator for TarShardIter {
+    type Item = TarSample;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.reader.is_none() {
+                let path = self.shards.pop_front()?;
+                let reader = ShardReader::open(&path)
+                    .unwrap_or_else(|err| panic!("could not open `{}`: {err}", path.display()));
+                self.reader = Some(reader);
+            }
+            let reader = self.reader.as_mut().expect("a shard is opened");
+            let entry = reader
+                .next_file()
+                .unwrap_or_else(|err| panic!("could not read `{}`: {err}", reader.path.display()));
+
+            let Some((path, content)) = entry else {
+                // Samples never span several shards.
+                self.reader = None;
+                match self.sample.take() {
+                    Some((_, sample)) => return Some(sample),
+                    None => continue,
+                }
+            };
+
+            let (key, extension) = split_key(&path);
+            let content = match self.decoders.get(extension) {
+                Some(decoder) => decoder(content),
+                None => content,
+            };
+            match &mut self.sample {
+                Some((current_key, sample)) if current_key == key => {
+                    sample.insert(String::from(extension), content);
+                }
+                _ => {
+                    let mut sample = TarSample::new();
+                    sample.insert(String::from("__key__"), key.as_bytes().to_vec());
+                    sample.insert(String::from(extension), content);
+                    if let Some((_, finished)) = self.sample.replace((String::from(key), sample)) {
+                        return Some(finished);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Split a path in a sample key and an extension, at the first dot of the file name.
+fn split_key(path: &str) -> (&str, &str) {
+    let file_name_start = path.rfind('/').map_or(0, |i| i + 1);
+    match path[file_name_start..].find('.') {
+        Some(dot) => (
+            &path[..file_name_start + dot],
+            &path[file_name_start + dot + 1..],
+        ),
+        None => (path, ""),
+    }
+}
+
+/// Path and content of a regular file of a shard.
+type ShardFile = (String, Vec<u8>);
+
+/// Sequential reader of the regular files of a tar archive.
+///
+/// The [`tar::Entries`] iterator borrows its archive, so the archive is walked by a dedicated thread sending the
+/// files through a bounded channel.
+struct ShardReader {
+    /// Path of the shard, used in error messages.
+    path: PathBuf,
+    /// Files read by the thread, the channel being closed at the end of the archive.
+    files: Receiver<io::Result<ShardFile>>,
+}
+
+impl ShardReader {
+    /// Open a shard, decompressing it if it starts with the gzip magic number.
+    fn open(path: &Path) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let reader: Box<dyn Read + Send> = if reader.fill_buf()?.starts_with(&[0x1f, 0x8b]) {
+            Box::new(MultiGzDecoder::new(reader))
+        } else {
+            Box::new(reader)
+        };
+        let (sender, files) = mpsc::sync_channel(1);
+        thread::spawn(move || {
+            let mut archive = Archive::new(reader);
+            let result = read_files(&mut archive, |file| sender.send(Ok(file)).is_ok());
+            if let Err(err) = result {
+                // The iterator may already be dropped.
+                let _ = sender.send(Err(err));
+            }
+        });
+        Ok(Self {
+            path: path.to_path_buf(),
+            files,
+        })
+    }
+
+    /// Return the path and the content of the next regular file, or `None` at the end of the archive.
+    fn next_file(&mut self) -> io::Result<Option<ShardFile>> {
+        match self.files.recv() {
+            Ok(file) => file.map(Some),
+            Err(mpsc::RecvError) => Ok(None),
+        }
+    }
+}
+
+/// Read the regular files of an archive, passing them to `send` until it returns `false`.
+fn read_files<R: Read>(
+    archive: &mut Archive<R>,
+    mut send: impl FnMut(ShardFile) -> bool,
+) -> io::Result<()> {
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        // Directories, links and other special entries aren't part of any sample.
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = String::from_utf8_lossy(&entry.path_bytes()).into_owned();
+        // The size comes from the header, it isn't trusted for the allocation.
+        let size = entry.size();
+        let mut content = Vec::new();
+        entry.read_to_end(&mut content)?;
+        if content.len() as u64 != size {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("`{path}` is truncated"),
+            ));
+        }
+        if !send((path, content)) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{collate::NoOpCollate, iterable::DataLoader};
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write;
+    use tar::Header;
+
+    /// Write a shard containing the given files.
+    #[allow(clippy::fn_params_excessive_bools)]
+    fn write_shard(path: &Path, files: &[(&str, &[u8])], gzip: bool) {
+        let file = File::create(path).unwrap();
+        let writer: Box<dyn Write> = if gzip {
+            Box::new(GzEncoder::new(file, Compression::default()))
+        } else {
+            Box::new(file)
+        };
+        let mut builder = tar::Builder::new(writer);
+        for (path, content) in files {
+            let mut header = Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, path, *content).unwrap();
+        }
+        builder.into_inner().unwrap().flush().unwrap();
+    }
+
+    fn shards(dir: &Path) -> Vec<PathBuf> {
+        let first = dir.join("shard-000.tar");
+        write_shard(
+            &first,
+            &[
+                ("images/0.jpg", b"jpeg 0"),
+                ("images/0.cls", b"3"),
+                ("images/1.jpg", b"jpeg 1"),
+                ("images/1.cls", b"7"),
+            ],
+            false,
+        );
+        let second = dir.join("shard-001.tar.gz");
+        let long_name = format!("{}/2.seg.png", "nested".repeat(30));
+        write_shard(
+            &second,
+            &[
+                (long_name.as_str(), b"png 2"),
+                (&long_name.replace("seg.png", "cls"), b"1"),
+            ],
+            true,
+        );
+        vec![first, second]
+    }
+
+    #[test]
+    fn split() {
+        assert_eq!(split_key("a/b.c/0001.seg.png"), ("a/b.c/0001", "seg.png"));
+        assert_eq!(split_key("0001.jpg"), ("0001", "jpg"));
+        assert_eq!(split_key("README"), ("README", ""));
+    }
+
+    #[test]
+    fn group_by_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let dataset = TarShardDataset::new(shards(dir.path()));
+        let samples: Vec<_> = dataset.iter().collect();
+        assert_eq!(samples.len(), 3);
+        assert_eq!(samples[0]["__key__"], b"images/0");
+        assert_eq!(samples[0]["jpg"], b"jpeg 0");
+        assert_eq!(samples[1]["cls"], b"7");
+        // Gzip and long names.
+        assert_eq!(samples[2]["seg.png"], b"png 2");
+        assert_eq!(samples[2]["cls"], b"1");
+        assert!(samples[2]["__key__"].starts_with(b"nestednested"));
+    }
+
+    #[test]
+    #[should_panic(expected = "could not read")]
+    fn corrupted_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("corrupted.tar");
+        let mut header = Header::new_gnu();
+        header.set_path("0.jpg").unwrap();
+        header.set_size(1 << 60);
+        header.set_cksum();
+        let mut shard = header.as_bytes().to_vec();
+        shard.extend_from_slice(b"jpeg 0");
+        std::fs::write(&path, shard).unwrap();
+
+        let _ = TarShardDataset::new([path]).iter().count();
+    }
+
+    #[test]
+    fn decoder_and_shuffle() {
+        let dir = tempfile::tempdir().unwrap();
+        let dataset = TarShardDataset::new(shards(dir.path()))
+            .shuffle_shards()
+            .decoder("cls", |bytes| bytes.repeat(2));
+        let mut classes: Vec<_> = dataset.iter().map(|sample| sample["cls"].clone()).collect();
+        classes.sort();
+        assert_eq!(classes, [b"11", b"33", b"77"]);
+    }
+
+    #[test]
+    fn loader() {
+        let dir = tempfile::tempdir().unwrap();
+        let dataset = TarShardDataset::new(shards(dir.path()));
+        let loader = DataLoader::builder(dataset)
+            .batch_size(2)
+            .collate_fn(NoOpCollate)
+            .build();
+        for _ in 0..2 {
+            let batches: Vec<_> = loader.iter().collect();
+            assert_eq!(batches.len(), 2);
+            assert_eq!(batches[0].len(), 2);
+            assert_eq!(batches[1][0]["cls"], b"1");
+        }
+    }
+}
@@ -13,6 +13,8 @@
 //! - Integration with [`ndarray`] and [`tch-rs`], CPU and GPU support.
 //! - Default collate function that will automatically collate most of your type (supporting nesting).
 //! - Shuffling for iterable and indexable `DataLoader`.
+//! - A [`Loader`] trait implemented by both `DataLoader`, to write training loops generic over the dataset style.
+//! - Composable and reproducible [`transforms`] for data augmentation.
 //!
 //! ## Examples
 //!
@@ -28,6 +30,7 @@
 //! `DataLoader(dataset, batch_size=2)` | `DataLoader::builder(dataset).batch_size(2).build()` | Setup the batch size
 //! `DataLoader(dataset, shuffle=True)` | `DataLoader::builder(dataset).shuffle().build()` | Shuffle the data
 //! `DataLoader(dataset, sampler=CustomSampler)` | `DataLoader::builder(dataset).sampler::<CustomSampler>().build()` | Provide a custom sampler
+//! `DataLoader(dataset, generator=torch.Generator().manual_seed(0))` | `DataLoader::builder(dataset).seed(0).build()` | Seed the random transforms
 //!
 //! ### Combined options
 //!
@@ -71,14 +74,21 @@
 pub mod collate;
 pub mod indexable;
 pub mod iterable;
+mod loader;
+#[cfg(feature = "stats")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stats")))]
+pub mod stats;
+pub mod transforms;
+pub mod validation;
 
-pub use indexable::{sampler, Dataset, GetSample, Len, NdarrayDataset};
-
-#[cfg(feature = "rayon")]
-use once_cell::sync::OnceCell;
-#[cfg(feature = "rayon")]
-use rayon::ThreadPool;
-
-/// Thread pool used by the dataloader.
-#[cfg(feature = "rayon")]
-pub static THREAD_POOL: OnceCell<ThreadPool> = OnceCell::new();
+#[cfg(feature = "parquet")]
+#[cfg_attr(docsrs, doc(cfg(feature = "parquet")))]
+pub use indexable::ParquetDataset;
+pub use indexable::{sampler, Dataset, GetSample, GetSampleRef, Len, NdarrayDataset};
+#[cfg(feature = "tfrecord")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tfrecord")))]
+pub use indexable::{Example, Feature, TfRecordDataset, TfRecordIter};
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+pub use indexable::{JsonLines, JsonLinesDataset};
+pub use loader::{Loader, LoaderState};
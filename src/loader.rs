@@ -0,0 +1,95 @@
+//! # `Loader` trait
+//!
+//! Common interface of the indexable and the iterable `DataLoader`, to write training loops generic over the style of
+//! the dataset.
+
+/// Position of a [`Loader`] in the training, saved in a checkpoint to resume the iteration where it stopped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LoaderState {
+    /// The current epoch.
+    pub epoch: u64,
+    /// Number of batches already yielded in the current epoch.
+    pub step: usize,
+}
+
+/// A dataloader, yielding batches of samples epoch after epoch.
+///
+/// Implemented by both the [`indexable::DataLoader`](crate::indexable::DataLoader) and the
+/// [`iterable::DataLoader`](crate::iterable::DataLoader).
+///
+/// ```
+/// use ai_dataloader::{indexable, iterable, Loader, LoaderState};
+///
+/// fn train<L: Loader>(loader: &mut L, state: LoaderState, epochs: u64) -> usize {
+///     let mut batches = 0;
+///     for batch in loader.restore(state) {
+///         batches += 1;
+///     }
+///     for epoch in state.epoch + 1..epochs {
+///         loader.set_epoch(epoch);
+///         for batch in loader.iter() {
+///             batches += 1;
+///         }
+///     }
+///     batches
+/// }
+///
+/// let resume = LoaderState { epoch: 0, step: 1 };
+/// let mut loader = indexable::DataLoader::builder(vec![1, 2, 3, 4]).batch_size(2).build();
+/// assert_eq!(train(&mut loader, resume, 2), 3);
+/// let mut loader = iterable::DataLoader::builder(vec![1, 2, 3, 4]).batch_size(2).build();
+/// assert_eq!(train(&mut loader, resume, 2), 3);
+/// ```
+pub trait Loader {
+    /// Batch yielded by the loader, which may borrow from it.
+    type Batch<'loader>
+    where
+        Self: 'loader;
+    /// Iterator over the batches of an epoch.
+    type Iter<'loader>: Iterator<Item = Self::Batch<'loader>>
+    where
+        Self: 'loader;
+
+    /// Return an iterator over the batches of the current epoch, skipping the first `step` batches.
+    ///
+    /// The indexable `DataLoader` counts the batches drawn from its sampler, including the ones left out by the
+    /// `skip_failed_samples` method of its iterator.
+    ///
+    /// # Panics
+    ///
+    /// The indexable `DataLoader` panics if `step` isn't zero and it yields the batches out of order, or shuffles the
+    /// samples without a seed.
+    fn iter_from(&self, step: usize) -> Self::Iter<'_>;
+
+    /// Return an iterator over the batches of the current epoch.
+    fn iter(&self) -> Self::Iter<'_> {
+        self.iter_from(0)
+    }
+
+    /// Return the number of batches in an epoch, if it's known.
+    ///
+    /// Named differently from [`Len::len`](crate::Len::len), which the indexable `DataLoader` also implements.
+    fn num_batches(&self) -> Option<usize> {
+        None
+    }
+
+    /// Return the current epoch.
+    fn epoch(&self) -> u64;
+
+    /// Set the current epoch.
+    fn set_epoch(&mut self, epoch: u64);
+
+    /// Return the state of the loader after `step` batches of the current epoch.
+    fn state(&self, step: usize) -> LoaderState {
+        LoaderState {
+            epoch: self.epoch(),
+            step,
+        }
+    }
+
+    /// Restore a saved state, and return an iterator over the remaining batches of its epoch.
+    fn restore(&mut self, state: LoaderState) -> Self::Iter<'_> {
+        self.set_epoch(state.epoch);
+        self.iter_from(state.step)
+    }
+}
@@ -0,0 +1,534 @@
+//! # Dataset statistics
+//!
+//! Compute the per-channel or per-feature mean, standard deviation, minimum, maximum and histogram of a dataset, and
+//! the frequency of each class, in a single pass.
+//!
+//! The statistics are computed by the [`StatsCollate`] collate function: each batch of a `DataLoader` is reduced to a
+//! partial [`DatasetStats`], so the samples are fetched in parallel as during the training, then the batches are
+//! merged together. The running statistics are accumulated with the Welford algorithm to stay numerically stable.
+//!
+//! ```
+//! use ai_dataloader::indexable::DataLoader;
+//! use ai_dataloader::stats::{DatasetStats, StatsCollate};
+//! use ndarray::array;
+//!
+//! let dataset = vec![(array![[0_u8, 2], [10, 30]], 1), (array![[4, 6], [20, 40]], 0)];
+//! let loader = DataLoader::builder(dataset)
+//!     .batch_size(64)
+//!     .collate_fn(StatsCollate::new().channel_axis(0))
+//!     .build();
+//! let stats: DatasetStats = loader.iter().collect();
+//!
+//! assert_eq!(stats.mean(), vec![3., 25.]);
+//! assert_eq!(stats.classes()[&1], 1);
+//! let stats = DatasetStats::from_json(&stats.to_json()).unwrap();
+//! let normalize = stats.normalize();
+//! ```
+
+use crate::{collate::Collate, transforms::Normalize};
+use ndarray::{ArrayBase, Axis, Data, RemoveAxis};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Collate function reducing a batch of samples to their [`DatasetStats`].
+///
+/// By default each element of the samples is a feature with its own statistics, which suits tabular data. Use
+/// [`StatsCollate::channel_axis`] to gather the statistics along a channel axis instead, for instance for images.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StatsCollate {
+    /// Axis of the channels, if the statistics are per channel.
+    channel_axis: Option<usize>,
+    /// Number of bins, lower and upper bound of the histograms.
+    histogram: Option<(usize, f64, f64)>,
+}
+
+impl StatsCollate {
+    /// Create a new [`StatsCollate`] computing statistics per feature, without histogram.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compute the statistics per channel, the channels being along the given axis of the samples.
+    #[must_use]
+    pub fn channel_axis(mut self, axis: usize) -> Self {
+        self.channel_axis = Some(axis);
+        self
+    }
+
+    /// Compute a histogram of `bins` bins of equal width between `low` and `high` for each channel. The values
+    /// outside of the range are counted in the first or the last bin.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bins` is zero or if `low` is not lower than `high`.
+    #[must_use]
+    pub fn histogram(mut self, bins: usize, low: f64, high: f64) -> Self {
+        assert!(bins > 0, "histogram should have at least one bin");
+        assert!(
+            low < high,
+            "histogram lower bound should be below the upper bound"
+        );
+        self.histogram = Some((bins, low, high));
+        self
+    }
+
+    /// Add the values of the features of a sample to `stats`.
+    fn observe_features<S, D>(&self, features: &ArrayBase<S, D>, stats: &mut DatasetStats)
+    where
+        S: Data,
+        S::Elem: Copy + Into<f64>,
+        D: RemoveAxis,
+    {
+        if let Some(axis) = self.channel_axis {
+            self.ensure_channels(stats, features.len_of(Axis(axis)));
+            for (channel, values) in stats
+                .channels
+                .iter_mut()
+                .zip(features.axis_iter(Axis(axis)))
+            {
+                for &value in &values {
+                    channel.push(value.into());
+                }
+            }
+        } else {
+            self.ensure_channels(stats, features.len());
+            for (channel, &value) in stats.channels.iter_mut().zip(features) {
+                channel.push(value.into());
+            }
+        }
+    }
+
+    fn ensure_channels(&self, stats: &mut DatasetStats, channels: usize) {
+        if stats.channels.is_empty() {
+            stats.channels = vec![ChannelStats::new(self.histogram); channels];
+        }
+        assert_eq!(
+            stats.channels.len(),
+            channels,
+            "all the samples should have the same number of channels"
+        );
+    }
+}
+
+/// A sample whose statistics can be gathered by [`StatsCollate`].
+pub trait Observe {
+    /// Add the statistics of this sample to `stats`.
+    fn observe(&self, collate: &StatsCollate, stats: &mut DatasetStats);
+}
+
+impl<S, D> Observe for ArrayBase<S, D>
+where
+    S: Data,
+    S::Elem: Copy + Into<f64>,
+    D: RemoveAxis,
+{
+    fn observe(&self, collate: &StatsCollate, stats: &mut DatasetStats) {
+        collate.observe_features(self, stats);
+    }
+}
+
+/// Samples made of features and a class label.
+impl<F, L> Observe for (F, L)
+where
+    F: Observe,
+    L: Label,
+{
+    fn observe(&self, collate: &StatsCollate, stats: &mut DatasetStats) {
+        self.0.observe(collate, stats);
+        *stats.classes.entry(self.1.class()).or_default() += 1;
+    }
+}
+
+/// A class label, counted in the class frequencies.
+pub trait Label {
+    /// Return the class of the label.
+    fn class(&self) -> i64;
+}
+
+macro_rules! label_impl {
+    ($($t:ty)*) => {
+        $(
+            impl Label for $t {
+                fn class(&self) -> i64 {
+                    i64::try_from(*self).expect("class label should fit in an i64")
+                }
+            }
+        )*
+    };
+}
+label_impl! { bool u8 u16 u32 u64 usize i8 i16 i32 i64 isize }
+
+impl<T> Collate<T> for StatsCollate
+where
+    T: Observe,
+{
+    type Output = DatasetStats;
+    fn collate(&self, batch: Vec<T>) -> Self::Output {
+        let mut stats = DatasetStats::default();
+        for sample in &batch {
+            sample.observe(self, &mut stats);
+        }
+        stats.samples = batch.len() as u64;
+        stats
+    }
+}
+
+/// Statistics of a dataset, or of a part of it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DatasetStats {
+    /// Number of samples.
+    samples: u64,
+    /// Statistics of each channel or feature.
+    channels: Vec<ChannelStats>,
+    /// Number of samples of each class.
+    classes: BTreeMap<i64, u64>,
+}
+
+impl DatasetStats {
+    /// Add the statistics of another part of the dataset.
+    ///
+    /// # Panics
+    ///
+    /// Panics if both parts have a different number of channels.
+    pub fn merge(&mut self, other: &Self) {
+        if self.channels.is_empty() {
+            self.channels.clone_from(&other.channels);
+        } else if !other.channels.is_empty() {
+            assert_eq!(
+                self.channels.len(),
+                other.channels.len(),
+                "all the samples should have the same number of channels"
+            );
+            for (channel, other) in self.channels.iter_mut().zip(&other.channels) {
+                channel.merge(other);
+            }
+        }
+        for (&class, &count) in &other.classes {
+            *self.classes.entry(class).or_default() += count;
+        }
+        self.samples += other.samples;
+    }
+
+    /// Return the number of samples.
+    #[must_use]
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    /// Return the statistics of each channel or feature.
+    #[must_use]
+    pub fn channels(&self) -> &[ChannelStats] {
+        &self.channels
+    }
+
+    /// Return the number of samples of each class.
+    #[must_use]
+    pub fn classes(&self) -> &BTreeMap<i64, u64> {
+        &self.classes
+    }
+
+    /// Return the mean of each channel.
+    #[must_use]
+    pub fn mean(&self) -> Vec<f64> {
+        self.channels.iter().map(ChannelStats::mean).collect()
+    }
+
+    /// Return the standard deviation of each channel.
+    #[must_use]
+    pub fn std(&self) -> Vec<f64> {
+        self.channels.iter().map(ChannelStats::std).collect()
+    }
+
+    /// Return a [`Normalize`] transform with the mean and the standard deviation of each channel.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn normalize(&self) -> Normalize {
+        Normalize::new(
+            self.mean().into_iter().map(|mean| mean as f32).collect(),
+            self.std().into_iter().map(|std| std as f32).collect(),
+        )
+    }
+
+    /// Serialize the statistics to JSON.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a statistic isn't a finite number, as JSON doesn't support them.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("statistics should be finite")
+    }
+
+    /// Deserialize statistics from JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the JSON doesn't describe statistics.
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
+
+impl FromIterator<DatasetStats> for DatasetStats {
+    fn from_iter<I: IntoIterator<Item = DatasetStats>>(iter: I) -> Self {
+        let mut stats = Self::default();
+        for batch in iter {
+            stats.merge(&batch);
+        }
+        stats
+    }
+}
+
+/// Running statistics of a channel or a feature.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChannelStats {
+    /// Number of values.
+    count: u64,
+    /// Running mean.
+    mean: f64,
+    /// Running sum of the squared differences to the mean.
+    m2: f64,
+    /// Minimum value.
+    min: f64,
+    /// Maximum value.
+    max: f64,
+    /// Histogram of the values, if requested.
+    histogram: Option<Histogram>,
+}
+
+impl ChannelStats {
+    fn new(histogram: Option<(usize, f64, f64)>) -> Self {
+        Self {
+            count: 0,
+            mean: 0.,
+            m2: 0.,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            histogram: histogram.map(|(bins, low, high)| Histogram {
+                low,
+                high,
+                counts: vec![0; bins],
+            }),
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn push(&mut self, value: f64) {
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        if let Some(histogram) = &mut self.histogram {
+            histogram.push(value);
+        }
+    }
+
+    /// Combine the running statistics of two parts, with the parallel algorithm of Chan et al.
+    #[allow(clippy::cast_precision_loss)]
+    fn merge(&mut self, other: &Self) {
+        let count = self.count + other.count;
+        if count == 0 {
+            return;
+        }
+        let delta = other.mean - self.mean;
+        let (left, right) = (self.count as f64, other.count as f64);
+        self.mean += delta * right / count as f64;
+        self.m2 += other.m2 + delta * delta * left * right / count as f64;
+        self.count = count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        if let (Some(histogram), Some(other)) = (&mut self.histogram, &other.histogram) {
+            for (count, other) in histogram.counts.iter_mut().zip(&other.counts) {
+                *count += other;
+            }
+        }
+    }
+
+    /// Return the number of values.
+    #[must_use]
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Return the mean of the values.
+    #[must_use]
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// Return the variance of the values, dividing by their number.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn variance(&self) -> f64 {
+        if self.count == 0 {
+            0.
+        } else {
+            self.m2 / self.count as f64
+        }
+    }
+
+    /// Return the standard deviation of the values, dividing by their number.
+    #[must_use]
+    pub fn std(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    /// Return the minimum value.
+    #[must_use]
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+
+    /// Return the maximum value.
+    #[must_use]
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+
+    /// Return the histogram of the values, if requested with [`StatsCollate::histogram`].
+    #[must_use]
+    pub fn histogram(&self) -> Option<&Histogram> {
+        self.histogram.as_ref()
+    }
+}
+
+/// Histogram with bins of equal width.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Histogram {
+    /// Lower bound of the first bin.
+    low: f64,
+    /// Upper bound of the last bin.
+    high: f64,
+    /// Number of values in each bin.
+    counts: Vec<u64>,
+}
+
+impl Histogram {
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn push(&mut self, value: f64) {
+        let bins = self.counts.len();
+        let bin = ((value - self.low) / (self.high - self.low) * bins as f64).floor();
+        // The cast saturates the values below the range to zero.
+        let bin = (bin as usize).min(bins - 1);
+        self.counts[bin] += 1;
+    }
+
+    /// Return the lower bound of the first bin.
+    #[must_use]
+    pub fn low(&self) -> f64 {
+        self.low
+    }
+
+    /// Return the upper bound of the last bin.
+    #[must_use]
+    pub fn high(&self) -> f64 {
+        self.high
+    }
+
+    /// Return the number of values in each bin.
+    #[must_use]
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+    use crate::indexable::DataLoader;
+    use ndarray::{array, Array1, Array3};
+
+    #[test]
+    fn per_feature() {
+        let dataset = vec![array![1., 10.], array![2., 20.], array![3., 60.]];
+        let stats = StatsCollate::new().collate(dataset);
+        assert_eq!(stats.samples(), 3);
+        assert_eq!(stats.mean(), vec![2., 30.]);
+        assert!((stats.std()[0] - (2_f64 / 3.).sqrt()).abs() < 1e-12);
+        assert_eq!(stats.channels()[1].min(), 10.);
+        assert_eq!(stats.channels()[1].max(), 60.);
+        assert!(stats.classes().is_empty());
+    }
+
+    #[test]
+    fn merge_matches_single_pass() {
+        let dataset: Vec<_> = (0..100_u8)
+            .map(|i| {
+                (
+                    Array3::from_shape_fn((3, 4, 4), |(c, h, w)| {
+                        i.wrapping_mul(7) ^ u8::try_from(c * 16 + h * 4 + w).unwrap()
+                    }),
+                    i % 3,
+                )
+            })
+            .collect();
+        let collate = StatsCollate::new().channel_axis(0).histogram(4, 0., 256.);
+        let whole = collate.collate(dataset.clone());
+
+        let loader = DataLoader::builder(dataset)
+            .batch_size(7)
+            .collate_fn(collate)
+            .build();
+        let merged: DatasetStats = loader.iter().collect();
+
+        assert_eq!(merged.samples(), 100);
+        assert_eq!(merged.classes(), whole.classes());
+        assert_eq!(merged.classes()[&0], 34);
+        for (merged, whole) in merged.channels().iter().zip(whole.channels()) {
+            assert_eq!(merged.count(), 1600);
+            assert!((merged.mean() - whole.mean()).abs() < 1e-9);
+            assert!((merged.variance() - whole.variance()).abs() < 1e-6);
+            assert_eq!(merged.min(), whole.min());
+            assert_eq!(merged.max(), whole.max());
+            assert_eq!(merged.histogram(), whole.histogram());
+            assert_eq!(
+                merged.histogram().unwrap().counts().iter().sum::<u64>(),
+                1600
+            );
+        }
+    }
+
+    #[test]
+    fn stable_with_large_offset() {
+        let dataset: Vec<Array1<f64>> = (0..1000).map(|i| array![1e9 + f64::from(i % 2)]).collect();
+        let stats = StatsCollate::new().collate(dataset);
+        assert!((stats.std()[0] - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn histogram_clamps() {
+        let stats = StatsCollate::new().histogram(2, 0., 1.).collate(vec![
+            array![-5.],
+            array![0.2],
+            array![0.7],
+            array![1.],
+            array![9.],
+        ]);
+        assert_eq!(stats.channels()[0].histogram().unwrap().counts(), &[2, 3]);
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let stats = StatsCollate::new()
+            .channel_axis(0)
+            .histogram(3, 0., 1.)
+            .collate(vec![
+                (array![[0.5_f32], [0.25]], 2_usize),
+                (array![[0.], [1.]], 7),
+            ]);
+        let json = stats.to_json();
+        assert_eq!(DatasetStats::from_json(&json).unwrap(), stats);
+        assert_eq!(
+            stats.normalize(),
+            Normalize::new(vec![0.25, 0.625], vec![0.25, 0.375])
+        );
+    }
+}
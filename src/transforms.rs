@@ -0,0 +1,203 @@
+//! Transforms applied to the samples of a dataset, such as data augmentation.
+//!
+//! A [`Transform`] takes its randomness from the generator it is given. When a dataset is wrapped in a
+//! [`Transformed`] and loaded by the indexable [`DataLoader`](crate::indexable::DataLoader), this generator is derived
+//! from the loader seed, the epoch, the iteration in the epoch and the index of the sample, so the augmentations are
+//! reproducible.
+//!
+//! ```
+//! use ai_dataloader::indexable::DataLoader;
+//! use ai_dataloader::transforms::{Normalize, RandomCrop, RandomHorizontalFlip, ToChw, ToFloat, Transform, Transformed};
+//! use ndarray::Array3;
+//! use rand::rngs::StdRng;
+//!
+//! // Images in height, width, channel layout, with a label.
+//! let images = vec![(Array3::<u8>::zeros((32, 32, 3)), 0), (Array3::<u8>::zeros((32, 32, 3)), 1)];
+//!
+//! let augment = ToFloat
+//!     .then(ToChw)
+//!     .then(RandomCrop::new(28, 28))
+//!     .then(RandomHorizontalFlip::default())
+//!     .then(Normalize::new(vec![0.5; 3], vec![0.25; 3]));
+//! let dataset = Transformed::new(images, move |(image, label), rng: &mut StdRng| {
+//!     (augment.apply(image, rng), label)
+//! });
+//!
+//! let loader = DataLoader::builder(dataset).batch_size(2).seed(42).build();
+//! let (images, labels) = loader.iter().next().unwrap();
+//! assert_eq!(images.shape(), [2, 3, 28, 28]);
+//! ```
+
+use crate::{Dataset, GetSample, Len};
+use rand::{rngs::StdRng, SeedableRng};
+
+mod image;
+pub use image::{
+    CenterCrop, ColorJitter, Normalize, RandomCrop, RandomHorizontalFlip, Resize, ToChw, ToFloat,
+};
+
+/// Transform a value, drawing any randomness from the given generator.
+pub trait Transform<T> {
+    /// The type of the transformed value.
+    type Output;
+    /// Apply the transform.
+    fn apply(&self, input: T, rng: &mut StdRng) -> Self::Output;
+
+    /// Chain this transform with another one, applied on its output.
+    fn then<N>(self, next: N) -> Compose<Self, N>
+    where
+        Self: Sized,
+        N: Transform<Self::Output>,
+    {
+        Compose::new(self, next)
+    }
+}
+
+// Allow user to specify closure as transform.
+impl<T, F, O> Transform<T> for F
+where
+    F: Fn(T, &mut StdRng) -> O,
+{
+    type Output = O;
+    fn apply(&self, input: T, rng: &mut StdRng) -> Self::Output {
+        self(input, rng)
+    }
+}
+
+/// Apply two transforms one after the other.
+///
+/// Longer pipelines are built by nesting, which is what [`Transform::then`] does.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Compose<A, B> {
+    /// Transform applied first.
+    first: A,
+    /// Transform applied on the output of the first one.
+    second: B,
+}
+
+impl<A, B> Compose<A, B> {
+    /// Create a new [`Compose`].
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+}
+
+impl<T, A, B> Transform<T> for Compose<A, B>
+where
+    A: Transform<T>,
+    B: Transform<A::Output>,
+{
+    type Output = B::Output;
+    fn apply(&self, input: T, rng: &mut StdRng) -> Self::Output {
+        let intermediate = self.first.apply(input, rng);
+        self.second.apply(intermediate, rng)
+    }
+}
+
+/// Dataset applying a [`Transform`] on each sample of another dataset.
+///
+/// When loaded by the indexable [`DataLoader`](crate::indexable::DataLoader), the transform uses the loader
+/// per-sample generator. When calling [`GetSample::get_sample`] directly, a generator seeded from the entropy of the
+/// system is used instead.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Transformed<D, T> {
+    /// The transformed dataset.
+    dataset: D,
+    /// The transform applied on each sample.
+    transform: T,
+}
+
+impl<D, T> Transformed<D, T>
+where
+    D: Dataset,
+    T: Transform<D::Sample>,
+{
+    /// Create a new [`Transformed`] dataset.
+    pub fn new(dataset: D, transform: T) -> Self {
+        Self { dataset, transform }
+    }
+}
+
+impl<D: Len, T> Len for Transformed<D, T> {
+    fn len(&self) -> usize {
+        self.dataset.len()
+    }
+}
+
+impl<D, T> GetSample for Transformed<D, T>
+where
+    D: GetSample,
+    T: Transform<D::Sample>,
+{
+    type Sample = T::Output;
+    fn get_sample(&self, index: usize) -> Self::Sample {
+        self.get_sample_with_rng(index, &mut StdRng::from_entropy())
+    }
+    fn get_sample_with_rng(&self, index: usize, rng: &mut StdRng) -> Self::Sample {
+        let sample = self.dataset.get_sample_with_rng(index, rng);
+        self.transform.apply(sample, rng)
+    }
+}
+
+impl<D, T> Dataset for Transformed<D, T>
+where
+    D: Dataset,
+    T: Transform<D::Sample>,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{collate::NoOpCollate, indexable::DataLoader};
+    use rand::Rng;
+
+    fn add_noise(x: f64, rng: &mut StdRng) -> f64 {
+        x + rng.gen_range(0.0..1.0)
+    }
+
+    #[test]
+    fn compose() {
+        let transform = (|x: i32, _: &mut StdRng| x + 1).then(|x: i32, _: &mut StdRng| x * 2);
+        assert_eq!(transform.apply(3, &mut StdRng::seed_from_u64(0)), 8);
+
+        let transform = Compose::new(
+            |x: i32, _: &mut StdRng| x * 2,
+            |x: i32, _: &mut StdRng| x + 1,
+        );
+        assert_eq!(transform.apply(3, &mut StdRng::seed_from_u64(0)), 7);
+    }
+
+    #[test]
+    fn reproducible() {
+        let dataset = Transformed::new(vec![0.; 8], add_noise);
+        let loader = |seed| {
+            DataLoader::builder(dataset.clone())
+                .batch_size(3)
+                .seed(seed)
+                .collate_fn(NoOpCollate)
+                .build()
+        };
+        let epoch: Vec<Vec<f64>> = loader(1).iter().collect();
+        assert_eq!(epoch, loader(1).iter().collect::<Vec<_>>());
+        assert_ne!(epoch, loader(2).iter().collect::<Vec<_>>());
+
+        // The generator of a sample doesn't depend on the batch size nor the number of threads.
+        let unbatched: Vec<f64> = DataLoader::builder(dataset.clone())
+            .seed(1)
+            .collate_fn(NoOpCollate)
+            .build()
+            .iter()
+            .flatten()
+            .collect();
+        assert_eq!(epoch.concat(), unbatched);
+
+        let mut loader = loader(1);
+        assert_eq!(epoch, loader.iter().collect::<Vec<_>>());
+        // A new iteration over the same epoch gets new random transforms.
+        assert_ne!(epoch, loader.iter().collect::<Vec<_>>());
+        loader.set_epoch(1);
+        assert_eq!(loader.epoch(), 1);
+        assert_ne!(epoch, loader.iter().collect::<Vec<_>>());
+    }
+}
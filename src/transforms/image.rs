@@ -0,0 +1,450 @@
+//! Transforms for `ndarray` images.
+//!
+//! Decoded images usually come in height, width, channel layout. As in `torchvision`, they are first converted with
+//! [`ToFloat`] and [`ToChw`], and all the other transforms expect images in channel, height, width layout.
+
+use super::Transform;
+use ndarray::{s, Array, Array3, Axis, Dimension};
+use rand::{rngs::StdRng, Rng};
+
+/// Convert an `u8` image into a `f32` image with values in `[0, 1]`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ToFloat;
+
+impl<D: Dimension> Transform<Array<u8, D>> for ToFloat {
+    type Output = Array<f32, D>;
+    fn apply(&self, input: Array<u8, D>, _rng: &mut StdRng) -> Self::Output {
+        input.mapv(|value| f32::from(value) / 255.)
+    }
+}
+
+/// Convert an image from height, width, channel layout into channel, height, width layout.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ToChw;
+
+impl<A: Clone> Transform<Array3<A>> for ToChw {
+    type Output = Array3<A>;
+    fn apply(&self, input: Array3<A>, _rng: &mut StdRng) -> Self::Output {
+        input
+            .permuted_axes([2, 0, 1])
+            .as_standard_layout()
+            .into_owned()
+    }
+}
+
+/// Normalize each channel of an image with the given mean and standard deviation: `(value - mean) / std`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Normalize {
+    /// Mean of each channel.
+    mean: Vec<f32>,
+    /// Standard deviation of each channel.
+    std: Vec<f32>,
+}
+
+impl Normalize {
+    /// Create a new [`Normalize`] from the mean and the standard deviation of each channel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mean` and `std` have different lengths.
+    #[must_use]
+    pub fn new(mean: Vec<f32>, std: Vec<f32>) -> Self {
+        assert_eq!(
+            mean.len(),
+            std.len(),
+            "mean and std should have the same number of channels"
+        );
+        Self { mean, std }
+    }
+}
+
+impl Transform<Array3<f32>> for Normalize {
+    type Output = Array3<f32>;
+    fn apply(&self, mut input: Array3<f32>, _rng: &mut StdRng) -> Self::Output {
+        assert_eq!(
+            input.len_of(Axis(0)),
+            self.mean.len(),
+            "the image should have one channel per mean value"
+        );
+        for ((mut channel, mean), std) in
+            input.axis_iter_mut(Axis(0)).zip(&self.mean).zip(&self.std)
+        {
+            channel.mapv_inplace(|value| (value - mean) / std);
+        }
+        input
+    }
+}
+
+/// Crop the center of an image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CenterCrop {
+    /// Height of the crop.
+    height: usize,
+    /// Width of the crop.
+    width: usize,
+}
+
+impl CenterCrop {
+    /// Create a new [`CenterCrop`] of the given size.
+    #[must_use]
+    pub fn new(height: usize, width: usize) -> Self {
+        Self { height, width }
+    }
+}
+
+impl<A: Clone> Transform<Array3<A>> for CenterCrop {
+    type Output = Array3<A>;
+    fn apply(&self, input: Array3<A>, _rng: &mut StdRng) -> Self::Output {
+        let (height, width) = image_size(&input, self.height, self.width);
+        let (top, left) = ((height - self.height) / 2, (width - self.width) / 2);
+        crop(&input, top, left, self.height, self.width)
+    }
+}
+
+/// Crop an image at a random location.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RandomCrop {
+    /// Height of the crop.
+    height: usize,
+    /// Width of the crop.
+    width: usize,
+}
+
+impl RandomCrop {
+    /// Create a new [`RandomCrop`] of the given size.
+    #[must_use]
+    pub fn new(height: usize, width: usize) -> Self {
+        Self { height, width }
+    }
+}
+
+impl<A: Clone> Transform<Array3<A>> for RandomCrop {
+    type Output = Array3<A>;
+    fn apply(&self, input: Array3<A>, rng: &mut StdRng) -> Self::Output {
+        let (height, width) = image_size(&input, self.height, self.width);
+        let top = rng.gen_range(0..=height - self.height);
+        let left = rng.gen_range(0..=width - self.width);
+        crop(&input, top, left, self.height, self.width)
+    }
+}
+
+/// Flip an image horizontally with a given probability.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RandomHorizontalFlip {
+    /// Probability to flip the image.
+    probability: f64,
+}
+
+impl RandomHorizontalFlip {
+    /// Create a new [`RandomHorizontalFlip`] flipping the images with the given probability.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the probability is not in `[0, 1]`.
+    #[must_use]
+    pub fn new(probability: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&probability),
+            "probability should be in [0, 1]"
+        );
+        Self { probability }
+    }
+}
+
+/// Flip half of the images.
+impl Default for RandomHorizontalFlip {
+    fn default() -> Self {
+        Self::new(0.5)
+    }
+}
+
+impl<A: Clone> Transform<Array3<A>> for RandomHorizontalFlip {
+    type Output = Array3<A>;
+    fn apply(&self, input: Array3<A>, rng: &mut StdRng) -> Self::Output {
+        if rng.gen_bool(self.probability) {
+            input.slice(s![.., .., ..;-1]).to_owned()
+        } else {
+            input
+        }
+    }
+}
+
+/// Resize an image with a bilinear interpolation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Resize {
+    /// Height of the resized image.
+    height: usize,
+    /// Width of the resized image.
+    width: usize,
+}
+
+impl Resize {
+    /// Create a new [`Resize`] to the given size.
+    #[must_use]
+    pub fn new(height: usize, width: usize) -> Self {
+        Self { height, width }
+    }
+}
+
+impl Transform<Array3<f32>> for Resize {
+    type Output = Array3<f32>;
+    fn apply(&self, input: Array3<f32>, _rng: &mut StdRng) -> Self::Output {
+        let (channels, height, width) = input.dim();
+        assert!(height > 0 && width > 0, "can't resize an empty image");
+        let rows: Vec<_> = (0..self.height)
+            .map(|y| source_position(y, height, self.height))
+            .collect();
+        let columns: Vec<_> = (0..self.width)
+            .map(|x| source_position(x, width, self.width))
+            .collect();
+
+        Array3::from_shape_fn((channels, self.height, self.width), |(c, y, x)| {
+            let (top, bottom, dy) = rows[y];
+            let (left, right, dx) = columns[x];
+            let upper = input[[c, top, left]] * (1. - dx) + input[[c, top, right]] * dx;
+            let lower = input[[c, bottom, left]] * (1. - dx) + input[[c, bottom, right]] * dx;
+            upper * (1. - dy) + lower * dy
+        })
+    }
+}
+
+/// Randomly change the brightness, the contrast and the saturation of an image with values in `[0, 1]`.
+///
+/// Each factor is drawn uniformly in `[max(0, 1 - x), 1 + x]`, where `x` is the jitter set for the property, and
+/// the adjustments are applied in this order: brightness, contrast, saturation. The saturation only applies to RGB
+/// images.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ColorJitter {
+    /// How much to jitter the brightness.
+    brightness: f32,
+    /// How much to jitter the contrast.
+    contrast: f32,
+    /// How much to jitter the saturation.
+    saturation: f32,
+}
+
+impl ColorJitter {
+    /// Create a new [`ColorJitter`] leaving the images untouched.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Set how much to jitter the brightness.
+    #[must_use]
+    pub fn brightness(mut self, brightness: f32) -> Self {
+        self.brightness = brightness;
+        self
+    }
+    /// Set how much to jitter the contrast.
+    #[must_use]
+    pub fn contrast(mut self, contrast: f32) -> Self {
+        self.contrast = contrast;
+        self
+    }
+    /// Set how much to jitter the saturation.
+    #[must_use]
+    pub fn saturation(mut self, saturation: f32) -> Self {
+        self.saturation = saturation;
+        self
+    }
+}
+
+impl Transform<Array3<f32>> for ColorJitter {
+    type Output = Array3<f32>;
+    fn apply(&self, mut input: Array3<f32>, rng: &mut StdRng) -> Self::Output {
+        if let Some(factor) = jitter_factor(self.brightness, rng) {
+            input.mapv_inplace(|value| value * factor);
+        }
+        if let Some(factor) = jitter_factor(self.contrast, rng) {
+            let mean = grayscale(&input).mean().unwrap_or(0.);
+            input.mapv_inplace(|value| (value - mean) * factor + mean);
+        }
+        if let Some(factor) = jitter_factor(self.saturation, rng) {
+            if input.len_of(Axis(0)) == 3 {
+                let gray = grayscale(&input);
+                for mut channel in input.axis_iter_mut(Axis(0)) {
+                    channel.zip_mut_with(&gray, |value, gray| {
+                        *value = (*value - gray) * factor + gray;
+                    });
+                }
+            }
+        }
+        input.mapv_inplace(|value| value.clamp(0., 1.));
+        input
+    }
+}
+
+/// Return the height and width of an image, checking that it's large enough to be cropped.
+fn image_size<A>(image: &Array3<A>, crop_height: usize, crop_width: usize) -> (usize, usize) {
+    let (_, height, width) = image.dim();
+    assert!(
+        crop_height <= height && crop_width <= width,
+        "crop size ({crop_height}, {crop_width}) is larger than the image size ({height}, {width})"
+    );
+    (height, width)
+}
+
+fn crop<A: Clone>(
+    image: &Array3<A>,
+    top: usize,
+    left: usize,
+    height: usize,
+    width: usize,
+) -> Array3<A> {
+    image
+        .slice(s![.., top..top + height, left..left + width])
+        .to_owned()
+}
+
+/// Return the two neighbours of a resized pixel in the source image and the interpolation weight of the second one,
+/// aligning the pixel centers.
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn source_position(position: usize, source_len: usize, target_len: usize) -> (usize, usize, f32) {
+    let scale = source_len as f32 / target_len as f32;
+    let source = ((position as f32 + 0.5) * scale - 0.5).clamp(0., (source_len - 1) as f32);
+    let before = source.floor() as usize;
+    let after = (before + 1).min(source_len - 1);
+    (before, after, source - before as f32)
+}
+
+/// Draw a jitter factor, or `None` if the property isn't jittered.
+fn jitter_factor(jitter: f32, rng: &mut StdRng) -> Option<f32> {
+    (jitter > 0.).then(|| rng.gen_range((1. - jitter).max(0.)..=1. + jitter))
+}
+
+/// Return the luminance of an RGB image, or the mean over the channels for other images.
+fn grayscale(image: &Array3<f32>) -> ndarray::Array2<f32> {
+    if image.len_of(Axis(0)) == 3 {
+        &image.index_axis(Axis(0), 0) * 0.299
+            + &image.index_axis(Axis(0), 1) * 0.587
+            + &image.index_axis(Axis(0), 2) * 0.114
+    } else {
+        image.mean_axis(Axis(0)).expect("the image has channels")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+    use rand::SeedableRng;
+
+    fn rng() -> StdRng {
+        StdRng::seed_from_u64(0)
+    }
+
+    #[test]
+    fn to_float_to_chw() {
+        let image = array![[[0_u8, 255], [51, 102]]];
+        assert_eq!(
+            ToFloat.apply(image, &mut rng()),
+            array![[[0., 1.], [0.2, 0.4]]]
+        );
+
+        // 1 row, 2 columns, 3 channels.
+        let image = array![[[1, 2, 3], [4, 5, 6]]];
+        assert_eq!(
+            ToChw.apply(image, &mut rng()),
+            array![[[1, 4]], [[2, 5]], [[3, 6]]]
+        );
+    }
+
+    #[test]
+    fn normalize() {
+        let image = array![[[1., 3.]], [[2., 6.]]];
+        let normalize = Normalize::new(vec![2., 4.], vec![1., 2.]);
+        assert_eq!(
+            normalize.apply(image, &mut rng()),
+            array![[[-1., 1.]], [[-1., 1.]]]
+        );
+    }
+
+    #[test]
+    fn crops() {
+        let image = Array3::from_shape_fn((2, 4, 5), |(c, y, x)| c * 100 + y * 10 + x);
+        assert_eq!(
+            CenterCrop::new(2, 3).apply(image.clone(), &mut rng()),
+            array![
+                [[11, 12, 13], [21, 22, 23]],
+                [[111, 112, 113], [121, 122, 123]]
+            ]
+        );
+
+        let mut rng = rng();
+        for _ in 0..20 {
+            let cropped = RandomCrop::new(2, 3).apply(image.clone(), &mut rng);
+            assert_eq!(cropped.dim(), (2, 2, 3));
+            let (top, left) = (cropped[[0, 0, 0]] / 10, cropped[[0, 0, 0]] % 10);
+            assert_eq!(cropped, crop(&image, top, left, 2, 3));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "larger than the image size")]
+    fn crop_too_large() {
+        let _ = RandomCrop::new(3, 3).apply(Array3::<f32>::zeros((1, 2, 2)), &mut rng());
+    }
+
+    #[test]
+    fn horizontal_flip() {
+        let image = array![[[1, 2, 3]]];
+        assert_eq!(
+            RandomHorizontalFlip::new(1.).apply(image.clone(), &mut rng()),
+            array![[[3, 2, 1]]]
+        );
+        assert_eq!(
+            RandomHorizontalFlip::new(0.).apply(image.clone(), &mut rng()),
+            image
+        );
+    }
+
+    #[test]
+    fn resize() {
+        let image = array![[[0., 1.], [2., 3.]]];
+        let resized = Resize::new(4, 4).apply(image.clone(), &mut rng());
+        assert_eq!(
+            resized,
+            array![[
+                [0., 0.25, 0.75, 1.],
+                [0.5, 0.75, 1.25, 1.5],
+                [1.5, 1.75, 2.25, 2.5],
+                [2., 2.25, 2.75, 3.]
+            ]]
+        );
+        assert_eq!(
+            Resize::new(2, 2).apply(resized, &mut rng()),
+            array![[[0.375, 1.125], [1.875, 2.625]]]
+        );
+        assert_eq!(Resize::new(2, 2).apply(image.clone(), &mut rng()), image);
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn color_jitter() {
+        let image = Array3::from_shape_fn((3, 4, 4), |(c, y, x)| (c + y + x) as f32 / 10.);
+        assert_eq!(ColorJitter::new().apply(image.clone(), &mut rng()), image);
+
+        let jitter = ColorJitter::new()
+            .brightness(0.5)
+            .contrast(0.5)
+            .saturation(0.5);
+        let jittered = jitter.apply(image.clone(), &mut rng());
+        assert_eq!(jittered, jitter.apply(image.clone(), &mut rng()));
+        assert_ne!(jittered, image);
+        assert!(jittered.iter().all(|value| (0. ..=1.).contains(value)));
+
+        // Saturation doesn't change gray images.
+        let gray = Array3::from_shape_fn((3, 2, 2), |(_, y, x)| (y + x) as f32 / 4.);
+        let saturated = ColorJitter::new()
+            .saturation(0.9)
+            .apply(gray.clone(), &mut rng());
+        assert!(saturated
+            .iter()
+            .zip(&gray)
+            .all(|(saturated, gray)| (saturated - gray).abs() < 1e-6));
+    }
+}
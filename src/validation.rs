@@ -0,0 +1,530 @@
+//! # Dataset validation
+//!
+//! Check every sample of a dataset before the training, instead of discovering bad data mid-epoch when the collate
+//! function panics.
+//!
+//! [`validate`] fetches all the samples, in parallel with the `rayon` feature, and returns a [`ValidationReport`]
+//! listing the samples whose `get_sample` panicked, the fields whose shape differs from the shape of most samples,
+//! the fields containing NaN or infinite values, and the labels out of the range given with
+//! [`Validator::label_range`].
+//!
+//! ```
+//! use ai_dataloader::validation::Validator;
+//! use ndarray::array;
+//!
+//! let dataset = vec![
+//!     (array![1., 2.], 0),
+//!     (array![3., f64::NAN], 1),
+//!     (array![5., 6., 7.], 2),
+//!     (array![8., 9.], 12),
+//! ];
+//! let report = Validator::new().label_range(1, 0..10).validate(&dataset);
+//!
+//! assert!(!report.is_ok());
+//! assert_eq!(report.non_finite[0].index, 1);
+//! assert_eq!(report.shape_mismatches[0].index, 2);
+//! assert_eq!(report.labels_out_of_range[0].index, 3);
+//! ```
+
+use crate::Dataset;
+use ndarray::{ArrayBase, Data, Dimension};
+#[cfg(feature = "rayon")]
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use std::{
+    any::Any,
+    collections::HashMap,
+    fmt,
+    ops::Range,
+    panic::{self, AssertUnwindSafe},
+};
+
+/// Summary of a field of a sample, checked by the [`Validator`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct FieldSummary {
+    /// Shape of the field, empty for a scalar.
+    pub shape: Vec<usize>,
+    /// Number of NaN or infinite values.
+    pub non_finite: usize,
+    /// Lowest and highest integer value, used as labels.
+    pub labels: Option<(i64, i64)>,
+}
+
+impl FieldSummary {
+    /// Summarize a field of the given shape from its values.
+    pub fn new<T: Value>(shape: Vec<usize>, values: impl IntoIterator<Item = T>) -> Self {
+        let mut summary = Self {
+            shape,
+            ..Self::default()
+        };
+        for value in values {
+            if !value.is_finite() {
+                summary.non_finite += 1;
+            }
+            if let Some(label) = value.label() {
+                summary.labels = Some(match summary.labels {
+                    Some((low, high)) => (low.min(label), high.max(label)),
+                    None => (label, label),
+                });
+            }
+        }
+        summary
+    }
+}
+
+/// A primitive value of a sample.
+pub trait Value: Copy {
+    /// Return `false` for NaN or infinite values.
+    fn is_finite(self) -> bool;
+    /// Return the value as a label, for integers.
+    fn label(self) -> Option<i64>;
+}
+
+macro_rules! float_impl {
+    ($($t:ty)*) => {
+        $(
+            impl Value for $t {
+                fn is_finite(self) -> bool {
+                    <$t>::is_finite(self)
+                }
+                fn label(self) -> Option<i64> {
+                    None
+                }
+            }
+        )*
+    };
+}
+float_impl! { f32 f64 }
+
+macro_rules! integer_impl {
+    ($($t:ty)*) => {
+        $(
+            impl Value for $t {
+                fn is_finite(self) -> bool {
+                    true
+                }
+                fn label(self) -> Option<i64> {
+                    // Labels too large for an `i64` are out of range anyway.
+                    Some(i64::try_from(self).unwrap_or(i64::MAX))
+                }
+            }
+        )*
+    };
+}
+integer_impl! { bool u8 u16 u32 u64 usize i8 i16 i32 i64 isize }
+
+/// A sample that can be checked by the [`Validator`], made of one or several fields.
+pub trait Inspect {
+    /// Push the summary of each field of the sample.
+    fn inspect(&self, fields: &mut Vec<FieldSummary>);
+}
+
+impl<T: Value> Inspect for T {
+    fn inspect(&self, fields: &mut Vec<FieldSummary>) {
+        fields.push(FieldSummary::new(Vec::new(), [*self]));
+    }
+}
+
+impl<T: Value> Inspect for Vec<T> {
+    fn inspect(&self, fields: &mut Vec<FieldSummary>) {
+        fields.push(FieldSummary::new(vec![self.len()], self.iter().copied()));
+    }
+}
+
+impl<S, D> Inspect for ArrayBase<S, D>
+where
+    S: Data,
+    S::Elem: Value,
+    D: Dimension,
+{
+    fn inspect(&self, fields: &mut Vec<FieldSummary>) {
+        fields.push(FieldSummary::new(
+            self.shape().to_vec(),
+            self.iter().copied(),
+        ));
+    }
+}
+
+macro_rules! tuple_impl {
+    ($($name:ident)+) => {
+        impl<$($name),+> Inspect for ($($name,)+)
+        where
+            $($name: Inspect,)+
+        {
+            #[allow(non_snake_case)]
+            fn inspect(&self, fields: &mut Vec<FieldSummary>) {
+                let ($($name,)+) = self;
+                $($name.inspect(fields);)+
+            }
+        }
+    };
+}
+
+tuple_impl! { A }
+tuple_impl! { A B }
+tuple_impl! { A B C }
+tuple_impl! { A B C D }
+tuple_impl! { A B C D E }
+tuple_impl! { A B C D E F }
+tuple_impl! { A B C D E F G }
+tuple_impl! { A B C D E F G H }
+
+/// Configure the checks of [`validate`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Validator {
+    /// Allowed range of the labels of each field.
+    label_ranges: HashMap<usize, Range<i64>>,
+}
+
+impl Validator {
+    /// Create a new [`Validator`], checking the shapes and the float values of the samples.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check that the integers of the field at position `field` in the samples are in `range`, for instance the
+    /// class labels `0..num_classes`.
+    #[must_use]
+    pub fn label_range(mut self, field: usize, range: Range<i64>) -> Self {
+        self.label_ranges.insert(field, range);
+        self
+    }
+
+    /// Fetch all the samples of the dataset and check them.
+    ///
+    /// The panics of `get_sample` are caught and reported, but still printed by the panic hook.
+    pub fn validate<D>(&self, dataset: &D) -> ValidationReport
+    where
+        D: Dataset + Sync,
+        D::Sample: Inspect,
+    {
+        let summarize = |index| {
+            panic::catch_unwind(AssertUnwindSafe(|| {
+                let mut fields = Vec::new();
+                dataset.get_sample(index).inspect(&mut fields);
+                fields
+            }))
+            .map_err(panic_message)
+        };
+        #[cfg(feature = "rayon")]
+        let samples: Vec<_> = (0..dataset.len()).into_par_iter().map(summarize).collect();
+        #[cfg(not(feature = "rayon"))]
+        let samples: Vec<_> = (0..dataset.len()).map(summarize).collect();
+
+        self.report(&samples)
+    }
+
+    fn report(&self, samples: &[Result<Vec<FieldSummary>, String>]) -> ValidationReport {
+        let mut report = ValidationReport {
+            samples: samples.len(),
+            ..ValidationReport::default()
+        };
+
+        // The expected shape of a field is its most frequent shape.
+        let mut shape_counts: Vec<HashMap<&[usize], usize>> = Vec::new();
+        for fields in samples.iter().flatten() {
+            shape_counts.resize_with(shape_counts.len().max(fields.len()), HashMap::new);
+            for (counts, field) in shape_counts.iter_mut().zip(fields) {
+                *counts.entry(&field.shape).or_default() += 1;
+            }
+        }
+        let expected_shapes: Vec<&[usize]> = shape_counts
+            .iter()
+            .map(|counts| {
+                counts
+                    .iter()
+                    .max_by_key(|&(shape, count)| (*count, std::cmp::Reverse(*shape)))
+                    .map(|(shape, _)| *shape)
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        for (index, sample) in samples.iter().enumerate() {
+            let fields = match sample {
+                Ok(fields) => fields,
+                Err(message) => {
+                    report.panics.push(SamplePanic {
+                        index,
+                        message: message.clone(),
+                    });
+                    continue;
+                }
+            };
+            for (field, (summary, expected)) in fields.iter().zip(&expected_shapes).enumerate() {
+                if summary.shape != *expected {
+                    report.shape_mismatches.push(ShapeMismatch {
+                        index,
+                        field,
+                        expected: expected.to_vec(),
+                        found: summary.shape.clone(),
+                    });
+                }
+                if summary.non_finite > 0 {
+                    report.non_finite.push(NonFinite {
+                        index,
+                        field,
+                        count: summary.non_finite,
+                    });
+                }
+                if let (Some(range), Some((low, high))) =
+                    (self.label_ranges.get(&field), summary.labels)
+                {
+                    for label in [low, high] {
+                        if !range.contains(&label) {
+                            report.labels_out_of_range.push(LabelOutOfRange {
+                                index,
+                                field,
+                                label,
+                            });
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        report
+    }
+}
+
+/// Check all the samples of the dataset with the default [`Validator`].
+pub fn validate<D>(dataset: &D) -> ValidationReport
+where
+    D: Dataset + Sync,
+    D::Sample: Inspect,
+{
+    Validator::new().validate(dataset)
+}
+
+/// Return the message of a caught panic.
+pub(crate) fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload.downcast_ref::<&str>().map_or_else(
+            || "unknown panic".to_owned(),
+            |message| (*message).to_owned(),
+        ),
+    }
+}
+
+/// Problems found in a dataset by [`validate`], sorted by sample index.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// Number of samples checked.
+    pub samples: usize,
+    /// Samples whose `get_sample` panicked.
+    pub panics: Vec<SamplePanic>,
+    /// Fields whose shape differs from the shape of most samples.
+    pub shape_mismatches: Vec<ShapeMismatch>,
+    /// Fields containing NaN or infinite values.
+    pub non_finite: Vec<NonFinite>,
+    /// Fields with a label out of the range set with [`Validator::label_range`].
+    pub labels_out_of_range: Vec<LabelOutOfRange>,
+}
+
+impl ValidationReport {
+    /// Return `true` if no problem was found.
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.panics.is_empty()
+            && self.shape_mismatches.is_empty()
+            && self.non_finite.is_empty()
+            && self.labels_out_of_range.is_empty()
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} samples checked", self.samples)?;
+        for panic in &self.panics {
+            writeln!(f, "sample {}: panicked: {}", panic.index, panic.message)?;
+        }
+        for mismatch in &self.shape_mismatches {
+            writeln!(
+                f,
+                "sample {}, field {}: shape {:?} instead of {:?}",
+                mismatch.index, mismatch.field, mismatch.found, mismatch.expected
+            )?;
+        }
+        for non_finite in &self.non_finite {
+            writeln!(
+                f,
+                "sample {}, field {}: {} NaN or infinite values",
+                non_finite.index, non_finite.field, non_finite.count
+            )?;
+        }
+        for label in &self.labels_out_of_range {
+            writeln!(
+                f,
+                "sample {}, field {}: label {} out of range",
+                label.index, label.field, label.label
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A sample whose `get_sample` panicked.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SamplePanic {
+    /// Index of the sample.
+    pub index: usize,
+    /// Message of the panic.
+    pub message: String,
+}
+
+/// A field whose shape differs from the shape of most samples.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ShapeMismatch {
+    /// Index of the sample.
+    pub index: usize,
+    /// Position of the field in the sample.
+    pub field: usize,
+    /// Shape of the field in most samples.
+    pub expected: Vec<usize>,
+    /// Shape of the field in this sample.
+    pub found: Vec<usize>,
+}
+
+/// A field containing NaN or infinite values.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NonFinite {
+    /// Index of the sample.
+    pub index: usize,
+    /// Position of the field in the sample.
+    pub field: usize,
+    /// Number of NaN or infinite values.
+    pub count: usize,
+}
+
+/// A field with a label out of the allowed range.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LabelOutOfRange {
+    /// Index of the sample.
+    pub index: usize,
+    /// Position of the field in the sample.
+    pub field: usize,
+    /// The first label of the field out of the range.
+    pub label: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GetSample, Len};
+    use ndarray::{array, Array2};
+
+    #[test]
+    fn valid_dataset() {
+        let dataset: Vec<_> = (0..100)
+            .map(|i| (Array2::<f32>::zeros((3, 2)), i % 10))
+            .collect();
+        let report = Validator::new().label_range(1, 0..10).validate(&dataset);
+        assert!(report.is_ok(), "{report}");
+        assert_eq!(report.samples, 100);
+        assert!(validate(&vec![1., 2.]).is_ok());
+    }
+
+    #[test]
+    fn invalid_samples() {
+        let dataset = vec![
+            (array![[1., 2.]], vec![1, 2]),
+            (array![[1., f64::INFINITY]], vec![1, 2]),
+            (array![[f64::NAN, f64::NAN]], vec![1, 2, 3]),
+            (array![[1.], [2.]], vec![-1, 2]),
+            (array![[1., 2.]], vec![1, 2]),
+        ];
+        let report = Validator::new().label_range(1, 0..3).validate(&dataset);
+        assert!(!report.is_ok());
+        assert_eq!(
+            report.shape_mismatches,
+            vec![
+                ShapeMismatch {
+                    index: 2,
+                    field: 1,
+                    expected: vec![2],
+                    found: vec![3]
+                },
+                ShapeMismatch {
+                    index: 3,
+                    field: 0,
+                    expected: vec![1, 2],
+                    found: vec![2, 1]
+                },
+            ]
+        );
+        assert_eq!(
+            report.non_finite,
+            vec![
+                NonFinite {
+                    index: 1,
+                    field: 0,
+                    count: 1
+                },
+                NonFinite {
+                    index: 2,
+                    field: 0,
+                    count: 2
+                },
+            ]
+        );
+        assert_eq!(
+            report.labels_out_of_range,
+            vec![
+                LabelOutOfRange {
+                    index: 2,
+                    field: 1,
+                    label: 3
+                },
+                LabelOutOfRange {
+                    index: 3,
+                    field: 1,
+                    label: -1
+                },
+            ]
+        );
+        assert!(report.panics.is_empty());
+    }
+
+    struct Faulty;
+
+    impl Len for Faulty {
+        fn len(&self) -> usize {
+            4
+        }
+    }
+
+    impl Dataset for Faulty {}
+
+    impl GetSample for Faulty {
+        type Sample = (u8, f32);
+        fn get_sample(&self, index: usize) -> Self::Sample {
+            match index {
+                1 => panic!("corrupted sample {index}"),
+                3 => panic!("unreadable"),
+                _ => (1, 0.),
+            }
+        }
+    }
+
+    #[test]
+    fn catch_panics() {
+        let report = validate(&Faulty);
+        assert_eq!(report.samples, 4);
+        assert_eq!(
+            report.panics,
+            vec![
+                SamplePanic {
+                    index: 1,
+                    message: "corrupted sample 1".to_owned()
+                },
+                SamplePanic {
+                    index: 3,
+                    message: "unreadable".to_owned()
+                },
+            ]
+        );
+        assert!(report
+            .to_string()
+            .contains("sample 3: panicked: unreadable"));
+    }
+}
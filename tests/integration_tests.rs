@@ -1,3 +1,5 @@
+//! Integration tests of the indexable `DataLoader`.
+
 use ai_dataloader::indexable::DataLoader;
 use ndarray::array;
 